@@ -2,6 +2,7 @@ use criterion::BenchmarkId;
 use criterion::{criterion_group, criterion_main, Criterion};
 
 use std::fs::File;
+use std::io::Cursor;
 
 fn read_mp4(filename: &str) -> u64 {
     let f = File::open(filename).unwrap();
@@ -10,6 +11,64 @@ fn read_mp4(filename: &str) -> u64 {
     m.size()
 }
 
+fn read_samples(data: &[u8]) -> usize {
+    let mut m = mp4::Mp4Reader::read_header(Cursor::new(data), data.len() as u64).unwrap();
+    let mut total = 0;
+    for track_id in 1..=2 {
+        for sample_id in 1..=m.sample_count(track_id).unwrap() {
+            total += m
+                .read_sample(track_id, sample_id)
+                .unwrap()
+                .unwrap()
+                .bytes
+                .len();
+        }
+    }
+    total
+}
+
+fn read_sample_refs(data: &[u8]) -> usize {
+    let m = mp4::Mp4Reader::read_header(Cursor::new(data), data.len() as u64).unwrap();
+    let mut total = 0;
+    for track_id in 1..=2 {
+        for sample_id in 1..=m.sample_count(track_id).unwrap() {
+            total += m
+                .read_sample_ref(track_id, sample_id)
+                .unwrap()
+                .unwrap()
+                .bytes
+                .len();
+        }
+    }
+    total
+}
+
+// A file with long sample tables, where probing saves the most.
+fn long_file() -> Vec<u8> {
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("isom").unwrap(),
+        minor_version: 512,
+        compatible_brands: vec![str::parse("isom").unwrap()],
+        timescale: 1000,
+    };
+    let mut writer = mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for i in 0..100_000u64 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40 + (i % 2) as u32,
+            rendering_offset: 0,
+            is_sync: i % 60 == 0,
+            bytes: mp4::Bytes::from(vec![0; 1 + (i % 7) as usize]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    writer.into_writer().into_inner()
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let filename = "tests/samples/minimal.mp4";
 
@@ -20,6 +79,30 @@ fn criterion_benchmark(c: &mut Criterion) {
             b.iter(|| read_mp4(s));
         },
     );
+
+    let data = std::fs::read(filename).unwrap();
+    c.bench_with_input(
+        BenchmarkId::new("read_sample", filename),
+        &data,
+        |b, data| {
+            b.iter(|| read_samples(data));
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("read_sample_ref", filename),
+        &data,
+        |b, data| {
+            b.iter(|| read_sample_refs(data));
+        },
+    );
+
+    let data = long_file();
+    c.bench_with_input(BenchmarkId::new("read_header", "long"), &data, |b, data| {
+        b.iter(|| mp4::Mp4Reader::read_header(Cursor::new(data), data.len() as u64).unwrap());
+    });
+    c.bench_with_input(BenchmarkId::new("probe", "long"), &data, |b, data| {
+        b.iter(|| mp4::probe(&mut Cursor::new(data), data.len() as u64).unwrap());
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
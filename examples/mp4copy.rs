@@ -5,8 +5,8 @@ use std::io::{self, BufReader, BufWriter};
 use std::path::Path;
 
 use mp4::{
-    AacConfig, AvcConfig, HevcConfig, MediaConfig, MediaType, Mp4Config, Result, TrackConfig,
-    TtxtConfig, Vp9Config,
+    AvcConfig, FixedPointI8, HevcConfig, MediaConfig, MediaType, Mp4Config, Result, TrackConfig,
+    TtxtConfig,
 };
 
 fn main() {
@@ -53,25 +53,48 @@ fn copy<P: AsRef<Path>>(src_filename: &P, dst_filename: &P) -> Result<()> {
             MediaType::H265 => MediaConfig::HevcConfig(HevcConfig {
                 width: track.width(),
                 height: track.height(),
+                format: track
+                    .trak
+                    .mdia
+                    .minf
+                    .stbl
+                    .stsd
+                    .hev1
+                    .as_ref()
+                    .map(|hev1| hev1.format)
+                    .unwrap_or_default(),
             }),
-            MediaType::VP9 => MediaConfig::Vp9Config(Vp9Config {
-                width: track.width(),
-                height: track.height(),
-            }),
-            MediaType::AAC => MediaConfig::AacConfig(AacConfig {
-                bitrate: track.bitrate(),
-                profile: track.audio_profile()?,
-                freq_index: track.sample_freq_index()?,
-                chan_conf: track.channel_config()?,
-            }),
+            MediaType::VP9 => MediaConfig::Vp9Config(track.vp9_config()?),
+            MediaType::AAC => MediaConfig::AacConfig(track.aac_config()?),
             MediaType::TTXT => MediaConfig::TtxtConfig(TtxtConfig {}),
+            MediaType::PCM => MediaConfig::PcmConfig(track.pcm_config()?),
+            MediaType::AC4 => MediaConfig::Ac4Config(track.ac4_config()?),
+            MediaType::STPP => MediaConfig::StppConfig(track.stpp_config()?),
         };
 
+        let vmhd = track.trak.mdia.minf.vmhd.as_ref();
         let track_conf = TrackConfig {
             track_type: track.track_type()?,
             timescale: track.timescale(),
             language: track.language().to_string(),
             media_conf,
+            encoder_delay: 0,
+            balance: track
+                .trak
+                .mdia
+                .minf
+                .smhd
+                .as_ref()
+                .map(|smhd| smhd.balance)
+                .unwrap_or(FixedPointI8::new(0)),
+            graphics_mode: vmhd.map(|vmhd| vmhd.graphics_mode).unwrap_or_default(),
+            op_color: vmhd.map(|vmhd| vmhd.op_color.clone()).unwrap_or_default(),
+            extended_language: track.extended_language().map(String::from),
+            alternate_group: track.alternate_group(),
+            is_default: track.is_enabled(),
+            layer: track.layer(),
+            volume: track.volume(),
+            matrix: track.matrix().clone(),
         };
 
         mp4_writer.add_track(&track_conf)?;
@@ -87,6 +110,9 @@ fn copy<P: AsRef<Path>>(src_filename: &P, dst_filename: &P) -> Result<()> {
         }
     }
 
+    if let Some(meta) = &mp4_reader.meta {
+        mp4_writer.set_meta(meta.clone());
+    }
     mp4_writer.write_end()?;
 
     Ok(())
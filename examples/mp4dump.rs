@@ -56,7 +56,9 @@ fn get_boxes(file: File) -> Result<Vec<Box>> {
         if let Some(mehd) = &mvex.mehd {
             boxes.push(build_box(mehd));
         }
-        boxes.push(build_box(&mvex.trex));
+        for trex in mvex.trexs.iter() {
+            boxes.push(build_box(trex));
+        }
     }
 
     // trak.
@@ -99,6 +101,12 @@ fn get_boxes(file: File) -> Result<Vec<Box>> {
         if let Some(ref mp4a) = &stbl.stsd.mp4a {
             boxes.push(build_box(mp4a));
         }
+        if let Some(ref pcm) = &stbl.stsd.pcm {
+            boxes.push(build_box(pcm));
+        }
+        if let Some(ref ac4) = &stbl.stsd.ac4 {
+            boxes.push(build_box(ac4));
+        }
         boxes.push(build_box(&stbl.stts));
         if let Some(ref ctts) = &stbl.ctts {
             boxes.push(build_box(ctts));
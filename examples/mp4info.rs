@@ -75,7 +75,7 @@ fn video_info(track: &Mp4Track) -> Result<String> {
             track.width(),
             track.height(),
             track.bitrate() / 1000,
-            track.frame_rate()
+            track.frame_rate().unwrap_or(0.0)
         ))
     } else {
         Ok(format!(
@@ -85,7 +85,7 @@ fn video_info(track: &Mp4Track) -> Result<String> {
             track.width(),
             track.height(),
             track.bitrate() / 1000,
-            track.frame_rate()
+            track.frame_rate().unwrap_or(0.0)
         ))
     }
 }
@@ -120,6 +120,17 @@ fn audio_info(track: &Mp4Track) -> Result<String> {
                 track.bitrate() / 1000
             ))
         }
+    } else if track.trak.mdia.minf.stbl.stsd.pcm.is_some()
+        || track.trak.mdia.minf.stbl.stsd.ac4.is_some()
+    {
+        Ok(format!(
+            "{} ({:?}), {} Hz, {} channels, {} bits",
+            track.media_type()?,
+            track.box_type()?,
+            track.sample_rate()?,
+            track.channel_count()?,
+            track.bits_per_sample()?
+        ))
     } else {
         Err(Error::InvalidData("mp4a box not found"))
     }
@@ -0,0 +1,217 @@
+//! Human-readable dumps of a file's box structure.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::mp4box::avc1::AvcCBox;
+use crate::mp4box::dinf::{DrefBox, UrlBox};
+use crate::mp4box::hev1::HvcCBox;
+use crate::mp4box::mp4a::EsdsBox;
+use crate::mp4box::pcm::PcmCBox;
+use crate::mp4box::*;
+use crate::*;
+
+/// Print the box hierarchy of a file, one box per line, indented by depth,
+/// with each box's offset, size and [summary](Mp4Box::summary).
+///
+/// The structure is walked from the box headers in `reader`, so boxes the
+/// crate does not parse are listed too, without a summary. A box that fails to
+/// parse is listed with the error in place of its summary.
+///
+/// ```text
+/// [ftyp] offset=0 size=32 major_brand=isom minor_version=512 compatible_brands=isom-iso2-avc1-mp41
+/// [moov] offset=32 size=1273 traks=2
+///   [mvhd] offset=40 size=108 creation_time=0 timescale=1000 duration=62 ...
+///   [trak] offset=148 size=536
+///     [tkhd] offset=156 size=92 creation_time=0 track_id=1 duration=40 ...
+/// ```
+pub fn tree<R: Read + Seek, W: Write>(reader: &mut R, size: u64, writer: &mut W) -> Result<()> {
+    reader.seek(SeekFrom::Start(0))?;
+    dump_children(reader, 0, size, None, 0, writer)
+}
+
+// Number of bytes between the header of a container and its first child.
+fn children_offset(name: BoxType) -> Option<u64> {
+    match name {
+        BoxType::MoovBox
+        | BoxType::TrakBox
+        | BoxType::TaptBox
+        | BoxType::EdtsBox
+        | BoxType::MdiaBox
+        | BoxType::MinfBox
+        | BoxType::DinfBox
+        | BoxType::StblBox
+        | BoxType::MvexBox
+        | BoxType::MoofBox
+        | BoxType::TrafBox
+        | BoxType::UdtaBox
+        | BoxType::IlstBox => Some(0),
+        BoxType::StsdBox | BoxType::DrefBox => Some(HEADER_EXT_SIZE + 4),
+        BoxType::Mp4aBox
+        | BoxType::IpcmBox
+        | BoxType::FpcmBox
+        | BoxType::SowtBox
+        | BoxType::TwosBox
+        | BoxType::Ac4Box => Some(28),
+        BoxType::LpcmBox => Some(64),
+        BoxType::UrimBox => Some(8),
+        BoxType::Avc1Box | BoxType::Hev1Box | BoxType::Hvc1Box | BoxType::Vp09Box => Some(78),
+        _ => None,
+    }
+}
+
+fn dump_children<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+    parent: Option<BoxType>,
+    depth: usize,
+    writer: &mut W,
+) -> Result<()> {
+    for b in scan_child_boxes(reader, start, end)? {
+        reader.seek(SeekFrom::Start(b.body.start))?;
+        write!(
+            writer,
+            "{:indent$}[{}] offset={} size={}",
+            "",
+            b.name,
+            b.start,
+            b.size(),
+            indent = depth * 2
+        )?;
+        match summary(reader, b.name, b.read_size()) {
+            Some(Ok(s)) if !s.is_empty() => write!(writer, " {}", s)?,
+            Some(Err(err)) => write!(writer, " error=\"{}\"", err)?,
+            _ => {}
+        }
+        writeln!(writer)?;
+
+        // Items of an ilst are containers of data boxes, whatever their type.
+        let children = match parent {
+            Some(BoxType::IlstBox) => Some(0),
+            _ if b.name == BoxType::MetaBox => Some(meta_children_offset(reader, b.body.start)?),
+            _ => children_offset(b.name),
+        };
+        if let Some(children) = children {
+            let children_start = b.body.start + children;
+            let children_end = b.body.end.min(end);
+            if children_start <= children_end {
+                dump_children(
+                    reader,
+                    children_start,
+                    children_end,
+                    Some(b.name),
+                    depth + 1,
+                    writer,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// A meta box is a full box in ISO files but a plain container in QuickTime
+// files, so look for its hdlr to tell them apart.
+fn meta_children_offset<R: Read + Seek>(reader: &mut R, start: u64) -> Result<u64> {
+    reader.seek(SeekFrom::Start(start))?;
+    let mut buf = [0u8; 8];
+    if reader.read_exact(&mut buf).is_ok() && &buf[4..8] == b"hdlr" {
+        Ok(0)
+    } else {
+        Ok(HEADER_EXT_SIZE)
+    }
+}
+
+// Parse a box, with the reader positioned after its header, and summarize it.
+fn summary<R: Read + Seek>(reader: &mut R, name: BoxType, size: u64) -> Option<Result<String>> {
+    macro_rules! summarize {
+        ($box:ty) => {
+            Some(<$box>::read_box(&mut *reader, size).and_then(|b| Mp4Box::summary(&b)))
+        };
+    }
+
+    match name {
+        BoxType::FtypBox => summarize!(FtypBox),
+        BoxType::MoovBox => summarize!(MoovBox),
+        BoxType::MvhdBox => summarize!(MvhdBox),
+        BoxType::MvexBox => summarize!(MvexBox),
+        BoxType::MehdBox => summarize!(MehdBox),
+        BoxType::TrexBox => summarize!(TrexBox),
+        BoxType::TrepBox => summarize!(TrepBox),
+        BoxType::LevaBox => summarize!(LevaBox),
+        BoxType::EmsgBox => summarize!(EmsgBox),
+        BoxType::MoofBox => summarize!(MoofBox),
+        BoxType::MfhdBox => summarize!(MfhdBox),
+        BoxType::TrafBox => summarize!(TrafBox),
+        BoxType::TfhdBox => summarize!(TfhdBox),
+        BoxType::TfdtBox => summarize!(TfdtBox),
+        BoxType::TrunBox => summarize!(TrunBox),
+        BoxType::TrakBox => summarize!(TrakBox),
+        BoxType::TkhdBox => summarize!(TkhdBox),
+        BoxType::TaptBox => summarize!(TaptBox),
+        BoxType::ClefBox => summarize!(ClefBox),
+        BoxType::ProfBox => summarize!(ProfBox),
+        BoxType::EnofBox => summarize!(EnofBox),
+        BoxType::EdtsBox => summarize!(EdtsBox),
+        BoxType::ElstBox => summarize!(ElstBox),
+        BoxType::MdiaBox => summarize!(MdiaBox),
+        BoxType::MdhdBox => summarize!(MdhdBox),
+        BoxType::HdlrBox => summarize!(HdlrBox),
+        BoxType::ElngBox => summarize!(ElngBox),
+        BoxType::MinfBox => summarize!(MinfBox),
+        BoxType::VmhdBox => summarize!(VmhdBox),
+        BoxType::SmhdBox => summarize!(SmhdBox),
+        BoxType::NmhdBox => summarize!(NmhdBox),
+        BoxType::SthdBox => summarize!(SthdBox),
+        BoxType::DinfBox => summarize!(DinfBox),
+        BoxType::DrefBox => summarize!(DrefBox),
+        BoxType::UrlBox => summarize!(UrlBox),
+        BoxType::StblBox => summarize!(StblBox),
+        BoxType::StsdBox => summarize!(StsdBox),
+        BoxType::Avc1Box => summarize!(Avc1Box),
+        BoxType::AvcCBox => summarize!(AvcCBox),
+        BoxType::Hev1Box | BoxType::Hvc1Box => summarize!(Hev1Box),
+        BoxType::HvcCBox => summarize!(HvcCBox),
+        BoxType::Vp09Box => summarize!(Vp09Box),
+        BoxType::VpccBox => summarize!(VpccBox),
+        BoxType::MdcvBox => summarize!(MdcvBox),
+        BoxType::ClliBox => summarize!(ClliBox),
+        BoxType::AmveBox => summarize!(AmveBox),
+        BoxType::Mp4aBox => summarize!(Mp4aBox),
+        BoxType::EsdsBox => summarize!(EsdsBox),
+        BoxType::IpcmBox
+        | BoxType::FpcmBox
+        | BoxType::LpcmBox
+        | BoxType::SowtBox
+        | BoxType::TwosBox => summarize!(PcmBox),
+        BoxType::PcmCBox => summarize!(PcmCBox),
+        BoxType::Ac4Box => summarize!(Ac4Box),
+        BoxType::Dac4Box => summarize!(Dac4Box),
+        BoxType::Tx3gBox => summarize!(Tx3gBox),
+        BoxType::MettBox => summarize!(MettBox),
+        BoxType::MetxBox => summarize!(MetxBox),
+        BoxType::UrimBox => summarize!(UrimBox),
+        BoxType::UriBox => summarize!(UriBox),
+        BoxType::UriInitBox => summarize!(UriInitBox),
+        BoxType::MimeBox => summarize!(MimeBox),
+        BoxType::StppBox => summarize!(StppBox),
+        BoxType::StxtBox => summarize!(StxtBox),
+        BoxType::SttsBox => summarize!(SttsBox),
+        BoxType::CttsBox => summarize!(CttsBox),
+        BoxType::StssBox => summarize!(StssBox),
+        BoxType::SubsBox => summarize!(SubsBox),
+        BoxType::PadbBox => summarize!(PadbBox),
+        BoxType::SaizBox => summarize!(SaizBox),
+        BoxType::SaioBox => summarize!(SaioBox),
+        BoxType::StscBox => summarize!(StscBox),
+        BoxType::StszBox => summarize!(StszBox),
+        BoxType::StcoBox => summarize!(StcoBox),
+        BoxType::Co64Box => summarize!(Co64Box),
+        BoxType::UdtaBox => summarize!(UdtaBox),
+        BoxType::CprtBox => summarize!(CprtBox),
+        BoxType::MetaBox => summarize!(MetaBox),
+        BoxType::IlstBox => summarize!(IlstBox),
+        BoxType::KeysBox => summarize!(KeysBox),
+        BoxType::DataBox => summarize!(DataBox),
+        _ => None,
+    }
+}
@@ -66,7 +66,7 @@
 #![doc(html_root_url = "https://docs.rs/mp4/*")]
 
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor};
 
 mod error;
 pub use error::Error;
@@ -82,11 +82,25 @@ pub use mp4box::*;
 mod track;
 pub use track::{Mp4Track, TrackConfig};
 
+pub mod dump;
+
+pub mod package;
+
+pub mod recover;
+
+pub mod repack;
+
 mod reader;
-pub use reader::Mp4Reader;
+pub use reader::{probe_container, Mp4Reader};
+
+mod probe;
+pub use probe::{probe, MediaInfo, TrackInfo};
+
+mod stream;
+pub use stream::Mp4StreamReader;
 
 mod writer;
-pub use writer::{Mp4Config, Mp4Writer};
+pub use writer::{FMp4Writer, Mp4Config, Mp4Writer, Mp4WriterConfig};
 
 pub fn read_mp4(f: File) -> Result<Mp4Reader<BufReader<File>>> {
     let size = f.metadata()?.len();
@@ -94,3 +108,12 @@ pub fn read_mp4(f: File) -> Result<Mp4Reader<BufReader<File>>> {
     let mp4 = reader::Mp4Reader::read_header(reader, size)?;
     Ok(mp4)
 }
+
+/// Parse a file held in memory, as a fuzz target would.
+///
+/// Malformed input, such as a box whose size does not move the parser past
+/// its header, is reported as an error instead of hanging the parse.
+pub fn parse(bytes: &[u8]) -> Result<Mp4Reader<Cursor<&[u8]>>> {
+    let size = bytes.len() as u64;
+    Mp4Reader::read_header(Cursor::new(bytes), size)
+}
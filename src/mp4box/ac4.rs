@@ -0,0 +1,312 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::bits::BitReader;
+use crate::mp4box::*;
+
+/// Dolby AC-4 sample entry (ETSI TS 103 190-2 Annex E).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ac4Box {
+    pub data_reference_index: u16,
+    pub channelcount: u16,
+    pub samplesize: u16,
+    pub samplerate: u32,
+    pub dac4: Dac4Box,
+}
+
+impl Default for Ac4Box {
+    fn default() -> Self {
+        Self::new(&Ac4Config::default())
+    }
+}
+
+impl Ac4Box {
+    pub fn new(config: &Ac4Config) -> Self {
+        Ac4Box {
+            data_reference_index: 1,
+            channelcount: config.channel_count,
+            samplesize: 16,
+            // Higher rates are signalled as multiples of 48 kHz.
+            samplerate: if config.sample_rate == 44100 {
+                44100
+            } else {
+                48000
+            },
+            dac4: Dac4Box {
+                data: config.dsi.clone(),
+            },
+        }
+    }
+
+    pub fn get_type(&self) -> BoxType {
+        BoxType::Ac4Box
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8 + 20 + self.dac4.box_size()
+    }
+}
+
+impl Mp4Box for Ac4Box {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "channel_count={} sample_size={} sample_rate={}",
+            self.channelcount, self.samplesize, self.samplerate
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for Ac4Box {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        reader.read_u64::<BigEndian>()?; // reserved
+        let channelcount = reader.read_u16::<BigEndian>()?;
+        let samplesize = reader.read_u16::<BigEndian>()?;
+        reader.read_u32::<BigEndian>()?; // pre-defined, reserved
+        let samplerate = reader.read_u32::<BigEndian>()? >> 16;
+
+        let mut dac4 = None;
+        let end = start + size;
+        while reader.stream_position()? + HEADER_SIZE <= end {
+            let current = reader.stream_position()?;
+            let BoxHeader { name, size: s, .. } = BoxHeader::read(reader)?;
+            if s < HEADER_SIZE || current + s > end {
+                return Err(Error::InvalidData(
+                    "ac-4 box contains a box with a larger size than it",
+                ));
+            }
+            if name == BoxType::Dac4Box {
+                dac4 = Some(Dac4Box::read_box(reader, s)?);
+            }
+            skip_bytes_to(reader, current + s)?;
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(Ac4Box {
+            data_reference_index,
+            channelcount,
+            samplesize,
+            samplerate,
+            dac4: dac4.ok_or(Error::BoxNotFound(BoxType::Dac4Box))?,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for Ac4Box {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if self.samplerate > 0xFFFF {
+            return Err(Error::InvalidData(
+                "sample rate too high for a version 0 audio sample entry",
+            ));
+        }
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        writer.write_u64::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.channelcount)?;
+        writer.write_u16::<BigEndian>(self.samplesize)?;
+        writer.write_u32::<BigEndian>(0)?; // pre-defined, reserved
+        writer.write_u32::<BigEndian>(self.samplerate << 16)?;
+
+        self.dac4.write_box(writer)?;
+
+        Ok(size)
+    }
+}
+
+/// The AC-4 decoder specific information, kept as the raw `ac4_dsi_v1`
+/// bytes so that its presentations are written back unchanged. The fields
+/// preceding the presentations are available from [Dac4Box::dsi].
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct Dac4Box {
+    #[serde(skip_serializing)]
+    pub data: Vec<u8>,
+}
+
+/// The leading fields of an `ac4_dsi_v1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ac4Dsi {
+    pub ac4_dsi_version: u8,
+    pub bitstream_version: u8,
+    pub fs_index: u8,
+    pub frame_rate_index: u8,
+    pub n_presentations: u16,
+
+    /// The `ac4_bitrate_dsi`, present from `ac4_dsi_version` 1.
+    pub bit_rate_mode: Option<u8>,
+    /// Bits per second, 0 if unknown.
+    pub bit_rate: Option<u32>,
+}
+
+impl Ac4Dsi {
+    /// The base sampling frequency, 44100 or 48000 Hz.
+    pub fn sampling_frequency(&self) -> u32 {
+        if self.fs_index == 0 {
+            44100
+        } else {
+            48000
+        }
+    }
+}
+
+impl Dac4Box {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::Dac4Box
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + self.data.len() as u64
+    }
+
+    pub fn dsi(&self) -> Result<Ac4Dsi> {
+        let mut bits = BitReader::new(&self.data, "dac4 box is truncated");
+        let ac4_dsi_version = bits.read(3)? as u8;
+        let bitstream_version = bits.read(7)? as u8;
+        let fs_index = bits.read(1)? as u8;
+        let frame_rate_index = bits.read(4)? as u8;
+        let n_presentations = bits.read(9)? as u16;
+
+        let mut bit_rate_mode = None;
+        let mut bit_rate = None;
+        if ac4_dsi_version >= 1 {
+            if bitstream_version > 1 && bits.read(1)? == 1 {
+                bits.read(16)?; // short_program_id
+                if bits.read(1)? == 1 {
+                    bits.skip(128)?; // program_uuid
+                }
+            }
+            bit_rate_mode = Some(bits.read(2)? as u8);
+            bit_rate = Some(bits.read(32)?);
+        }
+
+        Ok(Ac4Dsi {
+            ac4_dsi_version,
+            bitstream_version,
+            fs_index,
+            frame_rate_index,
+            n_presentations,
+            bit_rate_mode,
+            bit_rate,
+        })
+    }
+}
+
+impl Mp4Box for Dac4Box {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = match self.dsi() {
+            Ok(dsi) => format!(
+                "ac4_dsi_version={} bitstream_version={} sampling_frequency={} n_presentations={}",
+                dsi.ac4_dsi_version,
+                dsi.bitstream_version,
+                dsi.sampling_frequency(),
+                dsi.n_presentations
+            ),
+            Err(_) => format!("size={}", self.data.len()),
+        };
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for Dac4Box {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut data = vec![0; size.saturating_sub(HEADER_SIZE) as usize];
+        reader.read_exact(&mut data)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Dac4Box { data })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for Dac4Box {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_all(&self.data)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ac4() {
+        // ac4_dsi_v1 for bitstream version 2 at 48 kHz with one presentation
+        // and a 64 kb/s bit rate, followed by made-up presentation bytes.
+        let dsi = vec![
+            0x20, 0xA6, 0x01, 0x20, 0x00, 0x1F, 0x40, 0x1F, 0xFF, 0xFF, 0xFF, 0xE0, 0x01, 0x02,
+            0x03,
+        ];
+        let src_box = Ac4Box::new(&Ac4Config {
+            sample_rate: 48000,
+            channel_count: 6,
+            dsi: dsi.clone(),
+        });
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::Ac4Box);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = Ac4Box::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.dac4.data, dsi);
+        assert_eq!(
+            dst_box.dac4.dsi().unwrap(),
+            Ac4Dsi {
+                ac4_dsi_version: 1,
+                bitstream_version: 2,
+                fs_index: 1,
+                frame_rate_index: 3,
+                n_presentations: 1,
+                bit_rate_mode: Some(1),
+                bit_rate: Some(64000),
+            }
+        );
+    }
+}
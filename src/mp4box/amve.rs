@@ -0,0 +1,107 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Ambient viewing environment of HDR video, as in the H.274 SEI message.
+///
+/// The illuminance is in units of 0.0001 lux and the chromaticity
+/// coordinates in units of 0.00002.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct AmveBox {
+    pub ambient_illuminance: u32,
+    pub ambient_light_x: u16,
+    pub ambient_light_y: u16,
+}
+
+impl AmveBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::AmveBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8
+    }
+}
+
+impl Mp4Box for AmveBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "ambient_illuminance={} ambient_light_x={} ambient_light_y={}",
+            self.ambient_illuminance, self.ambient_light_x, self.ambient_light_y
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for AmveBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let ambient_illuminance = reader.read_u32::<BigEndian>()?;
+        let ambient_light_x = reader.read_u16::<BigEndian>()?;
+        let ambient_light_y = reader.read_u16::<BigEndian>()?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(AmveBox {
+            ambient_illuminance,
+            ambient_light_x,
+            ambient_light_y,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for AmveBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(self.ambient_illuminance)?;
+        writer.write_u16::<BigEndian>(self.ambient_light_x)?;
+        writer.write_u16::<BigEndian>(self.ambient_light_y)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_amve() {
+        // 314 lux at the D65 white point.
+        let src_box = AmveBox {
+            ambient_illuminance: 3_140_000,
+            ambient_light_x: 15635,
+            ambient_light_y: 16450,
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::AmveBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = AmveBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
@@ -18,6 +18,15 @@ pub struct Avc1Box {
     pub frame_count: u16,
     pub depth: u16,
     pub avcc: AvcCBox,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mdcv: Option<MdcvBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clli: Option<ClliBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amve: Option<AmveBox>,
 }
 
 impl Default for Avc1Box {
@@ -31,6 +40,9 @@ impl Default for Avc1Box {
             frame_count: 1,
             depth: 0x0018,
             avcc: AvcCBox::default(),
+            mdcv: None,
+            clli: None,
+            amve: None,
         }
     }
 }
@@ -46,6 +58,9 @@ impl Avc1Box {
             frame_count: 1,
             depth: 0x0018,
             avcc: AvcCBox::new(&config.seq_param_set, &config.pic_param_set),
+            mdcv: None,
+            clli: None,
+            amve: None,
         }
     }
 
@@ -54,7 +69,11 @@ impl Avc1Box {
     }
 
     pub fn get_size(&self) -> u64 {
-        HEADER_SIZE + 8 + 70 + self.avcc.box_size()
+        HEADER_SIZE
+            + 8
+            + 70
+            + self.avcc.box_size()
+            + hdr_boxes_size(&self.mdcv, &self.clli, &self.amve)
     }
 }
 
@@ -101,38 +120,48 @@ impl<R: Read + Seek> ReadBox<&mut R> for Avc1Box {
         let depth = reader.read_u16::<BigEndian>()?;
         reader.read_i16::<BigEndian>()?; // pre-defined
 
+        let mut avcc = None;
+        let mut mdcv = None;
+        let mut clli = None;
+        let mut amve = None;
+
         let end = start + size;
-        loop {
-            let current = reader.stream_position()?;
-            if current >= end {
-                return Err(Error::InvalidData("avcc not found"));
-            }
+        let mut current = reader.stream_position()?;
+        while current + HEADER_SIZE <= end {
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "avc1 box contains a box with a larger size than it",
                 ));
             }
-            if name == BoxType::AvcCBox {
-                let avcc = AvcCBox::read_box(reader, s)?;
-
-                skip_bytes_to(reader, start + size)?;
-
-                return Ok(Avc1Box {
-                    data_reference_index,
-                    width,
-                    height,
-                    horizresolution,
-                    vertresolution,
-                    frame_count,
-                    depth,
-                    avcc,
-                });
-            } else {
-                skip_bytes_to(reader, current + s)?;
+            match name {
+                BoxType::AvcCBox => avcc = Some(AvcCBox::read_box(reader, s)?),
+                BoxType::MdcvBox => mdcv = Some(MdcvBox::read_box(reader, s)?),
+                BoxType::ClliBox => clli = Some(ClliBox::read_box(reader, s)?),
+                BoxType::AmveBox => amve = Some(AmveBox::read_box(reader, s)?),
+                _ => skip_bytes_to(reader, current + s)?,
             }
+            current = next_box_position(reader, current)?;
         }
+
+        let avcc = avcc.ok_or(Error::InvalidData("avcc not found"))?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(Avc1Box {
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            depth,
+            avcc,
+            mdcv,
+            clli,
+            amve,
+        })
     }
 }
 
@@ -160,6 +189,7 @@ impl<W: Write> WriteBox<&mut W> for Avc1Box {
         writer.write_i16::<BigEndian>(-1)?; // pre-defined
 
         self.avcc.write_box(writer)?;
+        write_hdr_boxes(writer, &self.mdcv, &self.clli, &self.amve)?;
 
         Ok(size)
     }
@@ -338,6 +368,9 @@ mod tests {
                     bytes: vec![0x68, 0xEB, 0xE3, 0xCB, 0x22, 0xC0],
                 }],
             },
+            mdcv: None,
+            clli: None,
+            amve: None,
         };
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
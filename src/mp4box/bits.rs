@@ -0,0 +1,96 @@
+use crate::mp4box::*;
+
+/// Reader of the MSB-first bit fields of codec configuration records.
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+
+    // The error returned when reading past the end of the data.
+    truncated: &'static str,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8], truncated: &'static str) -> Self {
+        Self {
+            data,
+            pos: 0,
+            truncated,
+        }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    pub(crate) fn read(&mut self, n: usize) -> Result<u32> {
+        if n > self.remaining() {
+            return Err(Error::InvalidData(self.truncated));
+        }
+        let mut value = 0u32;
+        for _ in 0..n {
+            let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn skip(&mut self, n: usize) -> Result<()> {
+        if n > self.remaining() {
+            return Err(Error::InvalidData(self.truncated));
+        }
+        self.pos += n;
+        Ok(())
+    }
+}
+
+/// Writer of MSB-first bit fields, padding the last byte with zeros.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl BitWriter {
+    pub(crate) fn write(&mut self, value: u32, n: usize) {
+        for i in (0..n).rev() {
+            if self.pos.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.data.last_mut().unwrap() |= bit << (7 - self.pos % 8);
+            self.pos += 1;
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_reader_writer() {
+        let mut writer = BitWriter::default();
+        writer.write(0b101, 3);
+        writer.write(0x1234, 16);
+        writer.write(1, 1);
+        let data = writer.into_bytes();
+        assert_eq!(data, vec![0b1010_0010, 0b0100_0110, 0b1001_0000]);
+
+        let mut reader = BitReader::new(&data, "truncated");
+        assert_eq!(reader.read(3).unwrap(), 0b101);
+        assert_eq!(reader.read(16).unwrap(), 0x1234);
+        reader.skip(1).unwrap();
+        assert_eq!(reader.remaining(), 4);
+        assert_eq!(reader.read(4).unwrap(), 0);
+        assert!(matches!(
+            reader.read(1),
+            Err(Error::InvalidData("truncated"))
+        ));
+        assert!(reader.skip(1).is_err());
+    }
+}
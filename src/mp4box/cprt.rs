@@ -0,0 +1,220 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::mdhd::{language_code, language_string};
+use crate::mp4box::*;
+
+/// A copyright notice in one language.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct CprtBox {
+    pub version: u8,
+    pub flags: u32,
+
+    /// The ISO 639-2/T language code, packed as in the `mdhd`.
+    pub language: u16,
+    pub notice: String,
+
+    /// Whether the notice is written as UTF-16 with a byte order mark
+    /// rather than as UTF-8.
+    pub utf16: bool,
+}
+
+impl CprtBox {
+    pub fn new(language: &str, notice: &str) -> Self {
+        CprtBox {
+            language: language_code(language),
+            notice: String::from(notice),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_type(&self) -> BoxType {
+        BoxType::CprtBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let notice_size = if self.utf16 {
+            2 * (self.notice.encode_utf16().count() as u64 + 2)
+        } else {
+            self.notice.len() as u64 + 1
+        };
+        HEADER_SIZE + HEADER_EXT_SIZE + 2 + notice_size
+    }
+
+    /// The language of the notice, such as `eng`.
+    pub fn language_string(&self) -> String {
+        language_string(self.language)
+    }
+}
+
+impl Mp4Box for CprtBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("language={} notice={}", self.language_string(), self.notice);
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for CprtBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let language = reader.read_u16::<BigEndian>()?;
+
+        let buf_size = size
+            .checked_sub(HEADER_SIZE + HEADER_EXT_SIZE + 2)
+            .ok_or(Error::InvalidData("cprt size too small"))?;
+        let mut buf = vec![0u8; buf_size as usize];
+        reader.read_exact(&mut buf)?;
+
+        let (notice, utf16) = match buf.get(..2) {
+            Some([0xFE, 0xFF]) | Some([0xFF, 0xFE]) => {
+                let little_endian = buf[0] == 0xFF;
+                let units: Vec<u16> = buf[2..]
+                    .chunks_exact(2)
+                    .map(|b| {
+                        if little_endian {
+                            u16::from_le_bytes([b[0], b[1]])
+                        } else {
+                            u16::from_be_bytes([b[0], b[1]])
+                        }
+                    })
+                    .take_while(|&unit| unit != 0)
+                    .collect();
+                (String::from_utf16_lossy(&units), true)
+            }
+            _ => {
+                if let Some(end) = buf.iter().position(|&b| b == b'\0') {
+                    buf.truncate(end);
+                }
+                (String::from_utf8_lossy(&buf).into_owned(), false)
+            }
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(CprtBox {
+            version,
+            flags,
+            language,
+            notice,
+            utf16,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for CprtBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        writer.write_u16::<BigEndian>(self.language)?;
+
+        if self.utf16 {
+            writer.write_u16::<BigEndian>(0xFEFF)?;
+            for unit in self.notice.encode_utf16() {
+                writer.write_u16::<BigEndian>(unit)?;
+            }
+            writer.write_u16::<BigEndian>(0)?;
+        } else {
+            writer.write_all(self.notice.as_bytes())?;
+            writer.write_u8(0)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_cprt() {
+        let src_box = CprtBox::new("eng", "(c) 2024 Example");
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::CprtBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = CprtBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.language_string(), "eng");
+    }
+
+    #[test]
+    fn test_cprt_utf16() {
+        // "© Société" in little-endian UTF-16, as some writers produce it.
+        let mut buf = vec![0, 0, 0, 0, b'c', b'p', b'r', b't', 0, 0, 0, 0];
+        buf.extend_from_slice(&language_code("fra").to_be_bytes());
+        buf.extend_from_slice(&[0xFF, 0xFE]);
+        for unit in "© Société".encode_utf16() {
+            buf.extend_from_slice(&unit.to_le_bytes());
+        }
+        buf.extend_from_slice(&[0, 0]);
+        let size = buf.len() as u32;
+        buf[..4].copy_from_slice(&size.to_be_bytes());
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let dst_box = CprtBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(dst_box.language_string(), "fra");
+        assert_eq!(dst_box.notice, "© Société");
+        assert!(dst_box.utf16);
+
+        // Written back in big-endian UTF-16.
+        let mut out = Vec::new();
+        dst_box.write_box(&mut out).unwrap();
+        assert_eq!(out.len(), buf.len());
+        assert_eq!(&out[14..16], &[0xFE, 0xFF]);
+        let mut reader = Cursor::new(&out);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(
+            CprtBox::read_box(&mut reader, header.size).unwrap(),
+            dst_box
+        );
+    }
+
+    #[test]
+    fn test_cprt_invalid_text() {
+        // A notice in Latin-1 rather than UTF-8, and a lone UTF-16 surrogate.
+        for (notice, expected) in [
+            (&b"(c) Soci\xe9t\xe9\0"[..], "(c) Soci\u{FFFD}t\u{FFFD}"),
+            (
+                &[0xFE, 0xFF, 0x00, 0x41, 0xD8, 0x00, 0x00, 0x00][..],
+                "A\u{FFFD}",
+            ),
+        ] {
+            let mut buf = vec![0, 0, 0, 0, b'c', b'p', b'r', b't', 0, 0, 0, 0];
+            buf.extend_from_slice(&language_code("fra").to_be_bytes());
+            buf.extend_from_slice(notice);
+            let size = buf.len() as u32;
+            buf[..4].copy_from_slice(&size.to_be_bytes());
+
+            let mut reader = Cursor::new(&buf);
+            let header = BoxHeader::read(&mut reader).unwrap();
+            let dst_box = CprtBox::read_box(&mut reader, header.size).unwrap();
+            assert_eq!(dst_box.notice, expected);
+        }
+    }
+}
@@ -48,7 +48,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "dinf box contains a box with a larger size than it",
@@ -65,7 +65,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if dref.is_none() {
@@ -160,7 +160,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for DrefBox {
 
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "dinf box contains a box with a larger size than it",
@@ -176,7 +176,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for DrefBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         skip_bytes_to(reader, start + size)?;
@@ -232,7 +232,7 @@ impl UrlBox {
         let mut size = HEADER_SIZE + HEADER_EXT_SIZE;
 
         if !self.location.is_empty() {
-            size += self.location.bytes().len() as u64 + 1;
+            size += self.location.len() as u64 + 1;
         }
 
         size
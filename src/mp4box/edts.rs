@@ -53,7 +53,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for EdtsBox {
         let mut edts = EdtsBox::new();
 
         let header = BoxHeader::read(reader)?;
-        let BoxHeader { name, size: s } = header;
+        let BoxHeader { name, size: s, .. } = header;
         if s > size {
             return Err(Error::InvalidData(
                 "edts box contains a box with a larger size than it",
@@ -1,5 +1,6 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use serde::Serialize;
+use std::convert::TryFrom;
 use std::io::{Read, Seek, Write};
 use std::mem::size_of;
 
@@ -7,6 +8,8 @@ use crate::mp4box::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct ElstBox {
+    /// 0 for 32-bit durations and media times, 1 for 64-bit ones. Version 1
+    /// is written whenever an entry does not fit in 32 bits.
     pub version: u8,
     pub flags: u32,
 
@@ -17,6 +20,9 @@ pub struct ElstBox {
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct ElstEntry {
     pub segment_duration: u64,
+
+    /// A signed value stored as its two's complement, so that the -1 of an
+    /// empty edit reads as `u64::MAX` in both versions.
     pub media_time: u64,
     pub media_rate: u16,
     pub media_rate_fraction: u16,
@@ -29,13 +35,26 @@ impl ElstBox {
 
     pub fn get_size(&self) -> u64 {
         let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 4;
-        if self.version == 1 {
+        if self.write_version() == 1 {
             size += self.entries.len() as u64 * 20;
-        } else if self.version == 0 {
+        } else {
             size += self.entries.len() as u64 * 12;
         }
         size
     }
+
+    // The version written: 1 if asked for or if any entry needs it.
+    fn write_version(&self) -> u8 {
+        let fits_32_bits = |entry: &ElstEntry| {
+            entry.segment_duration <= u32::MAX as u64
+                && i32::try_from(entry.media_time as i64).is_ok()
+        };
+        if self.version == 1 || !self.entries.iter().all(fits_32_bits) {
+            1
+        } else {
+            self.version
+        }
+    }
 }
 
 impl Mp4Box for ElstBox {
@@ -96,7 +115,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for ElstBox {
             } else {
                 (
                     reader.read_u32::<BigEndian>()? as u64,
-                    reader.read_u32::<BigEndian>()? as u64,
+                    reader.read_i32::<BigEndian>()? as i64 as u64,
                 )
             };
 
@@ -124,11 +143,12 @@ impl<W: Write> WriteBox<&mut W> for ElstBox {
         let size = self.box_size();
         BoxHeader::new(self.box_type(), size).write(writer)?;
 
-        write_box_header_ext(writer, self.version, self.flags)?;
+        let version = self.write_version();
+        write_box_header_ext(writer, version, self.flags)?;
 
         writer.write_u32::<BigEndian>(self.entries.len() as u32)?;
         for entry in self.entries.iter() {
-            if self.version == 1 {
+            if version == 1 {
                 writer.write_u64::<BigEndian>(entry.segment_duration)?;
                 writer.write_u64::<BigEndian>(entry.media_time)?;
             } else {
@@ -198,4 +218,48 @@ mod tests {
         let dst_box = ElstBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(src_box, dst_box);
     }
+
+    #[test]
+    fn test_elst_version_selection() {
+        // An empty edit, then a segment longer than 32 bits allow.
+        let mut src_box = ElstBox {
+            version: 0,
+            flags: 0,
+            entries: vec![
+                ElstEntry {
+                    segment_duration: 1000,
+                    media_time: u64::MAX,
+                    media_rate: 1,
+                    media_rate_fraction: 0,
+                },
+                ElstEntry {
+                    segment_duration: 0x1_2345_6789,
+                    media_time: 0x1_0000_0000,
+                    media_rate: 1,
+                    media_rate_fraction: 0,
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let dst_box = ElstBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(dst_box.version, 1);
+        assert_eq!(dst_box.entries, src_box.entries);
+
+        // The empty edit alone fits version 0.
+        src_box.entries.truncate(1);
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+        assert_eq!(buf[8], 0);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let dst_box = ElstBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
 }
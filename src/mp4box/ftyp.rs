@@ -19,6 +19,19 @@ impl FtypBox {
     pub fn get_size(&self) -> u64 {
         HEADER_SIZE + 8 + (4 * self.compatible_brands.len() as u64)
     }
+
+    pub fn major_brand_is<B: Into<FourCC>>(&self, brand: B) -> bool {
+        self.major_brand == brand.into()
+    }
+
+    pub fn has_brand<B: Into<FourCC>>(&self, brand: B) -> bool {
+        let brand = brand.into();
+        self.major_brand == brand || self.compatible_brands.contains(&brand)
+    }
+
+    pub fn container_kind(&self) -> ContainerKind {
+        ContainerKind::from_brands(&self.major_brand, &self.compatible_brands)
+    }
 }
 
 impl Mp4Box for FtypBox {
@@ -53,7 +66,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for FtypBox {
     fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
 
-        if size < 16 || size % 4 != 0 {
+        if size < 16 || !size.is_multiple_of(4) {
             return Err(Error::InvalidData("ftyp size too small or not aligned"));
         }
         let brand_count = (size - 16) / 4; // header + major + minor
@@ -120,4 +133,41 @@ mod tests {
         let dst_box = FtypBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(src_box, dst_box);
     }
+
+    #[test]
+    fn test_ftyp_container_kind() {
+        let ftyp = FtypBox {
+            major_brand: str::parse("avif").unwrap(),
+            minor_version: 0,
+            compatible_brands: vec![
+                str::parse("avif").unwrap(),
+                str::parse("mif1").unwrap(),
+                str::parse("miaf").unwrap(),
+            ],
+        };
+        assert!(ftyp.major_brand_is(*b"avif"));
+        assert!(ftyp.has_brand(*b"mif1"));
+        assert_eq!(ftyp.container_kind(), ContainerKind::Avif);
+
+        let ftyp = FtypBox {
+            major_brand: str::parse("heic").unwrap(),
+            minor_version: 0,
+            compatible_brands: vec![str::parse("mif1").unwrap()],
+        };
+        assert_eq!(ftyp.container_kind(), ContainerKind::Heif);
+
+        let ftyp = FtypBox {
+            major_brand: str::parse("iso6").unwrap(),
+            minor_version: 0,
+            compatible_brands: vec![str::parse("dash").unwrap()],
+        };
+        assert_eq!(ftyp.container_kind(), ContainerKind::FragmentedMp4);
+
+        let ftyp = FtypBox {
+            major_brand: str::parse("isom").unwrap(),
+            minor_version: 512,
+            compatible_brands: vec![str::parse("mp41").unwrap()],
+        };
+        assert_eq!(ftyp.container_kind(), ContainerKind::Mp4);
+    }
 }
@@ -4,8 +4,37 @@ use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
 
+/// The sample entry types of HEVC video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum HevcFormat {
+    /// Parameter sets are in the `hvcC` and may also be in the samples.
+    #[default]
+    Hev1,
+    /// Parameter sets are only in the `hvcC`.
+    Hvc1,
+}
+
+impl HevcFormat {
+    pub fn box_type(&self) -> BoxType {
+        match self {
+            HevcFormat::Hev1 => BoxType::Hev1Box,
+            HevcFormat::Hvc1 => BoxType::Hvc1Box,
+        }
+    }
+
+    pub fn from_box_type(box_type: BoxType) -> Option<Self> {
+        match box_type {
+            BoxType::Hev1Box => Some(HevcFormat::Hev1),
+            BoxType::Hvc1Box => Some(HevcFormat::Hvc1),
+            _ => None,
+        }
+    }
+}
+
+/// An HEVC sample entry, `hev1` or `hvc1` as given by `format`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Hev1Box {
+    pub format: HevcFormat,
     pub data_reference_index: u16,
     pub width: u16,
     pub height: u16,
@@ -18,11 +47,21 @@ pub struct Hev1Box {
     pub frame_count: u16,
     pub depth: u16,
     pub hvcc: HvcCBox,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mdcv: Option<MdcvBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clli: Option<ClliBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amve: Option<AmveBox>,
 }
 
 impl Default for Hev1Box {
     fn default() -> Self {
         Hev1Box {
+            format: HevcFormat::Hev1,
             data_reference_index: 0,
             width: 0,
             height: 0,
@@ -31,6 +70,9 @@ impl Default for Hev1Box {
             frame_count: 1,
             depth: 0x0018,
             hvcc: HvcCBox::default(),
+            mdcv: None,
+            clli: None,
+            amve: None,
         }
     }
 }
@@ -38,6 +80,7 @@ impl Default for Hev1Box {
 impl Hev1Box {
     pub fn new(config: &HevcConfig) -> Self {
         Hev1Box {
+            format: config.format,
             data_reference_index: 1,
             width: config.width,
             height: config.height,
@@ -46,15 +89,50 @@ impl Hev1Box {
             frame_count: 1,
             depth: 0x0018,
             hvcc: HvcCBox::new(),
+            mdcv: None,
+            clli: None,
+            amve: None,
         }
     }
 
     pub fn get_type(&self) -> BoxType {
-        BoxType::Hev1Box
+        self.format.box_type()
     }
 
     pub fn get_size(&self) -> u64 {
-        HEADER_SIZE + 8 + 70 + self.hvcc.box_size()
+        HEADER_SIZE
+            + 8
+            + 70
+            + self.hvcc.box_size()
+            + hdr_boxes_size(&self.mdcv, &self.clli, &self.amve)
+    }
+
+    /// The RFC 6381 `codecs` parameter of the entry, such as
+    /// `hvc1.1.6.L93.B0`, as specified by ISO/IEC 14496-15 Annex E.
+    pub fn codec_string(&self) -> String {
+        let hvcc = &self.hvcc;
+        let mut s = format!("{}.", FourCC::from(self.get_type()));
+        if hvcc.general_profile_space > 0 {
+            s.push((b'A' + hvcc.general_profile_space - 1) as char);
+        }
+        s += &format!(
+            "{}.{:X}.{}{}",
+            hvcc.general_profile_idc,
+            hvcc.general_profile_compatibility_flags.reverse_bits(),
+            if hvcc.general_tier_flag { 'H' } else { 'L' },
+            hvcc.general_level_idc
+        );
+
+        // Six bytes of constraint flags, without the trailing zero bytes.
+        let constraints = &hvcc.general_constraint_indicator_flag.to_be_bytes()[2..];
+        let len = constraints
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |i| i + 1);
+        for byte in &constraints[..len] {
+            s += &format!(".{:X}", byte);
+        }
+        s
     }
 }
 
@@ -81,7 +159,20 @@ impl Mp4Box for Hev1Box {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for Hev1Box {
+    /// Read an `hev1` box; see [Hev1Box::read_entry] for `hvc1`.
     fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        Hev1Box::read_entry(reader, HevcFormat::Hev1, size)
+    }
+}
+
+impl Hev1Box {
+    /// Read a sample entry of the type given by `format`, with the reader
+    /// positioned after its header.
+    pub fn read_entry<R: Read + Seek>(
+        reader: &mut R,
+        format: HevcFormat,
+        size: u64,
+    ) -> Result<Self> {
         let start = box_start(reader)?;
 
         reader.read_u32::<BigEndian>()?; // reserved
@@ -101,31 +192,49 @@ impl<R: Read + Seek> ReadBox<&mut R> for Hev1Box {
         let depth = reader.read_u16::<BigEndian>()?;
         reader.read_i16::<BigEndian>()?; // pre-defined
 
-        let header = BoxHeader::read(reader)?;
-        let BoxHeader { name, size: s } = header;
-        if s > size {
-            return Err(Error::InvalidData(
-                "hev1 box contains a box with a larger size than it",
-            ));
-        }
-        if name == BoxType::HvcCBox {
-            let hvcc = HvcCBox::read_box(reader, s)?;
-
-            skip_bytes_to(reader, start + size)?;
-
-            Ok(Hev1Box {
-                data_reference_index,
-                width,
-                height,
-                horizresolution,
-                vertresolution,
-                frame_count,
-                depth,
-                hvcc,
-            })
-        } else {
-            Err(Error::InvalidData("hvcc not found"))
+        let mut hvcc = None;
+        let mut mdcv = None;
+        let mut clli = None;
+        let mut amve = None;
+
+        let end = start + size;
+        let mut current = reader.stream_position()?;
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            let BoxHeader { name, size: s, .. } = header;
+            if s > size {
+                return Err(Error::InvalidData(
+                    "hev1 box contains a box with a larger size than it",
+                ));
+            }
+            match name {
+                BoxType::HvcCBox => hvcc = Some(HvcCBox::read_box(reader, s)?),
+                BoxType::MdcvBox => mdcv = Some(MdcvBox::read_box(reader, s)?),
+                BoxType::ClliBox => clli = Some(ClliBox::read_box(reader, s)?),
+                BoxType::AmveBox => amve = Some(AmveBox::read_box(reader, s)?),
+                _ => skip_bytes_to(reader, current + s)?,
+            }
+            current = next_box_position(reader, current)?;
         }
+
+        let hvcc = hvcc.ok_or(Error::InvalidData("hvcc not found"))?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(Hev1Box {
+            format,
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            depth,
+            hvcc,
+            mdcv,
+            clli,
+            amve,
+        })
     }
 }
 
@@ -153,6 +262,7 @@ impl<W: Write> WriteBox<&mut W> for Hev1Box {
         writer.write_i16::<BigEndian>(-1)?; // pre-defined
 
         self.hvcc.write_box(writer)?;
+        write_hdr_boxes(writer, &self.mdcv, &self.clli, &self.amve)?;
 
         Ok(size)
     }
@@ -248,8 +358,8 @@ impl<R: Read + Seek> ReadBox<&mut R> for HvcCBox {
     fn read_box(reader: &mut R, _size: u64) -> Result<Self> {
         let configuration_version = reader.read_u8()?;
         let params = reader.read_u8()?;
-        let general_profile_space = params & 0b11000000 >> 6;
-        let general_tier_flag = (params & 0b00100000 >> 5) > 0;
+        let general_profile_space = (params & 0b11000000) >> 6;
+        let general_tier_flag = (params & 0b00100000) >> 5 > 0;
         let general_profile_idc = params & 0b00011111;
 
         let general_profile_compatibility_flags = reader.read_u32::<BigEndian>()?;
@@ -368,6 +478,7 @@ mod tests {
     #[test]
     fn test_hev1() {
         let src_box = Hev1Box {
+            format: HevcFormat::Hev1,
             data_reference_index: 1,
             width: 320,
             height: 240,
@@ -379,6 +490,9 @@ mod tests {
                 configuration_version: 1,
                 ..Default::default()
             },
+            mdcv: None,
+            clli: None,
+            amve: None,
         };
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
@@ -389,7 +503,95 @@ mod tests {
         assert_eq!(header.name, BoxType::Hev1Box);
         assert_eq!(src_box.box_size(), header.size);
 
-        let dst_box = Hev1Box::read_box(&mut reader, header.size).unwrap();
+        let format = HevcFormat::from_box_type(header.name).unwrap();
+        let dst_box = Hev1Box::read_entry(&mut reader, format, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+
+    #[test]
+    fn test_hvc1() {
+        let src_box = Hev1Box {
+            format: HevcFormat::Hvc1,
+            width: 1920,
+            height: 1080,
+            hvcc: HvcCBox {
+                configuration_version: 1,
+                general_profile_idc: 1,
+                general_profile_compatibility_flags: 0x6000_0000,
+                general_constraint_indicator_flag: 0xB000_0000_0000,
+                general_level_idc: 93,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::Hvc1Box);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let format = HevcFormat::from_box_type(header.name).unwrap();
+        let dst_box = Hev1Box::read_entry(&mut reader, format, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.codec_string(), "hvc1.1.6.L93.B0");
+
+        let hev1 = Hev1Box {
+            format: HevcFormat::Hev1,
+            hvcc: HvcCBox {
+                general_profile_space: 1,
+                general_tier_flag: true,
+                general_profile_idc: 2,
+                general_profile_compatibility_flags: 0x2000_0000,
+                general_level_idc: 120,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(hev1.codec_string(), "hev1.A2.4.H120");
+    }
+
+    #[test]
+    fn test_hev1_hdr() {
+        let src_box = Hev1Box {
+            format: HevcFormat::Hvc1,
+            width: 3840,
+            height: 2160,
+            hvcc: HvcCBox {
+                configuration_version: 1,
+                general_profile_idc: 2,
+                ..Default::default()
+            },
+            mdcv: Some(MdcvBox {
+                display_primaries: [(8500, 39850), (6550, 2300), (35400, 14600)],
+                white_point: (15635, 16450),
+                max_display_mastering_luminance: 10_000_000,
+                min_display_mastering_luminance: 50,
+            }),
+            clli: Some(ClliBox {
+                max_content_light_level: 1000,
+                max_pic_average_light_level: 400,
+            }),
+            amve: Some(AmveBox {
+                ambient_illuminance: 3_140_000,
+                ambient_light_x: 15635,
+                ambient_light_y: 16450,
+            }),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::Hvc1Box);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let format = HevcFormat::from_box_type(header.name).unwrap();
+        let dst_box = Hev1Box::read_entry(&mut reader, format, header.size).unwrap();
         assert_eq!(src_box, dst_box);
     }
 }
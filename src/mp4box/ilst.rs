@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::io::{Read, Seek};
 
 use byteorder::ByteOrder;
@@ -11,6 +12,26 @@ use crate::mp4box::*;
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct IlstBox {
     pub items: HashMap<MetadataKey, IlstItemBox>,
+
+    /// The `covr` images after the one in the `Poster` item, or all of them
+    /// when the first has a type code [DataType] does not know.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_covers: Vec<CovrImage>,
+}
+
+/// An image of a `covr` item, with its raw `data` type code.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct CovrImage {
+    pub type_code: u32,
+
+    #[serde(skip_serializing)]
+    pub data: Vec<u8>,
+}
+
+impl CovrImage {
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8 + self.data.len() as u64
+    }
 }
 
 impl IlstBox {
@@ -23,8 +44,18 @@ impl IlstBox {
         for item in self.items.values() {
             size += item.get_size();
         }
+        if !self.extra_covers.is_empty() {
+            if !self.items.contains_key(&MetadataKey::Poster) {
+                size += HEADER_SIZE;
+            }
+            size += self.extra_covers_size();
+        }
         size
     }
+
+    fn extra_covers_size(&self) -> u64 {
+        self.extra_covers.iter().map(CovrImage::get_size).sum()
+    }
 }
 
 impl Mp4Box for IlstBox {
@@ -51,13 +82,14 @@ impl<R: Read + Seek> ReadBox<&mut R> for IlstBox {
         let start = box_start(reader)?;
 
         let mut items = HashMap::new();
+        let mut extra_covers = Vec::new();
 
         let mut current = reader.stream_position()?;
         let end = start + size;
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "ilst box contains a box with a larger size than it",
@@ -72,24 +104,85 @@ impl<R: Read + Seek> ReadBox<&mut R> for IlstBox {
                     items.insert(MetadataKey::Year, IlstItemBox::read_box(reader, s)?);
                 }
                 BoxType::CovrBox => {
-                    items.insert(MetadataKey::Poster, IlstItemBox::read_box(reader, s)?);
+                    read_covr(reader, s, &mut items, &mut extra_covers)?;
                 }
                 BoxType::DescBox => {
                     items.insert(MetadataKey::Summary, IlstItemBox::read_box(reader, s)?);
                 }
+                BoxType::XyzBox => {
+                    items.insert(MetadataKey::Location, IlstItemBox::read_box(reader, s)?);
+                }
                 _ => {
                     // XXX warn!()
                     skip_box(reader, s)?;
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         skip_bytes_to(reader, start + size)?;
 
-        Ok(IlstBox { items })
+        Ok(IlstBox {
+            items,
+            extra_covers,
+        })
+    }
+}
+
+// Read the images of a covr item. The first one goes to the Poster item if
+// its type is known and there is no Poster yet, so that a file with several
+// covr boxes keeps its images in order.
+fn read_covr<R: Read + Seek>(
+    reader: &mut R,
+    size: u64,
+    items: &mut HashMap<MetadataKey, IlstItemBox>,
+    extra_covers: &mut Vec<CovrImage>,
+) -> Result<()> {
+    let start = box_start(reader)?;
+
+    let mut current = reader.stream_position()?;
+    let end = start + size;
+    while current < end {
+        // Get box header.
+        let header = BoxHeader::read(reader)?;
+        let BoxHeader { name, size: s, .. } = header;
+        if s > size {
+            return Err(Error::InvalidData(
+                "covr box contains a box with a larger size than it",
+            ));
+        }
+
+        match name {
+            BoxType::DataBox => {
+                let len = s
+                    .checked_sub(HEADER_SIZE + 8)
+                    .ok_or(Error::InvalidData("data box too small"))?;
+                let type_code = reader.read_u32::<BigEndian>()?;
+                reader.read_u32::<BigEndian>()?; // reserved = 0
+                let mut data = vec![0u8; len as usize];
+                reader.read_exact(&mut data)?;
+
+                match DataType::try_from(type_code) {
+                    Ok(data_type)
+                        if extra_covers.is_empty() && !items.contains_key(&MetadataKey::Poster) =>
+                    {
+                        let data = DataBox { data, data_type };
+                        items.insert(MetadataKey::Poster, IlstItemBox { data });
+                    }
+                    _ => extra_covers.push(CovrImage { type_code, data }),
+                }
+            }
+            _ => {
+                // XXX warn!()
+                skip_box(reader, s)?;
+            }
+        }
+
+        current = next_box_position(reader, current)?;
     }
+
+    skip_bytes_to(reader, end)
 }
 
 impl<W: Write> WriteBox<&mut W> for IlstBox {
@@ -98,19 +191,37 @@ impl<W: Write> WriteBox<&mut W> for IlstBox {
         BoxHeader::new(self.box_type(), size).write(writer)?;
 
         for (key, value) in &self.items {
-            let name = match key {
-                MetadataKey::Title => BoxType::NameBox,
-                MetadataKey::Year => BoxType::DayBox,
-                MetadataKey::Poster => BoxType::CovrBox,
-                MetadataKey::Summary => BoxType::DescBox,
-            };
-            BoxHeader::new(name, value.get_size()).write(writer)?;
+            if *key == MetadataKey::Poster {
+                let size = value.get_size() + self.extra_covers_size();
+                BoxHeader::new(BoxType::CovrBox, size).write(writer)?;
+                value.data.write_box(writer)?;
+                self.write_extra_covers(writer)?;
+                continue;
+            }
+            BoxHeader::new(item_box_type(key), value.get_size()).write(writer)?;
             value.data.write_box(writer)?;
         }
+        if !self.extra_covers.is_empty() && !self.items.contains_key(&MetadataKey::Poster) {
+            let size = HEADER_SIZE + self.extra_covers_size();
+            BoxHeader::new(BoxType::CovrBox, size).write(writer)?;
+            self.write_extra_covers(writer)?;
+        }
         Ok(size)
     }
 }
 
+impl IlstBox {
+    fn write_extra_covers<W: Write>(&self, writer: &mut W) -> Result<()> {
+        for cover in &self.extra_covers {
+            BoxHeader::new(BoxType::DataBox, cover.get_size()).write(writer)?;
+            writer.write_u32::<BigEndian>(cover.type_code)?;
+            writer.write_u32::<BigEndian>(0)?; // reserved = 0
+            writer.write_all(&cover.data)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct IlstItemBox {
     pub data: DataBox,
@@ -133,7 +244,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for IlstItemBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "ilst item box contains a box with a larger size than it",
@@ -150,7 +261,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for IlstItemBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if data.is_none() {
@@ -166,7 +277,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for IlstItemBox {
 }
 
 impl<'a> Metadata<'a> for IlstBox {
-    fn title(&self) -> Option<Cow<str>> {
+    fn title(&self) -> Option<Cow<'_, str>> {
         self.items.get(&MetadataKey::Title).map(item_to_str)
     }
 
@@ -178,16 +289,45 @@ impl<'a> Metadata<'a> for IlstBox {
         self.items.get(&MetadataKey::Poster).map(item_to_bytes)
     }
 
-    fn summary(&self) -> Option<Cow<str>> {
+    fn summary(&self) -> Option<Cow<'_, str>> {
         self.items.get(&MetadataKey::Summary).map(item_to_str)
     }
+
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        let poster = self.items.get(&MetadataKey::Poster).map(|item| CoverArt {
+            format: CoverArtFormat::from(item.data.data_type.clone() as u32),
+            data: &item.data.data,
+        });
+        poster
+            .into_iter()
+            .chain(self.extra_covers.iter().map(|cover| CoverArt {
+                format: CoverArtFormat::from(cover.type_code),
+                data: &cover.data,
+            }))
+            .collect()
+    }
+
+    fn location(&self) -> Option<GeoLocation> {
+        let location = self.items.get(&MetadataKey::Location).map(item_to_str)?;
+        GeoLocation::from_iso6709(&location)
+    }
+}
+
+pub(crate) fn item_box_type(key: &MetadataKey) -> BoxType {
+    match key {
+        MetadataKey::Title => BoxType::NameBox,
+        MetadataKey::Year => BoxType::DayBox,
+        MetadataKey::Poster => BoxType::CovrBox,
+        MetadataKey::Summary => BoxType::DescBox,
+        MetadataKey::Location => BoxType::XyzBox,
+    }
 }
 
 fn item_to_bytes(item: &IlstItemBox) -> &[u8] {
     &item.data.data
 }
 
-fn item_to_str(item: &IlstItemBox) -> Cow<str> {
+fn item_to_str(item: &IlstItemBox) -> Cow<'_, str> {
     String::from_utf8_lossy(&item.data.data)
 }
 
@@ -221,6 +361,7 @@ mod tests {
                 (MetadataKey::Summary, IlstItemBox::default()),
             ]
             .into(),
+            ..Default::default()
         };
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
@@ -235,6 +376,88 @@ mod tests {
         assert_eq!(src_box, dst_box);
     }
 
+    #[test]
+    fn test_ilst_covers() {
+        let src_box = IlstBox {
+            items: [(
+                MetadataKey::Poster,
+                IlstItemBox {
+                    data: DataBox {
+                        data_type: DataType::Image,
+                        data: b"jpeg".to_vec(),
+                    },
+                },
+            )]
+            .into(),
+            extra_covers: vec![
+                CovrImage {
+                    type_code: 14,
+                    data: b"png".to_vec(),
+                },
+                CovrImage {
+                    type_code: 27,
+                    data: b"bmp".to_vec(),
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::IlstBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = IlstBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.poster(), Some(&b"jpeg"[..]));
+        assert_eq!(
+            dst_box.cover_art(),
+            [
+                CoverArt {
+                    format: CoverArtFormat::Jpeg,
+                    data: b"jpeg",
+                },
+                CoverArt {
+                    format: CoverArtFormat::Png,
+                    data: b"png",
+                },
+                CoverArt {
+                    format: CoverArtFormat::Unknown(27),
+                    data: b"bmp",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ilst_covr_unknown_first() {
+        // Two covr items, the first with a type code DataType does not know.
+        let data = b"\x00\x00\x00\x3cilst\
+            \x00\x00\x00\x1bcovr\x00\x00\x00\x13data\x00\x00\x00\x1b\x00\x00\x00\x00bmp\
+            \x00\x00\x00\x19covr\x00\x00\x00\x11data\x00\x00\x00\x0d\x00\x00\x00\x00j";
+        let mut reader = Cursor::new(&data[..]);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.size, data.len() as u64);
+        let ilst = IlstBox::read_box(&mut reader, header.size).unwrap();
+
+        assert_eq!(ilst.poster(), None);
+        assert_eq!(
+            ilst.cover_art(),
+            [
+                CoverArt {
+                    format: CoverArtFormat::Unknown(27),
+                    data: b"bmp",
+                },
+                CoverArt {
+                    format: CoverArtFormat::Jpeg,
+                    data: b"j",
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_ilst_empty() {
         let src_box = IlstBox::default();
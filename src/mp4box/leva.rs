@@ -0,0 +1,226 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct LevaBox {
+    pub version: u8,
+    pub flags: u32,
+    pub levels: Vec<LevaEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct LevaEntry {
+    pub track_id: u32,
+    pub padding_flag: bool,
+    pub assignment: LevelAssignment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub enum LevelAssignment {
+    SampleGroup {
+        grouping_type: FourCC,
+    },
+    SampleGroupWithParameter {
+        grouping_type: FourCC,
+        grouping_type_parameter: u32,
+    },
+    #[default]
+    Track,
+    SubTrackLevel,
+    SubTrack {
+        sub_track_id: u32,
+    },
+    Other(u8),
+}
+
+impl LevelAssignment {
+    pub fn assignment_type(&self) -> u8 {
+        match self {
+            LevelAssignment::SampleGroup { .. } => 0,
+            LevelAssignment::SampleGroupWithParameter { .. } => 1,
+            LevelAssignment::Track => 2,
+            LevelAssignment::SubTrackLevel => 3,
+            LevelAssignment::SubTrack { .. } => 4,
+            LevelAssignment::Other(t) => *t,
+        }
+    }
+
+    fn get_size(&self) -> u64 {
+        match self {
+            LevelAssignment::SampleGroup { .. } => 4,
+            LevelAssignment::SampleGroupWithParameter { .. } => 8,
+            LevelAssignment::SubTrack { .. } => 4,
+            _ => 0,
+        }
+    }
+}
+
+impl LevaBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::LevaBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 1;
+        for level in self.levels.iter() {
+            size += 5 + level.assignment.get_size();
+        }
+        size
+    }
+}
+
+impl Mp4Box for LevaBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("level_count={}", self.levels.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for LevaBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let level_count = reader.read_u8()?;
+        let mut levels = Vec::with_capacity(level_count as usize);
+        for _ in 0..level_count {
+            let track_id = reader.read_u32::<BigEndian>()?;
+            let b = reader.read_u8()?;
+            let padding_flag = b & 0x80 != 0;
+            let assignment = match b & 0x7F {
+                0 => LevelAssignment::SampleGroup {
+                    grouping_type: reader.read_u32::<BigEndian>()?.into(),
+                },
+                1 => LevelAssignment::SampleGroupWithParameter {
+                    grouping_type: reader.read_u32::<BigEndian>()?.into(),
+                    grouping_type_parameter: reader.read_u32::<BigEndian>()?,
+                },
+                2 => LevelAssignment::Track,
+                3 => LevelAssignment::SubTrackLevel,
+                4 => LevelAssignment::SubTrack {
+                    sub_track_id: reader.read_u32::<BigEndian>()?,
+                },
+                t => LevelAssignment::Other(t),
+            };
+            levels.push(LevaEntry {
+                track_id,
+                padding_flag,
+                assignment,
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(LevaBox {
+            version,
+            flags,
+            levels,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for LevaBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.levels.len() > u8::MAX as usize {
+            return Err(Error::InvalidData("too many leva levels"));
+        }
+        writer.write_u8(self.levels.len() as u8)?;
+        for level in self.levels.iter() {
+            writer.write_u32::<BigEndian>(level.track_id)?;
+            let assignment_type = level.assignment.assignment_type() & 0x7F;
+            writer.write_u8(((level.padding_flag as u8) << 7) | assignment_type)?;
+            match level.assignment {
+                LevelAssignment::SampleGroup { grouping_type } => {
+                    writer.write_u32::<BigEndian>(grouping_type.into())?;
+                }
+                LevelAssignment::SampleGroupWithParameter {
+                    grouping_type,
+                    grouping_type_parameter,
+                } => {
+                    writer.write_u32::<BigEndian>(grouping_type.into())?;
+                    writer.write_u32::<BigEndian>(grouping_type_parameter)?;
+                }
+                LevelAssignment::SubTrack { sub_track_id } => {
+                    writer.write_u32::<BigEndian>(sub_track_id)?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_leva() {
+        let src_box = LevaBox {
+            version: 0,
+            flags: 0,
+            levels: vec![
+                LevaEntry {
+                    track_id: 1,
+                    padding_flag: false,
+                    assignment: LevelAssignment::Track,
+                },
+                LevaEntry {
+                    track_id: 1,
+                    padding_flag: true,
+                    assignment: LevelAssignment::SampleGroup {
+                        grouping_type: str::parse("tele").unwrap(),
+                    },
+                },
+                LevaEntry {
+                    track_id: 2,
+                    padding_flag: false,
+                    assignment: LevelAssignment::SampleGroupWithParameter {
+                        grouping_type: str::parse("sync").unwrap(),
+                        grouping_type_parameter: 7,
+                    },
+                },
+                LevaEntry {
+                    track_id: 2,
+                    padding_flag: false,
+                    assignment: LevelAssignment::SubTrack { sub_track_id: 3 },
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::LevaBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = LevaBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
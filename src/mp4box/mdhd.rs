@@ -139,7 +139,7 @@ impl<W: Write> WriteBox<&mut W> for MdhdBox {
     }
 }
 
-fn language_string(language: u16) -> String {
+pub(crate) fn language_string(language: u16) -> String {
     let mut lang: [u16; 3] = [0; 3];
 
     lang[0] = ((language >> 10) & 0x1F) + 0x60;
@@ -154,7 +154,7 @@ fn language_string(language: u16) -> String {
     lang_str
 }
 
-fn language_code(language: &str) -> u16 {
+pub(crate) fn language_code(language: &str) -> u16 {
     let mut lang = language.encode_utf16();
     let mut code = (lang.next().unwrap_or(0) & 0x1F) << 10;
     code += (lang.next().unwrap_or(0) & 0x1F) << 5;
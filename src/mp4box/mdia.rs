@@ -2,12 +2,16 @@ use serde::Serialize;
 use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
-use crate::mp4box::{hdlr::HdlrBox, mdhd::MdhdBox, minf::MinfBox};
+use crate::mp4box::{elng::ElngBox, hdlr::HdlrBox, mdhd::MdhdBox, minf::MinfBox};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct MdiaBox {
     pub mdhd: MdhdBox,
     pub hdlr: HdlrBox,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elng: Option<ElngBox>,
+
     pub minf: MinfBox,
 }
 
@@ -17,7 +21,11 @@ impl MdiaBox {
     }
 
     pub fn get_size(&self) -> u64 {
-        HEADER_SIZE + self.mdhd.box_size() + self.hdlr.box_size() + self.minf.box_size()
+        let mut size = HEADER_SIZE + self.mdhd.box_size() + self.hdlr.box_size();
+        if let Some(ref elng) = self.elng {
+            size += elng.box_size();
+        }
+        size + self.minf.box_size()
     }
 }
 
@@ -46,6 +54,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdiaBox {
 
         let mut mdhd = None;
         let mut hdlr = None;
+        let mut elng = None;
         let mut minf = None;
 
         let mut current = reader.stream_position()?;
@@ -53,7 +62,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdiaBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "mdia box contains a box with a larger size than it",
@@ -67,6 +76,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdiaBox {
                 BoxType::HdlrBox => {
                     hdlr = Some(HdlrBox::read_box(reader, s)?);
                 }
+                BoxType::ElngBox => {
+                    elng = Some(ElngBox::read_box(reader, s)?);
+                }
                 BoxType::MinfBox => {
                     minf = Some(MinfBox::read_box(reader, s)?);
                 }
@@ -76,7 +88,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdiaBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if mdhd.is_none() {
@@ -94,6 +106,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdiaBox {
         Ok(MdiaBox {
             mdhd: mdhd.unwrap(),
             hdlr: hdlr.unwrap(),
+            elng,
             minf: minf.unwrap(),
         })
     }
@@ -106,6 +119,9 @@ impl<W: Write> WriteBox<&mut W> for MdiaBox {
 
         self.mdhd.write_box(writer)?;
         self.hdlr.write_box(writer)?;
+        if let Some(ref elng) = self.elng {
+            elng.write_box(writer)?;
+        }
         self.minf.write_box(writer)?;
 
         Ok(size)
@@ -1,9 +1,12 @@
+use std::borrow::Cow;
 use std::io::{Read, Seek};
 
 use serde::Serialize;
 
+use crate::mp4box::data::DataBox;
 use crate::mp4box::hdlr::HdlrBox;
-use crate::mp4box::ilst::IlstBox;
+use crate::mp4box::ilst::{self, IlstBox, IlstItemBox};
+use crate::mp4box::keys::KeysBox;
 use crate::mp4box::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -15,17 +18,87 @@ pub enum MetaBox {
         ilst: Option<IlstBox>,
     },
 
+    Mdta {
+        keys: KeysBox,
+        items: Vec<MdtaItem>,
+    },
+
     #[serde(skip)]
     Unknown {
         #[serde(skip)]
         hdlr: HdlrBox,
 
+        /// The children after the `hdlr`, in their order in the file.
         #[serde(skip)]
-        data: Vec<(BoxType, Vec<u8>)>,
+        data: Vec<MetaChild>,
     },
 }
 
+/// A child of a `meta` box with no item layout the crate knows, such as a
+/// file-level `meta`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaChild {
+    /// The content type of the items.
+    Mime(MimeBox),
+    Uri(UriBox),
+    UriInit(UriInitBox),
+
+    /// Any other box, with its body.
+    Raw(BoxType, Vec<u8>),
+}
+
+impl MetaChild {
+    fn box_size(&self) -> u64 {
+        match self {
+            Self::Mime(mime) => mime.box_size(),
+            Self::Uri(uri) => uri.box_size(),
+            Self::UriInit(uri_init) => uri_init.box_size(),
+            Self::Raw(_, data) => HEADER_SIZE + data.len() as u64,
+        }
+    }
+
+    fn write_box<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Mime(mime) => {
+                mime.write_box(writer)?;
+            }
+            Self::Uri(uri) => {
+                uri.write_box(writer)?;
+            }
+            Self::UriInit(uri_init) => {
+                uri_init.write_box(writer)?;
+            }
+            Self::Raw(box_type, data) => {
+                BoxHeader::new(*box_type, HEADER_SIZE + data.len() as u64).write(writer)?;
+                writer.write_all(data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `ilst` item of an `mdta` meta box.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct MdtaItem {
+    /// 1-based index of the item's key in the `keys` table.
+    pub key_index: u32,
+    pub data: DataBox,
+}
+
+impl MdtaItem {
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + self.data.box_size()
+    }
+}
+
 const MDIR: FourCC = FourCC { value: *b"mdir" };
+const MDTA: FourCC = FourCC { value: *b"mdta" };
+
+const MDTA_TITLE: &str = "com.apple.quicktime.title";
+const MDTA_CREATION_DATE: &str = "com.apple.quicktime.creationdate";
+const MDTA_ARTWORK: &str = "com.apple.quicktime.artwork";
+const MDTA_DESCRIPTION: &str = "com.apple.quicktime.description";
+const MDTA_LOCATION: &str = "com.apple.quicktime.location.ISO6709";
 
 impl MetaBox {
     pub fn get_type(&self) -> BoxType {
@@ -41,16 +114,132 @@ impl MetaBox {
                     size += ilst.box_size();
                 }
             }
+            Self::Mdta { keys, items } => {
+                size += HdlrBox::default().box_size()
+                    + keys.box_size()
+                    + HEADER_SIZE
+                    + items.iter().map(MdtaItem::get_size).sum::<u64>();
+            }
             Self::Unknown { hdlr, data } => {
-                size += hdlr.box_size()
-                    + data
-                        .iter()
-                        .map(|(_, data)| data.len() as u64 + HEADER_SIZE)
-                        .sum::<u64>()
+                size += hdlr.box_size() + data.iter().map(MetaChild::box_size).sum::<u64>()
             }
         }
         size
     }
+
+    /// The item layout named by the box's `hdlr`.
+    pub fn layout(&self) -> MetadataLayout {
+        match self {
+            Self::Mdir { .. } => MetadataLayout::Mdir,
+            Self::Mdta { .. } => MetadataLayout::Mdta,
+            Self::Unknown { hdlr, .. } => MetadataLayout::Unknown(hdlr.handler_type),
+        }
+    }
+
+    /// All items as key-value pairs.
+    ///
+    /// `mdir` items are keyed by their four-character code (`©nam`) and
+    /// sorted by key; `mdta` items are keyed by their entry in the `keys`
+    /// table (`com.apple.quicktime.model`) and keep their file order.
+    /// Items whose key index is out of range are left out.
+    pub fn entries(&self) -> Vec<(Cow<'_, str>, &DataBox)> {
+        match self {
+            Self::Mdir { ilst: Some(ilst) } => {
+                let mut entries: Vec<(Cow<str>, &DataBox)> = ilst
+                    .items
+                    .iter()
+                    .map(|(key, item)| {
+                        let code = FourCC::from(ilst::item_box_type(key));
+                        let name: String = code.value.iter().map(|&b| b as char).collect();
+                        (Cow::Owned(name), &item.data)
+                    })
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries
+            }
+            Self::Mdta { keys, items } => items
+                .iter()
+                .filter_map(|item| Some((Cow::Borrowed(keys.key(item.key_index)?), &item.data)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The value of the first item with the given key, as named by
+    /// [`entries`](Self::entries).
+    pub fn get(&self, key: &str) -> Option<&DataBox> {
+        match self {
+            Self::Mdta { keys, items } => items
+                .iter()
+                .find(|item| keys.key(item.key_index) == Some(key))
+                .map(|item| &item.data),
+            _ => self
+                .entries()
+                .into_iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, data)| data),
+        }
+    }
+}
+
+impl<'a> Metadata<'a> for MetaBox {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Self::Mdir { ilst } => ilst.title(),
+            _ => self
+                .get(MDTA_TITLE)
+                .map(|data| String::from_utf8_lossy(&data.data)),
+        }
+    }
+
+    fn year(&self) -> Option<u32> {
+        match self {
+            Self::Mdir { ilst } => ilst.year(),
+            // An ISO 8601 date such as 2019-05-12T10:00:00+0200.
+            _ => self
+                .get(MDTA_CREATION_DATE)
+                .and_then(|data| std::str::from_utf8(data.data.get(..4)?).ok()?.parse().ok()),
+        }
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        match self {
+            Self::Mdir { ilst } => ilst.poster(),
+            _ => self.get(MDTA_ARTWORK).map(|data| data.data.as_slice()),
+        }
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Self::Mdir { ilst } => ilst.summary(),
+            _ => self
+                .get(MDTA_DESCRIPTION)
+                .map(|data| String::from_utf8_lossy(&data.data)),
+        }
+    }
+
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        match self {
+            Self::Mdir { ilst } => ilst.cover_art(),
+            _ => self
+                .get(MDTA_ARTWORK)
+                .map(|data| CoverArt {
+                    format: CoverArtFormat::from(data.data_type.clone() as u32),
+                    data: &data.data,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn location(&self) -> Option<GeoLocation> {
+        match self {
+            Self::Mdir { ilst } => ilst.location(),
+            _ => self
+                .get(MDTA_LOCATION)
+                .and_then(|data| GeoLocation::from_iso6709(std::str::from_utf8(&data.data).ok()?)),
+        }
+    }
 }
 
 impl Mp4Box for MetaBox {
@@ -69,7 +258,14 @@ impl Mp4Box for MetaBox {
     fn summary(&self) -> Result<String> {
         let s = match self {
             Self::Mdir { .. } => "hdlr=ilst".to_string(),
-            Self::Unknown { hdlr, data } => {
+            Self::Mdta { keys, items } => {
+                format!(
+                    "hdlr=mdta key_count={} item_count={}",
+                    keys.keys.len(),
+                    items.len()
+                )
+            }
+            Self::Unknown { hdlr, data, .. } => {
                 format!("hdlr={} data_len={}", hdlr.handler_type, data.len())
             }
         };
@@ -115,7 +311,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MetaBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
 
             match name {
                 BoxType::HdlrBox => {
@@ -127,7 +323,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MetaBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         let Some(hdlr) = hdlr else {
@@ -145,7 +341,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MetaBox {
                 while current < end {
                     // Get box header.
                     let header = BoxHeader::read(reader)?;
-                    let BoxHeader { name, size: s } = header;
+                    let BoxHeader { name, size: s, .. } = header;
 
                     match name {
                         BoxType::IlstBox => {
@@ -157,32 +353,76 @@ impl<R: Read + Seek> ReadBox<&mut R> for MetaBox {
                         }
                     }
 
-                    current = reader.stream_position()?;
+                    current = next_box_position(reader, current)?;
                 }
 
                 Ok(MetaBox::Mdir { ilst })
             }
+            MDTA => {
+                let mut keys = None;
+                let mut items = Vec::new();
+
+                while current < end {
+                    // Get box header.
+                    let header = BoxHeader::read(reader)?;
+                    let BoxHeader { name, size: s, .. } = header;
+
+                    match name {
+                        BoxType::KeysBox => {
+                            keys = Some(KeysBox::read_box(reader, s)?);
+                        }
+                        BoxType::IlstBox => {
+                            items = read_mdta_items(reader, s)?;
+                        }
+                        _ => {
+                            // XXX warn!()
+                            skip_box(reader, s)?;
+                        }
+                    }
+
+                    current = next_box_position(reader, current)?;
+                }
+
+                Ok(MetaBox::Mdta {
+                    keys: keys.unwrap_or_default(),
+                    items,
+                })
+            }
             _ => {
                 let mut data = Vec::new();
 
                 while current < end {
                     // Get box header.
                     let header = BoxHeader::read(reader)?;
-                    let BoxHeader { name, size: s } = header;
+                    let BoxHeader { name, size: s, .. } = header;
+                    if s < HEADER_SIZE || current + s > end {
+                        return Err(Error::InvalidData(
+                            "meta box contains a box with an invalid size",
+                        ));
+                    }
 
                     match name {
                         BoxType::HdlrBox => {
                             skip_box(reader, s)?;
                         }
+                        BoxType::MimeBox => {
+                            data.push(MetaChild::Mime(MimeBox::read_box(reader, s)?));
+                        }
+                        BoxType::UriBox => {
+                            data.push(MetaChild::Uri(UriBox::read_box(reader, s)?));
+                        }
+                        BoxType::UriInitBox => {
+                            data.push(MetaChild::UriInit(UriInitBox::read_box(reader, s)?));
+                        }
                         _ => {
                             let mut box_data = vec![0; (s - HEADER_SIZE) as usize];
                             reader.read_exact(&mut box_data)?;
 
-                            data.push((name, box_data));
+                            data.push(MetaChild::Raw(name, box_data));
                         }
                     }
 
-                    current = reader.stream_position()?;
+                    current = next_box_position(reader, current)?;
                 }
 
                 Ok(MetaBox::Unknown { hdlr, data })
@@ -191,6 +431,44 @@ impl<R: Read + Seek> ReadBox<&mut R> for MetaBox {
     }
 }
 
+// The items of an mdta ilst, typed by key index rather than by name. Items
+// with a data type the crate does not know are skipped.
+fn read_mdta_items<R: Read + Seek>(reader: &mut R, size: u64) -> Result<Vec<MdtaItem>> {
+    let start = box_start(reader)?;
+    let end = start + size;
+
+    let mut items = Vec::new();
+    let mut current = reader.stream_position()?;
+    while current < end {
+        // Get box header.
+        let header = BoxHeader::read(reader)?;
+        let BoxHeader { name, size: s, .. } = header;
+        if s > size {
+            return Err(Error::InvalidData(
+                "ilst box contains a box with a larger size than it",
+            ));
+        }
+
+        match IlstItemBox::read_box(reader, s) {
+            Ok(item) => items.push(MdtaItem {
+                key_index: name.into(),
+                data: item.data,
+            }),
+            Err(Error::InvalidData(_)) => {
+                // XXX warn!()
+                skip_bytes_to(reader, current + s)?;
+            }
+            Err(err) => return Err(err),
+        }
+
+        current = next_box_position(reader, current)?;
+    }
+
+    skip_bytes_to(reader, end)?;
+
+    Ok(items)
+}
+
 impl<W: Write> WriteBox<&mut W> for MetaBox {
     fn write_box(&self, writer: &mut W) -> Result<u64> {
         let size = self.box_size();
@@ -203,6 +481,10 @@ impl<W: Write> WriteBox<&mut W> for MetaBox {
                 handler_type: MDIR,
                 ..Default::default()
             },
+            Self::Mdta { .. } => HdlrBox {
+                handler_type: MDTA,
+                ..Default::default()
+            },
             Self::Unknown { hdlr, .. } => hdlr.clone(),
         };
         hdlr.write_box(writer)?;
@@ -213,10 +495,19 @@ impl<W: Write> WriteBox<&mut W> for MetaBox {
                     ilst.write_box(writer)?;
                 }
             }
+            Self::Mdta { keys, items } => {
+                keys.write_box(writer)?;
+
+                let ilst_size = HEADER_SIZE + items.iter().map(MdtaItem::get_size).sum::<u64>();
+                BoxHeader::new(BoxType::IlstBox, ilst_size).write(writer)?;
+                for item in items {
+                    BoxHeader::new(BoxType::from(item.key_index), item.get_size()).write(writer)?;
+                    item.data.write_box(writer)?;
+                }
+            }
             Self::Unknown { data, .. } => {
-                for (box_type, data) in data {
-                    BoxHeader::new(*box_type, data.len() as u64 + HEADER_SIZE).write(writer)?;
-                    writer.write_all(data)?;
+                for child in data {
+                    child.write_box(writer)?;
                 }
             }
         }
@@ -227,6 +518,7 @@ impl<W: Write> WriteBox<&mut W> for MetaBox {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mp4box::keys::KeyEntry;
     use crate::mp4box::BoxHeader;
     use std::io::Cursor;
 
@@ -285,13 +577,182 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_meta_mdir_entries() {
+        let item = |data: &[u8]| IlstItemBox {
+            data: DataBox {
+                data_type: DataType::Text,
+                data: data.to_vec(),
+            },
+        };
+        let meta_box = MetaBox::Mdir {
+            ilst: Some(IlstBox {
+                items: [
+                    (MetadataKey::Title, item(b"Big Buck Bunny")),
+                    (MetadataKey::Year, item(b"2008")),
+                ]
+                .into(),
+                ..Default::default()
+            }),
+        };
+        assert_eq!(meta_box.layout(), MetadataLayout::Mdir);
+
+        let entries: Vec<_> = meta_box
+            .entries()
+            .into_iter()
+            .map(|(key, data)| (key.into_owned(), data.data.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("\u{a9}day".to_string(), b"2008".to_vec()),
+                ("\u{a9}nam".to_string(), b"Big Buck Bunny".to_vec()),
+            ]
+        );
+        assert_eq!(meta_box.title().unwrap(), "Big Buck Bunny");
+        assert_eq!(meta_box.year(), Some(2008));
+    }
+
+    #[test]
+    fn test_meta_mdta() {
+        let key = |value: &str| KeyEntry {
+            namespace: MDTA,
+            value: value.to_string(),
+        };
+        let src_box = MetaBox::Mdta {
+            keys: KeysBox {
+                version: 0,
+                flags: 0,
+                keys: vec![
+                    key("com.apple.quicktime.make"),
+                    key("com.apple.quicktime.model"),
+                    key("com.apple.quicktime.creationdate"),
+                    key("com.apple.quicktime.location.accuracy.horizontal"),
+                ],
+            },
+            items: vec![
+                MdtaItem {
+                    key_index: 2,
+                    data: DataBox {
+                        data_type: DataType::Text,
+                        data: b"iPhone XS".to_vec(),
+                    },
+                },
+                MdtaItem {
+                    key_index: 1,
+                    data: DataBox {
+                        data_type: DataType::Text,
+                        data: b"Apple".to_vec(),
+                    },
+                },
+                MdtaItem {
+                    key_index: 3,
+                    data: DataBox {
+                        data_type: DataType::Text,
+                        data: b"2019-05-12T10:00:00+0200".to_vec(),
+                    },
+                },
+                MdtaItem {
+                    key_index: 4,
+                    data: DataBox {
+                        data_type: DataType::Float32,
+                        data: 65.0f32.to_be_bytes().to_vec(),
+                    },
+                },
+            ],
+        };
+
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MetaBox);
+        assert_eq!(header.size, src_box.box_size());
+
+        let dst_box = MetaBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(dst_box, src_box);
+
+        // iPhone recordings store moov/meta as a plain QuickTime container,
+        // without version and flags.
+        let mut qt = buf.clone();
+        qt.drain(8..12);
+        let qt_size = qt.len() as u32;
+        qt[..4].copy_from_slice(&qt_size.to_be_bytes());
+        let mut reader = Cursor::new(&qt);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let meta_box = MetaBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(meta_box, src_box);
+
+        assert_eq!(meta_box.layout(), MetadataLayout::Mdta);
+        let entries: Vec<_> = meta_box
+            .entries()
+            .into_iter()
+            .map(|(key, data)| (key.into_owned(), data.data.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "com.apple.quicktime.model".to_string(),
+                    b"iPhone XS".to_vec()
+                ),
+                ("com.apple.quicktime.make".to_string(), b"Apple".to_vec()),
+                (
+                    "com.apple.quicktime.creationdate".to_string(),
+                    b"2019-05-12T10:00:00+0200".to_vec()
+                ),
+                (
+                    "com.apple.quicktime.location.accuracy.horizontal".to_string(),
+                    65.0f32.to_be_bytes().to_vec()
+                ),
+            ]
+        );
+        assert_eq!(
+            meta_box.get("com.apple.quicktime.make").unwrap().data,
+            b"Apple"
+        );
+        assert_eq!(meta_box.year(), Some(2019));
+        assert_eq!(meta_box.title(), None);
+    }
+
+    #[test]
+    fn test_meta_mdta_location() {
+        let meta_box = MetaBox::Mdta {
+            keys: KeysBox {
+                version: 0,
+                flags: 0,
+                keys: vec![KeyEntry {
+                    namespace: MDTA,
+                    value: MDTA_LOCATION.to_string(),
+                }],
+            },
+            items: vec![MdtaItem {
+                key_index: 1,
+                data: DataBox {
+                    data_type: DataType::Text,
+                    data: b"+48.8584+002.2945+035.000/".to_vec(),
+                },
+            }],
+        };
+        assert_eq!(
+            meta_box.location(),
+            Some(GeoLocation {
+                lat: 48.8584,
+                lon: 2.2945,
+                altitude: Some(35.0),
+            })
+        );
+    }
+
     #[test]
     fn test_meta_unknown() {
         let src_hdlr = HdlrBox {
             handler_type: FourCC::from(*b"test"),
             ..Default::default()
         };
-        let src_data = (BoxType::UnknownBox(0x42494241), b"123".to_vec());
+        let src_data = MetaChild::Raw(BoxType::UnknownBox(0x42494241), b"123".to_vec());
         let src_box = MetaBox::Unknown {
             hdlr: src_hdlr,
             data: vec![src_data],
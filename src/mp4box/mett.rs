@@ -0,0 +1,228 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Timed metadata sample entry for text samples in some MIME format, such
+/// as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct MettBox {
+    pub data_reference_index: u16,
+
+    /// The content encoding of the samples, such as `gzip`; empty if they
+    /// are not encoded.
+    pub content_encoding: String,
+    pub mime_format: String,
+}
+
+impl MettBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::MettBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8 + self.content_encoding.len() as u64 + 1 + self.mime_format.len() as u64 + 1
+    }
+}
+
+impl Mp4Box for MettBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "content_encoding={} mime_format={}",
+            self.content_encoding, self.mime_format
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for MettBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let end = start + size;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        let content_encoding = read_cstring(reader, end)?;
+        let mime_format = read_cstring(reader, end)?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(MettBox {
+            data_reference_index,
+            content_encoding,
+            mime_format,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MettBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        write_cstring(writer, &self.content_encoding)?;
+        write_cstring(writer, &self.mime_format)?;
+
+        Ok(size)
+    }
+}
+
+/// Timed metadata sample entry for XML samples, identified by the XML
+/// namespaces of their schema.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct MetxBox {
+    pub data_reference_index: u16,
+
+    /// The content encoding of the samples, such as `gzip`; empty if they
+    /// are not encoded.
+    pub content_encoding: String,
+
+    /// Space-separated XML namespaces the samples conform to.
+    pub namespace: String,
+
+    /// Space-separated URLs of the schemas of the namespaces; may be empty.
+    pub schema_location: String,
+}
+
+impl MetxBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::MetxBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + self.content_encoding.len() as u64
+            + 1
+            + self.namespace.len() as u64
+            + 1
+            + self.schema_location.len() as u64
+            + 1
+    }
+}
+
+impl Mp4Box for MetxBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "content_encoding={} namespace={} schema_location={}",
+            self.content_encoding, self.namespace, self.schema_location
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for MetxBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let end = start + size;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        let content_encoding = read_cstring(reader, end)?;
+        let namespace = read_cstring(reader, end)?;
+        let schema_location = read_cstring(reader, end)?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(MetxBox {
+            data_reference_index,
+            content_encoding,
+            namespace,
+            schema_location,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MetxBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        write_cstring(writer, &self.content_encoding)?;
+        write_cstring(writer, &self.namespace)?;
+        write_cstring(writer, &self.schema_location)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_mett() {
+        let src_box = MettBox {
+            data_reference_index: 1,
+            content_encoding: String::new(),
+            mime_format: String::from("application/json"),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MettBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = MettBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+
+    #[test]
+    fn test_metx() {
+        let src_box = MetxBox {
+            data_reference_index: 1,
+            content_encoding: String::from("gzip"),
+            namespace: String::from("urn:example:telemetry"),
+            schema_location: String::new(),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MetxBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = MetxBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
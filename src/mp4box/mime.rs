@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// The MIME type of some content, such as the items of a file-level `meta`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct MimeBox {
+    pub version: u8,
+    pub flags: u32,
+    pub content_type: String,
+}
+
+impl MimeBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::MimeBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + self.content_type.len() as u64 + 1
+    }
+}
+
+impl Mp4Box for MimeBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("content_type={}", self.content_type);
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for MimeBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let content_type = read_cstring(reader, start + size)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(MimeBox {
+            version,
+            flags,
+            content_type,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MimeBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        write_cstring(writer, &self.content_type)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_mime() {
+        let src_box = MimeBox {
+            version: 0,
+            flags: 0,
+            content_type: String::from("image/jpeg"),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MimeBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = MimeBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
@@ -2,7 +2,9 @@ use serde::Serialize;
 use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
-use crate::mp4box::{dinf::DinfBox, smhd::SmhdBox, stbl::StblBox, vmhd::VmhdBox};
+use crate::mp4box::{
+    dinf::DinfBox, nmhd::NmhdBox, smhd::SmhdBox, stbl::StblBox, sthd::SthdBox, vmhd::VmhdBox,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct MinfBox {
@@ -12,6 +14,12 @@ pub struct MinfBox {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub smhd: Option<SmhdBox>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nmhd: Option<NmhdBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sthd: Option<SthdBox>,
+
     pub dinf: DinfBox,
     pub stbl: StblBox,
 }
@@ -29,6 +37,12 @@ impl MinfBox {
         if let Some(ref smhd) = self.smhd {
             size += smhd.box_size();
         }
+        if let Some(ref nmhd) = self.nmhd {
+            size += nmhd.box_size();
+        }
+        if let Some(ref sthd) = self.sthd {
+            size += sthd.box_size();
+        }
         size += self.dinf.box_size();
         size += self.stbl.box_size();
         size
@@ -60,6 +74,8 @@ impl<R: Read + Seek> ReadBox<&mut R> for MinfBox {
 
         let mut vmhd = None;
         let mut smhd = None;
+        let mut nmhd = None;
+        let mut sthd = None;
         let mut dinf = None;
         let mut stbl = None;
 
@@ -68,7 +84,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MinfBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "minf box contains a box with a larger size than it",
@@ -82,6 +98,12 @@ impl<R: Read + Seek> ReadBox<&mut R> for MinfBox {
                 BoxType::SmhdBox => {
                     smhd = Some(SmhdBox::read_box(reader, s)?);
                 }
+                BoxType::NmhdBox => {
+                    nmhd = Some(NmhdBox::read_box(reader, s)?);
+                }
+                BoxType::SthdBox => {
+                    sthd = Some(SthdBox::read_box(reader, s)?);
+                }
                 BoxType::DinfBox => {
                     dinf = Some(DinfBox::read_box(reader, s)?);
                 }
@@ -94,7 +116,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MinfBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if dinf.is_none() {
@@ -109,6 +131,8 @@ impl<R: Read + Seek> ReadBox<&mut R> for MinfBox {
         Ok(MinfBox {
             vmhd,
             smhd,
+            nmhd,
+            sthd,
             dinf: dinf.unwrap(),
             stbl: stbl.unwrap(),
         })
@@ -126,6 +150,12 @@ impl<W: Write> WriteBox<&mut W> for MinfBox {
         if let Some(ref smhd) = self.smhd {
             smhd.write_box(writer)?;
         }
+        if let Some(ref nmhd) = self.nmhd {
+            nmhd.write_box(writer)?;
+        }
+        if let Some(ref sthd) = self.sthd {
+            sthd.write_box(writer)?;
+        }
         self.dinf.write_box(writer)?;
         self.stbl.write_box(writer)?;
 
@@ -14,21 +14,43 @@
 //! moov
 //!     mvhd
 //!     udta
+//!         cprt
 //!         meta
 //!             ilst
 //!                 data
+//!     meta
+//!         keys
+//!         ilst
 //!     trak
 //!         tkhd
+//!         tapt
+//!             clef
+//!             prof
+//!             enof
 //!         mdia
 //!             mdhd
 //!             hdlr
+//!             elng
 //!             minf
 //!                 stbl
 //!                     stsd
 //!                         avc1
-//!                         hev1
+//!                             avcC
+//!                             mdcv, clli, amve
+//!                         hev1, hvc1
+//!                             hvcC
+//!                             mdcv, clli, amve
 //!                         mp4a
+//!                         ipcm, fpcm
+//!                             pcmC
+//!                         lpcm, sowt, twos
+//!                         ac-4
+//!                             dac4
 //!                         tx3g
+//!                         mett, metx
+//!                         stpp, stxt
+//!                         urim
+//!                             uri, uriI
 //!                     stts
 //!                     stsc
 //!                     stsz
@@ -36,15 +58,23 @@
 //!                     stco
 //!                     co64
 //!                     ctts
+//!                     subs
+//!                     padb
+//!                     saiz
+//!                     saio
 //!                 dinf
 //!                     dref
 //!                 smhd
 //!                 vmhd
+//!                 nmhd
+//!                 sthd
 //!         edts
 //!             elst
 //!     mvex
 //!         mehd
 //!         trex
+//!         trep
+//!         leva
 //! emsg
 //! moof
 //!     mfhd
@@ -52,6 +82,11 @@
 //!         tfhd
 //!         tfdt
 //!         trun
+//!         subs
+//!         saiz
+//!         saio
+//! meta
+//!     mime, uri, uriI
 //! mdat
 //! free
 //!
@@ -62,96 +97,139 @@ use std::io::{Read, Seek, SeekFrom, Write};
 
 use crate::*;
 
+pub(crate) mod ac4;
+pub(crate) mod amve;
 pub(crate) mod avc1;
+pub(crate) mod bits;
+pub(crate) mod clli;
 pub(crate) mod co64;
+pub(crate) mod cprt;
 pub(crate) mod ctts;
 pub(crate) mod data;
 pub(crate) mod dinf;
 pub(crate) mod edts;
+pub(crate) mod elng;
 pub(crate) mod elst;
 pub(crate) mod emsg;
 pub(crate) mod ftyp;
 pub(crate) mod hdlr;
 pub(crate) mod hev1;
 pub(crate) mod ilst;
+pub(crate) mod keys;
+pub(crate) mod leva;
+pub(crate) mod mdcv;
 pub(crate) mod mdhd;
 pub(crate) mod mdia;
 pub(crate) mod mehd;
 pub(crate) mod meta;
+pub(crate) mod mett;
 pub(crate) mod mfhd;
+pub(crate) mod mime;
 pub(crate) mod minf;
 pub(crate) mod moof;
 pub(crate) mod moov;
 pub(crate) mod mp4a;
 pub(crate) mod mvex;
 pub(crate) mod mvhd;
+pub(crate) mod nmhd;
+pub(crate) mod padb;
+pub(crate) mod pcm;
+pub(crate) mod saio;
+pub(crate) mod saiz;
 pub(crate) mod smhd;
 pub(crate) mod stbl;
 pub(crate) mod stco;
+pub(crate) mod sthd;
+pub(crate) mod stpp;
 pub(crate) mod stsc;
 pub(crate) mod stsd;
 pub(crate) mod stss;
 pub(crate) mod stsz;
 pub(crate) mod stts;
+pub(crate) mod subs;
+pub(crate) mod tapt;
 pub(crate) mod tfdt;
 pub(crate) mod tfhd;
 pub(crate) mod tkhd;
 pub(crate) mod traf;
 pub(crate) mod trak;
+pub(crate) mod trep;
 pub(crate) mod trex;
 pub(crate) mod trun;
 pub(crate) mod tx3g;
 pub(crate) mod udta;
+pub(crate) mod urim;
 pub(crate) mod vmhd;
 pub(crate) mod vp09;
 pub(crate) mod vpcc;
 
+pub use ac4::{Ac4Box, Ac4Dsi, Dac4Box};
+pub use amve::AmveBox;
 pub use avc1::Avc1Box;
+pub use clli::ClliBox;
 pub use co64::Co64Box;
-pub use ctts::CttsBox;
+pub use cprt::CprtBox;
+pub use ctts::{CttsBox, CttsEntry};
 pub use data::DataBox;
 pub use dinf::DinfBox;
 pub use edts::EdtsBox;
+pub use elng::ElngBox;
 pub use elst::ElstBox;
 pub use emsg::EmsgBox;
 pub use ftyp::FtypBox;
 pub use hdlr::HdlrBox;
-pub use hev1::Hev1Box;
-pub use ilst::IlstBox;
+pub use hev1::{Hev1Box, HevcFormat};
+pub use ilst::{CovrImage, IlstBox};
+pub use keys::{KeyEntry, KeysBox};
+pub use leva::LevaBox;
+pub use mdcv::MdcvBox;
 pub use mdhd::MdhdBox;
 pub use mdia::MdiaBox;
 pub use mehd::MehdBox;
-pub use meta::MetaBox;
+pub use meta::{MdtaItem, MetaBox, MetaChild};
+pub use mett::{MettBox, MetxBox};
 pub use mfhd::MfhdBox;
+pub use mime::MimeBox;
 pub use minf::MinfBox;
 pub use moof::MoofBox;
 pub use moov::MoovBox;
 pub use mp4a::Mp4aBox;
 pub use mvex::MvexBox;
 pub use mvhd::MvhdBox;
+pub use nmhd::NmhdBox;
+pub use padb::PadbBox;
+pub use pcm::{PcmBox, PcmFormat};
+pub use saio::SaioBox;
+pub use saiz::SaizBox;
 pub use smhd::SmhdBox;
 pub use stbl::StblBox;
 pub use stco::StcoBox;
-pub use stsc::StscBox;
-pub use stsd::StsdBox;
+pub use sthd::SthdBox;
+pub use stpp::{StppBox, StxtBox};
+pub use stsc::{StscBox, StscEntry};
+pub use stsd::{SampleEntry, StsdBox};
 pub use stss::StssBox;
 pub use stsz::StszBox;
-pub use stts::SttsBox;
+pub use stts::{SttsBox, SttsEntry};
+pub use subs::{SubsBox, SubsEntry, Subsample};
+pub use tapt::{ClefBox, EnofBox, ProfBox, TaptBox};
 pub use tfdt::TfdtBox;
 pub use tfhd::TfhdBox;
-pub use tkhd::TkhdBox;
+pub use tkhd::{Matrix, TkhdBox};
 pub use traf::TrafBox;
 pub use trak::TrakBox;
+pub use trep::TrepBox;
 pub use trex::TrexBox;
 pub use trun::TrunBox;
 pub use tx3g::Tx3gBox;
 pub use udta::UdtaBox;
-pub use vmhd::VmhdBox;
+pub use urim::{UriBox, UriInitBox, UrimBox};
+pub use vmhd::{RgbColor, VmhdBox};
 pub use vp09::Vp09Box;
 pub use vpcc::VpccBox;
 
 pub const HEADER_SIZE: u64 = 8;
-// const HEADER_LARGE_SIZE: u64 = 16;
+pub const HEADER_LARGE_SIZE: u64 = 16;
 pub const HEADER_EXT_SIZE: u64 = 4;
 
 macro_rules! boxtype {
@@ -192,15 +270,22 @@ boxtype! {
     MvexBox => 0x6d766578,
     MehdBox => 0x6d656864,
     TrexBox => 0x74726578,
+    TrepBox => 0x74726570,
+    LevaBox => 0x6c657661,
     EmsgBox => 0x656d7367,
     MoofBox => 0x6d6f6f66,
     TkhdBox => 0x746b6864,
+    TaptBox => 0x74617074,
+    ClefBox => 0x636c6566,
+    ProfBox => 0x70726f66,
+    EnofBox => 0x656e6f66,
     TfhdBox => 0x74666864,
     TfdtBox => 0x74666474,
     EdtsBox => 0x65647473,
     MdiaBox => 0x6d646961,
     ElstBox => 0x656c7374,
     MdhdBox => 0x6d646864,
+    ElngBox => 0x656c6e67,
     HdlrBox => 0x68646c72,
     MinfBox => 0x6d696e66,
     VmhdBox => 0x766d6864,
@@ -209,6 +294,10 @@ boxtype! {
     SttsBox => 0x73747473,
     CttsBox => 0x63747473,
     StssBox => 0x73747373,
+    SubsBox => 0x73756273,
+    PadbBox => 0x70616462,
+    SaizBox => 0x7361697a,
+    SaioBox => 0x7361696f,
     StscBox => 0x73747363,
     StszBox => 0x7374737A,
     StcoBox => 0x7374636F,
@@ -217,28 +306,53 @@ boxtype! {
     TrafBox => 0x74726166,
     TrunBox => 0x7472756E,
     UdtaBox => 0x75647461,
+    CprtBox => 0x63707274,
     MetaBox => 0x6d657461,
     DinfBox => 0x64696e66,
     DrefBox => 0x64726566,
     UrlBox  => 0x75726C20,
     SmhdBox => 0x736d6864,
+    NmhdBox => 0x6e6d6864,
+    SthdBox => 0x73746864,
     Avc1Box => 0x61766331,
     AvcCBox => 0x61766343,
     Hev1Box => 0x68657631,
+    Hvc1Box => 0x68766331,
     HvcCBox => 0x68766343,
     Mp4aBox => 0x6d703461,
     EsdsBox => 0x65736473,
     Tx3gBox => 0x74783367,
+    MettBox => 0x6d657474,
+    MetxBox => 0x6d657478,
+    UrimBox => 0x7572696d,
+    UriBox => 0x75726920,
+    UriInitBox => 0x75726949,
+    MimeBox => 0x6d696d65,
+    StppBox => 0x73747070,
+    StxtBox => 0x73747874,
     VpccBox => 0x76706343,
     Vp09Box => 0x76703039,
+    MdcvBox => 0x6d646376,
+    ClliBox => 0x636c6c69,
+    AmveBox => 0x616d7665,
     DataBox => 0x64617461,
     IlstBox => 0x696c7374,
+    KeysBox => 0x6b657973,
     NameBox => 0xa96e616d,
     DayBox => 0xa9646179,
     CovrBox => 0x636f7672,
     DescBox => 0x64657363,
+    XyzBox => 0xa978797a,
     WideBox => 0x77696465,
-    WaveBox => 0x77617665
+    WaveBox => 0x77617665,
+    IpcmBox => 0x6970636d,
+    FpcmBox => 0x6670636d,
+    LpcmBox => 0x6c70636d,
+    SowtBox => 0x736f7774,
+    TwosBox => 0x74776f73,
+    PcmCBox => 0x70636d43,
+    Ac4Box => 0x61632d34,
+    Dac4Box => 0x64616334
 }
 
 pub trait Mp4Box: Sized {
@@ -246,6 +360,63 @@ pub trait Mp4Box: Sized {
     fn box_size(&self) -> u64;
     fn to_json(&self) -> Result<String>;
     fn summary(&self) -> Result<String>;
+
+    /// Serialize the box, header included, to its on-disk form.
+    fn to_bytes(&self) -> Result<Vec<u8>>
+    where
+        Self: for<'a> WriteBox<&'a mut Vec<u8>>,
+    {
+        let mut buf = Vec::with_capacity(self.box_size() as usize);
+        self.write_box(&mut buf)?;
+        debug_assert_box_size(self, buf.len() as u64);
+        Ok(buf)
+    }
+}
+
+// Write `b` to `writer`, checking in debug builds that the bytes written add
+// up to its `box_size`. A child whose `get_size` disagrees with its
+// `write_box` would otherwise leave every enclosing box header wrong.
+pub(crate) fn write_box_checked<B, W>(b: &B, writer: &mut W) -> Result<u64>
+where
+    B: Mp4Box + for<'a, 'w> WriteBox<&'a mut CountingWriter<'w, W>>,
+    W: Write,
+{
+    let mut counter = CountingWriter {
+        inner: writer,
+        count: 0,
+    };
+    let size = b.write_box(&mut counter)?;
+    debug_assert_box_size(b, counter.count);
+    Ok(size)
+}
+
+fn debug_assert_box_size<B: Mp4Box>(b: &B, written: u64) {
+    debug_assert_eq!(
+        written,
+        b.box_size(),
+        "{} box wrote {} bytes but its box_size is {}",
+        b.box_type(),
+        written,
+        b.box_size()
+    );
+}
+
+// A writer that counts the bytes written through it.
+pub(crate) struct CountingWriter<'w, W> {
+    inner: &'w mut W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 pub trait ReadBox<T>: Sized {
@@ -259,12 +430,59 @@ pub trait WriteBox<T>: Sized {
 #[derive(Debug, Clone, Copy)]
 pub struct BoxHeader {
     pub name: BoxType,
+    /// The box size as if its header were [HEADER_SIZE] bytes long, so the
+    /// body is always `size - HEADER_SIZE` bytes; 0 for a box extending to
+    /// the end of the file.
     pub size: u64,
+    /// The length of the serialized header: [HEADER_LARGE_SIZE] for a box
+    /// with a 64-bit largesize, [HEADER_SIZE] otherwise.
+    pub header_size: u64,
+    /// The offset of the start of the box, if read with [BoxHeader::read_at].
+    pub offset: Option<u64>,
 }
 
 impl BoxHeader {
     pub fn new(name: BoxType, size: u64) -> Self {
-        Self { name, size }
+        let header_size = if size > u32::MAX as u64 {
+            HEADER_LARGE_SIZE
+        } else {
+            HEADER_SIZE
+        };
+        Self {
+            name,
+            size,
+            header_size,
+            offset: None,
+        }
+    }
+
+    /// Like [BoxHeader::read], also recording the offset the box starts at.
+    pub fn read_at<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let offset = reader.stream_position()?;
+        let header = Self::read(reader)?;
+        Ok(BoxHeader {
+            offset: Some(offset),
+            ..header
+        })
+    }
+
+    /// The range of offsets of the box body, if the header was read with
+    /// [BoxHeader::read_at] and the box does not extend to the end of the
+    /// file. Fails when the box would end past the largest 64-bit offset.
+    pub fn body_range(&self) -> Result<Option<std::ops::Range<u64>>> {
+        let offset = match (self.offset, self.size) {
+            (Some(_), 0) | (None, _) => return Ok(None),
+            (Some(offset), _) => offset,
+        };
+        let start = offset.checked_add(self.header_size);
+        let end = self
+            .size
+            .checked_sub(HEADER_SIZE)
+            .and_then(|body| start?.checked_add(body));
+        match (start, end) {
+            (Some(start), Some(end)) => Ok(Some(start..end)),
+            _ => Err(Error::InvalidData("box size overflows its offset")),
+        }
     }
 
     // TODO: if size is 0, then this box is the last one in the file
@@ -297,11 +515,17 @@ impl BoxHeader {
                     1..=15 => return Err(Error::InvalidData("64-bit box size too small")),
                     16..=u64::MAX => largesize - 8,
                 },
+                header_size: HEADER_LARGE_SIZE,
+                offset: None,
             })
+        } else if (2..8).contains(&size) {
+            Err(Error::InvalidData("box size smaller than its header"))
         } else {
             Ok(BoxHeader {
                 name: BoxType::from(typ),
                 size: size as u64,
+                header_size: HEADER_SIZE,
+                offset: None,
             })
         }
     }
@@ -332,6 +556,8 @@ pub fn write_box_header_ext<W: Write>(w: &mut W, v: u8, f: u32) -> Result<u64> {
     Ok(4)
 }
 
+/// The offset of the start of a box whose [HEADER_SIZE]-byte header was just
+/// read. Use [BoxHeader::read_at] for boxes that may have a largesize.
 pub fn box_start<R: Seek>(seeker: &mut R) -> Result<u64> {
     Ok(seeker.stream_position()? - HEADER_SIZE)
 }
@@ -352,6 +578,82 @@ pub fn skip_box<S: Seek>(seeker: &mut S, size: u64) -> Result<()> {
     Ok(())
 }
 
+// The position after the child box a container's read loop has just read,
+// which must lie past `current`, the position before it. A child of size 0
+// would otherwise send the loop back to its own header forever.
+pub(crate) fn next_box_position<S: Seek>(seeker: &mut S, current: u64) -> Result<u64> {
+    let pos = seeker.stream_position()?;
+    if pos <= current {
+        return Err(Error::InvalidData("box does not advance past its header"));
+    }
+    Ok(pos)
+}
+
+/// A box located by its header, without parsing its body.
+#[derive(Debug, Clone)]
+pub(crate) struct ChildBox {
+    pub(crate) name: BoxType,
+    pub(crate) start: u64,
+    pub(crate) body: std::ops::Range<u64>,
+}
+
+impl ChildBox {
+    pub(crate) fn size(&self) -> u64 {
+        self.body.end - self.start
+    }
+
+    // The size the box readers expect, as from an 8-byte header.
+    pub(crate) fn read_size(&self) -> u64 {
+        self.body.end - self.body.start + HEADER_SIZE
+    }
+}
+
+// The boxes between `start` and `end`, where a box of size 0 extends to
+// `end`. Each box must lie within `end`.
+pub(crate) fn child_boxes<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+) -> Result<Vec<ChildBox>> {
+    let boxes = scan_child_boxes(reader, start, end)?;
+    if boxes.last().is_some_and(|b| b.body.end > end) {
+        return Err(Error::InvalidData("box extends past its parent"));
+    }
+    Ok(boxes)
+}
+
+// Like [child_boxes], but the last box may extend past `end`, as in a
+// truncated file. A box whose end overflows a 64-bit offset is an error.
+pub(crate) fn scan_child_boxes<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+) -> Result<Vec<ChildBox>> {
+    let mut boxes = Vec::new();
+    let mut offset = start;
+    while end.saturating_sub(offset) >= HEADER_SIZE {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header = BoxHeader::read_at(reader)?;
+        let body = match header.body_range()? {
+            Some(body) => body,
+            None => {
+                let body_start = offset + header.header_size;
+                body_start..end.max(body_start)
+            }
+        };
+        if body.end <= offset {
+            return Err(Error::InvalidData("box does not advance past its header"));
+        }
+        boxes.push(ChildBox {
+            name: header.name,
+            start: offset,
+            body: body.clone(),
+        });
+        offset = body.end;
+    }
+    Ok(boxes)
+}
+
 pub fn write_zeros<W: Write>(writer: &mut W, size: u64) -> Result<()> {
     for _ in 0..size {
         writer.write_u8(0)?;
@@ -359,6 +661,55 @@ pub fn write_zeros<W: Write>(writer: &mut W, size: u64) -> Result<()> {
     Ok(())
 }
 
+// A null-terminated UTF-8 string ending at or before `end`, with invalid
+// bytes replaced. Reads as empty when the box ends first, as optional
+// trailing strings of sample entries may.
+pub(crate) fn read_cstring<R: Read + Seek>(reader: &mut R, end: u64) -> Result<String> {
+    let mut bytes = Vec::new();
+    while reader.stream_position()? < end {
+        match reader.read_u8()? {
+            0 => break,
+            byte => bytes.push(byte),
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub(crate) fn write_cstring<W: Write>(writer: &mut W, string: &str) -> Result<()> {
+    writer.write_all(string.as_bytes())?;
+    writer.write_u8(0)?;
+    Ok(())
+}
+
+// The HDR boxes a video sample entry carries after its codec configuration.
+pub(crate) fn hdr_boxes_size(
+    mdcv: &Option<MdcvBox>,
+    clli: &Option<ClliBox>,
+    amve: &Option<AmveBox>,
+) -> u64 {
+    mdcv.as_ref().map_or(0, |b| b.box_size())
+        + clli.as_ref().map_or(0, |b| b.box_size())
+        + amve.as_ref().map_or(0, |b| b.box_size())
+}
+
+pub(crate) fn write_hdr_boxes<W: Write>(
+    writer: &mut W,
+    mdcv: &Option<MdcvBox>,
+    clli: &Option<ClliBox>,
+    amve: &Option<AmveBox>,
+) -> Result<()> {
+    if let Some(mdcv) = mdcv {
+        mdcv.write_box(writer)?;
+    }
+    if let Some(clli) = clli {
+        clli.write_box(writer)?;
+    }
+    if let Some(amve) = amve {
+        amve.write_box(writer)?;
+    }
+    Ok(())
+}
+
 mod value_u32 {
     use crate::types::FixedPointU16;
     use serde::{self, Serializer};
@@ -408,6 +759,90 @@ mod tests {
         assert_eq!(ftyp_fcc, ftyp_fcc2);
     }
 
+    #[test]
+    fn test_to_bytes() {
+        let src_box = mvex::MvexBox {
+            mehd: Some(mehd::MehdBox {
+                fragment_duration: 1000,
+                ..Default::default()
+            }),
+            trexs: vec![TrexBox {
+                track_id: 1,
+                default_sample_description_index: 1,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let buf = src_box.to_bytes().unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = std::io::Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MvexBox);
+        let dst_box = mvex::MvexBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+
+    #[test]
+    fn test_read_cstring() {
+        let mut reader = std::io::Cursor::new(b"text/xml\0caf\xe9\0tail".to_vec());
+        assert_eq!(read_cstring(&mut reader, 14).unwrap(), "text/xml");
+        assert_eq!(read_cstring(&mut reader, 14).unwrap(), "caf\u{FFFD}");
+        assert_eq!(read_cstring(&mut reader, 14).unwrap(), "");
+    }
+
+    #[test]
+    fn test_write_box_checked() {
+        let src_box = UdtaBox {
+            meta: Some(MetaBox::default()),
+            cprt: Some(CprtBox::new("eng", "(c) 2024 Example")),
+            children: vec![udta::UserDefinedBox {
+                name: String::from("\u{a9}xyz"),
+                size: 0,
+                data: b"+37.7749-122.4194/".to_vec(),
+            }],
+        };
+        let mut buf = Vec::new();
+        let size = write_box_checked(&src_box, &mut buf).unwrap();
+        assert_eq!(size, buf.len() as u64);
+    }
+
+    // A box whose get_size leaves out a byte of what it writes.
+    struct MissizedBox;
+
+    impl Mp4Box for MissizedBox {
+        fn box_type(&self) -> BoxType {
+            BoxType::FreeBox
+        }
+
+        fn box_size(&self) -> u64 {
+            HEADER_SIZE
+        }
+
+        fn to_json(&self) -> Result<String> {
+            Ok(String::new())
+        }
+
+        fn summary(&self) -> Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    impl<W: Write> WriteBox<&mut W> for MissizedBox {
+        fn write_box(&self, writer: &mut W) -> Result<u64> {
+            BoxHeader::new(self.box_type(), self.box_size()).write(writer)?;
+            writer.write_u8(0)?;
+            Ok(self.box_size())
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "free box wrote 9 bytes but its box_size is 8")]
+    fn test_write_box_checked_size_mismatch() {
+        write_box_checked(&MissizedBox, &mut Vec::new()).unwrap();
+    }
+
     #[test]
     fn test_largesize_too_small() {
         let error = BoxHeader::read(&mut &[0, 0, 0, 1, 1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 7][..]);
@@ -431,4 +866,48 @@ mod tests {
         let header = BoxHeader::read(&mut &[0, 0, 0, 1, 1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 16][..]);
         assert!(matches!(header, Ok(BoxHeader { size: 8, .. })));
     }
+
+    #[test]
+    fn test_header_offset() {
+        let mut data = vec![0, 0, 0, 8, b'f', b'r', b'e', b'e'];
+        data.extend_from_slice(&[0, 0, 0, 1, b'm', b'd', b'a', b't', 0, 0, 0, 0, 0, 0, 0, 20]);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        let mut reader = std::io::Cursor::new(&data);
+
+        let free = BoxHeader::read_at(&mut reader).unwrap();
+        assert_eq!(free.header_size, HEADER_SIZE);
+        assert_eq!(free.offset, Some(0));
+        assert_eq!(free.body_range().unwrap(), Some(8..8));
+
+        let mdat = BoxHeader::read_at(&mut reader).unwrap();
+        assert_eq!(mdat.name, BoxType::MdatBox);
+        assert_eq!(mdat.header_size, HEADER_LARGE_SIZE);
+        assert_eq!(mdat.offset, Some(8));
+        assert_eq!(mdat.body_range().unwrap(), Some(24..28));
+    }
+
+    #[test]
+    fn test_body_range_overflow() {
+        let mut data = vec![0; 24];
+        data.extend_from_slice(&[0, 0, 0, 1, b'm', b'd', b'a', b't']);
+        data.extend_from_slice(&0xFFFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+        let mut reader = std::io::Cursor::new(&data);
+        reader.set_position(24);
+
+        let mdat = BoxHeader::read_at(&mut reader).unwrap();
+        assert!(matches!(mdat.body_range(), Err(Error::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_header_size_too_small() {
+        for size in 2..8u32 {
+            let mut data = size.to_be_bytes().to_vec();
+            data.extend_from_slice(b"free");
+            let mut reader = std::io::Cursor::new(&data);
+            assert!(matches!(
+                BoxHeader::read(&mut reader),
+                Err(Error::InvalidData(_))
+            ));
+        }
+    }
 }
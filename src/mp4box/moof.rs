@@ -57,7 +57,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoofBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "moof box contains a box with a larger size than it",
@@ -77,7 +77,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoofBox {
                     skip_box(reader, s)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if mfhd.is_none() {
@@ -35,11 +35,37 @@ impl MoovBox {
         if let Some(meta) = &self.meta {
             size += meta.box_size();
         }
+        if let Some(mvex) = &self.mvex {
+            size += mvex.box_size();
+        }
         if let Some(udta) = &self.udta {
             size += udta.box_size();
         }
         size
     }
+
+    /// Add `delta` to the chunk offsets of every track; see
+    /// [StblBox::rebase_chunk_offsets]. Fails without changing any track if
+    /// one of them cannot be rebased.
+    pub fn rebase_chunk_offsets(&mut self, delta: i64) -> Result<()> {
+        let offsets = self
+            .traks
+            .iter()
+            .map(|trak| trak.mdia.minf.stbl.rebased_chunk_offsets(delta))
+            .collect::<Result<Vec<_>>>()?;
+        for (trak, offsets) in self.traks.iter_mut().zip(offsets) {
+            trak.mdia.minf.stbl.set_chunk_offsets(offsets);
+        }
+        Ok(())
+    }
+
+    /// Compress the sample tables of every track; see
+    /// [StblBox::optimize_sample_tables].
+    pub fn optimize_sample_tables(&mut self) {
+        for trak in self.traks.iter_mut() {
+            trak.mdia.minf.stbl.optimize_sample_tables();
+        }
+    }
 }
 
 impl Mp4Box for MoovBox {
@@ -76,7 +102,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoovBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "moov box contains a box with a larger size than it",
@@ -106,7 +132,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoovBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if mvhd.is_none() {
@@ -137,6 +163,9 @@ impl<W: Write> WriteBox<&mut W> for MoovBox {
         if let Some(meta) = &self.meta {
             meta.write_box(writer)?;
         }
+        if let Some(mvex) = &self.mvex {
+            mvex.write_box(writer)?;
+        }
         if let Some(udta) = &self.udta {
             udta.write_box(writer)?;
         }
@@ -154,7 +183,13 @@ mod tests {
     fn test_moov() {
         let src_box = MoovBox {
             mvhd: MvhdBox::default(),
-            mvex: None, // XXX mvex is not written currently
+            mvex: Some(MvexBox {
+                trexs: vec![TrexBox {
+                    track_id: 1,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
             traks: vec![],
             meta: Some(MetaBox::default()),
             udta: Some(UdtaBox::default()),
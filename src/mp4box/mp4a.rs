@@ -1,7 +1,9 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use serde::Serialize;
+use std::convert::TryFrom;
 use std::io::{Read, Seek, Write};
 
+use crate::mp4box::bits::{BitReader, BitWriter};
 use crate::mp4box::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
@@ -94,6 +96,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for Mp4aBox {
             // Skip QTFF
             reader.read_u64::<BigEndian>()?;
             reader.read_u64::<BigEndian>()?;
+        } else if version == 2 {
+            // Skip QTFF v2 sound description fields
+            skip_bytes(reader, 36)?;
         }
 
         // Find esds in mp4a or wave
@@ -105,7 +110,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for Mp4aBox {
                 break;
             }
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "mp4a box contains a box with a larger size than it",
@@ -116,11 +121,17 @@ impl<R: Read + Seek> ReadBox<&mut R> for Mp4aBox {
                 break;
             } else if name == BoxType::WaveBox {
                 // Typically contains frma, mp4a, esds, and a terminator atom
+                esds = read_wave_esds(reader, current + s)?;
+                if esds.is_some() {
+                    break;
+                }
+                skip_bytes_to(reader, current + s)?;
             } else {
                 // Skip boxes
                 let skip_to = current + s;
                 skip_bytes_to(reader, skip_to)?;
             }
+            next_box_position(reader, current)?;
         }
 
         skip_bytes_to(reader, end)?;
@@ -135,6 +146,26 @@ impl<R: Read + Seek> ReadBox<&mut R> for Mp4aBox {
     }
 }
 
+// Find the esds among the children of a QuickTime wave atom ending at `end`.
+fn read_wave_esds<R: Read + Seek>(reader: &mut R, end: u64) -> Result<Option<EsdsBox>> {
+    loop {
+        let current = reader.stream_position()?;
+        if current + HEADER_SIZE > end {
+            return Ok(None);
+        }
+        let BoxHeader { name, size: s, .. } = BoxHeader::read(reader)?;
+        if s < HEADER_SIZE || current + s > end {
+            // A zero-sized or overlong atom ends the wave.
+            return Ok(None);
+        }
+        if name == BoxType::EsdsBox {
+            return Ok(Some(EsdsBox::read_box(reader, s)?));
+        }
+        // frma, the mp4a format atom, the terminator and anything else
+        skip_bytes_to(reader, current + s)?;
+    }
+}
+
 impl<W: Write> WriteBox<&mut W> for Mp4aBox {
     fn write_box(&self, writer: &mut W) -> Result<u64> {
         let size = self.box_size();
@@ -184,8 +215,8 @@ impl Mp4Box for EsdsBox {
         HEADER_SIZE
             + HEADER_EXT_SIZE
             + 1
-            + size_of_length(ESDescriptor::desc_size()) as u64
-            + ESDescriptor::desc_size() as u64
+            + size_of_length(self.es_desc.desc_size()) as u64
+            + self.es_desc.desc_size() as u64
     }
 
     fn to_json(&self) -> Result<String> {
@@ -215,7 +246,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for EsdsBox {
                 }
                 _ => break,
             }
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if es_desc.is_none() {
@@ -247,7 +278,7 @@ impl<W: Write> WriteBox<&mut W> for EsdsBox {
 
 trait Descriptor: Sized {
     fn desc_tag() -> u8;
-    fn desc_size() -> u32;
+    fn desc_size(&self) -> u32;
 }
 
 trait ReadDesc<T>: Sized {
@@ -285,7 +316,7 @@ fn size_of_length(size: u32) -> u32 {
 fn write_desc<W: Write>(writer: &mut W, tag: u8, size: u32) -> Result<u64> {
     writer.write_u8(tag)?;
 
-    if size as u64 > std::u32::MAX as u64 {
+    if size as u64 > u32::MAX as u64 {
         return Err(Error::InvalidData("invalid descriptor length range"));
     }
 
@@ -305,6 +336,21 @@ fn write_desc<W: Write>(writer: &mut W, tag: u8, size: u32) -> Result<u64> {
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct ESDescriptor {
     pub es_id: u16,
+    pub stream_priority: u8,
+
+    /// The ES_ID of the stream this one depends on, if streamDependenceFlag
+    /// is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on_es_id: Option<u16>,
+
+    /// The URL the stream is found at, if URL_Flag is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// The ES_ID of the stream carrying the clock reference, if
+    /// OCRstreamFlag is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ocr_es_id: Option<u16>,
 
     pub dec_config: DecoderConfigDescriptor,
     pub sl_config: SLConfigDescriptor,
@@ -314,6 +360,10 @@ impl ESDescriptor {
     pub fn new(config: &AacConfig) -> Self {
         Self {
             es_id: 1,
+            stream_priority: 0,
+            depends_on_es_id: None,
+            url: None,
+            ocr_es_id: None,
             dec_config: DecoderConfigDescriptor::new(config),
             sl_config: SLConfigDescriptor::new(),
         }
@@ -325,13 +375,16 @@ impl Descriptor for ESDescriptor {
         0x03
     }
 
-    fn desc_size() -> u32 {
-        3 + 1
-            + size_of_length(DecoderConfigDescriptor::desc_size())
-            + DecoderConfigDescriptor::desc_size()
+    fn desc_size(&self) -> u32 {
+        3 + self.depends_on_es_id.map_or(0, |_| 2)
+            + self.url.as_ref().map_or(0, |url| 1 + url.len() as u32)
+            + self.ocr_es_id.map_or(0, |_| 2)
+            + 1
+            + size_of_length(self.dec_config.desc_size())
+            + self.dec_config.desc_size()
             + 1
-            + size_of_length(SLConfigDescriptor::desc_size())
-            + SLConfigDescriptor::desc_size()
+            + size_of_length(self.sl_config.desc_size())
+            + self.sl_config.desc_size()
     }
 }
 
@@ -340,7 +393,24 @@ impl<R: Read + Seek> ReadDesc<&mut R> for ESDescriptor {
         let start = reader.stream_position()?;
 
         let es_id = reader.read_u16::<BigEndian>()?;
-        reader.read_u8()?; // XXX flags must be 0
+        let flags = reader.read_u8()?;
+        let depends_on_es_id = if flags & 0x80 != 0 {
+            Some(reader.read_u16::<BigEndian>()?)
+        } else {
+            None
+        };
+        let url = if flags & 0x40 != 0 {
+            let mut buf = vec![0; reader.read_u8()? as usize];
+            reader.read_exact(&mut buf)?;
+            Some(String::from_utf8_lossy(&buf).into_owned())
+        } else {
+            None
+        };
+        let ocr_es_id = if flags & 0x20 != 0 {
+            Some(reader.read_u16::<BigEndian>()?)
+        } else {
+            None
+        };
 
         let mut dec_config = None;
         let mut sl_config = None;
@@ -360,11 +430,15 @@ impl<R: Read + Seek> ReadDesc<&mut R> for ESDescriptor {
                     skip_bytes(reader, desc_size as u64)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         Ok(ESDescriptor {
             es_id,
+            stream_priority: flags & 0x1F,
+            depends_on_es_id,
+            url,
+            ocr_es_id,
             dec_config: dec_config.unwrap_or_default(),
             sl_config: sl_config.unwrap_or_default(),
         })
@@ -373,11 +447,28 @@ impl<R: Read + Seek> ReadDesc<&mut R> for ESDescriptor {
 
 impl<W: Write> WriteDesc<&mut W> for ESDescriptor {
     fn write_desc(&self, writer: &mut W) -> Result<u32> {
-        let size = Self::desc_size();
+        let size = self.desc_size();
         write_desc(writer, Self::desc_tag(), size)?;
 
         writer.write_u16::<BigEndian>(self.es_id)?;
-        writer.write_u8(0)?;
+        let flags = (self.depends_on_es_id.is_some() as u8) << 7
+            | (self.url.is_some() as u8) << 6
+            | (self.ocr_es_id.is_some() as u8) << 5
+            | (self.stream_priority & 0x1F);
+        writer.write_u8(flags)?;
+        if let Some(depends_on_es_id) = self.depends_on_es_id {
+            writer.write_u16::<BigEndian>(depends_on_es_id)?;
+        }
+        if let Some(ref url) = self.url {
+            if url.len() > u8::MAX as usize {
+                return Err(Error::InvalidData("ES_Descriptor URL is too long"));
+            }
+            writer.write_u8(url.len() as u8)?;
+            writer.write_all(url.as_bytes())?;
+        }
+        if let Some(ocr_es_id) = self.ocr_es_id {
+            writer.write_u16::<BigEndian>(ocr_es_id)?;
+        }
 
         self.dec_config.write_desc(writer)?;
         self.sl_config.write_desc(writer)?;
@@ -417,10 +508,8 @@ impl Descriptor for DecoderConfigDescriptor {
         0x04
     }
 
-    fn desc_size() -> u32 {
-        13 + 1
-            + size_of_length(DecoderSpecificDescriptor::desc_size())
-            + DecoderSpecificDescriptor::desc_size()
+    fn desc_size(&self) -> u32 {
+        13 + 1 + size_of_length(self.dec_specific.desc_size()) + self.dec_specific.desc_size()
     }
 }
 
@@ -450,7 +539,7 @@ impl<R: Read + Seek> ReadDesc<&mut R> for DecoderConfigDescriptor {
                     skip_bytes(reader, desc_size as u64)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         Ok(DecoderConfigDescriptor {
@@ -467,7 +556,7 @@ impl<R: Read + Seek> ReadDesc<&mut R> for DecoderConfigDescriptor {
 
 impl<W: Write> WriteDesc<&mut W> for DecoderConfigDescriptor {
     fn write_desc(&self, writer: &mut W) -> Result<u32> {
-        let size = Self::desc_size();
+        let size = self.desc_size();
         write_desc(writer, Self::desc_tag(), size)?;
 
         writer.write_u8(self.object_type_indication)?;
@@ -482,21 +571,203 @@ impl<W: Write> WriteDesc<&mut W> for DecoderConfigDescriptor {
     }
 }
 
+/// AudioSpecificConfig as defined in ISO/IEC 14496-3.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct DecoderSpecificDescriptor {
+    /// Audio object type of the core coder, e.g. 2 (AAC LC) for HE-AAC.
     pub profile: u8,
     pub freq_index: u8,
     pub chan_conf: u8,
+
+    /// Explicit sampling frequency, present when `freq_index` is 15.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling_frequency: Option<u32>,
+
+    /// Extension audio object type, 5 (SBR) when SBR or PS is signalled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_profile: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_freq_index: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_sampling_frequency: Option<u32>,
+
+    pub sbr_present: bool,
+    pub ps_present: bool,
+
+    /// Channel count from the ALSSpecificConfig, which ALS streams signal
+    /// instead of a `chan_conf`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub als_channel_count: Option<u16>,
+
+    /// The serialized AudioSpecificConfig, as read or as built by
+    /// [DecoderSpecificDescriptor::new], written back unchanged so that
+    /// parts not parsed into the fields above are kept. When empty, the
+    /// config is serialized from the fields.
+    #[serde(skip)]
+    pub data: Vec<u8>,
 }
 
+const AOT_SBR: u8 = 5;
+const AOT_ER_BSAC: u8 = 22;
+const AOT_PS: u8 = 29;
+const AOT_ALS: u8 = 36;
+const ALS_ID: u32 = 0x414C5300; // "ALS\0"
+const SYNC_EXTENSION_SBR: u32 = 0x2b7;
+const SYNC_EXTENSION_PS: u32 = 0x548;
+
 impl DecoderSpecificDescriptor {
     pub fn new(config: &AacConfig) -> Self {
-        Self {
+        let (extension_freq_index, extension_sampling_frequency) =
+            if config.sbr_present || config.ps_present {
+                match config.extension_freq_index {
+                    Some(index) => (Some(index as u8), None),
+                    // SBR doubles core rates up to 48kHz unless told
+                    // otherwise; the table index of twice a rate is three
+                    // lower, and rates without one are written explicitly.
+                    // Above 48kHz it runs downsampled, at the core rate.
+                    None => match config.freq_index as u8 {
+                        index @ 0x0..=0x2 => (Some(index), None),
+                        index @ 0x3..=0xb => (Some(index - 3), None),
+                        _ => (Some(15), Some(default_sbr_freq(config.freq_index.freq()))),
+                    },
+                }
+            } else {
+                (None, None)
+            };
+        let mut desc = Self {
             profile: config.profile as u8,
             freq_index: config.freq_index as u8,
             chan_conf: config.chan_conf as u8,
+            sampling_frequency: None,
+            extension_profile: extension_freq_index.map(|_| AOT_SBR),
+            extension_freq_index,
+            extension_sampling_frequency,
+            sbr_present: config.sbr_present || config.ps_present,
+            ps_present: config.ps_present,
+            als_channel_count: None,
+            data: Vec::new(),
+        };
+        desc.data = desc.to_bytes();
+        desc
+    }
+
+    /// Output sampling frequency in Hz, accounting for SBR upsampling.
+    pub fn output_sampling_frequency(&self) -> Option<u32> {
+        let freq = |index: u8, explicit: Option<u32>| {
+            explicit.or_else(|| SampleFreqIndex::try_from(index).ok().map(|f| f.freq()))
+        };
+        let core = freq(self.freq_index, self.sampling_frequency)?;
+        if self.sbr_present || self.ps_present {
+            Some(
+                self.extension_freq_index
+                    .and_then(|index| freq(index, self.extension_sampling_frequency))
+                    .unwrap_or(default_sbr_freq(core)),
+            )
+        } else {
+            Some(core)
+        }
+    }
+
+    /// Output channel count, or `None` if the channel configuration is
+    /// carried in a program config element.
+    pub fn channel_count(&self) -> Option<u16> {
+        if self.ps_present {
+            return Some(2);
+        }
+        match self.chan_conf {
+            0 => self.als_channel_count,
+            1..=6 => Some(self.chan_conf as u16),
+            7 => Some(8),
+            _ => None,
         }
     }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        if !self.data.is_empty() {
+            return self.data.clone();
+        }
+        let mut bits = BitWriter::default();
+        if self.sbr_present || self.ps_present {
+            // Explicit hierarchical signaling.
+            let aot = if self.ps_present { AOT_PS } else { AOT_SBR };
+            write_audio_object_type(&mut bits, aot);
+            write_freq(&mut bits, self.freq_index, self.sampling_frequency);
+            bits.write(self.chan_conf as u32, 4);
+            write_freq(
+                &mut bits,
+                self.extension_freq_index.unwrap_or(self.freq_index),
+                self.extension_sampling_frequency,
+            );
+            write_audio_object_type(&mut bits, self.profile);
+        } else {
+            write_audio_object_type(&mut bits, self.profile);
+            write_freq(&mut bits, self.freq_index, self.sampling_frequency);
+            bits.write(self.chan_conf as u32, 4);
+        }
+        if is_ga_audio_object_type(self.profile) {
+            // GASpecificConfig: frameLengthFlag, dependsOnCoreCoder, extensionFlag
+            bits.write(0, 3);
+        }
+        bits.into_bytes()
+    }
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut bits = BitReader::new(data, "AudioSpecificConfig is truncated");
+
+        let mut desc = DecoderSpecificDescriptor {
+            profile: read_audio_object_type(&mut bits)?,
+            data: data.to_vec(),
+            ..Default::default()
+        };
+        (desc.freq_index, desc.sampling_frequency) = read_freq(&mut bits)?;
+        desc.chan_conf = bits.read(4)? as u8;
+
+        if desc.profile == AOT_SBR || desc.profile == AOT_PS {
+            desc.extension_profile = Some(AOT_SBR);
+            desc.sbr_present = true;
+            desc.ps_present = desc.profile == AOT_PS;
+            let (freq_index, freq) = read_freq(&mut bits)?;
+            desc.extension_freq_index = Some(freq_index);
+            desc.extension_sampling_frequency = freq;
+            desc.profile = read_audio_object_type(&mut bits)?;
+            if desc.profile == AOT_ER_BSAC {
+                bits.read(4)?; // extensionChannelConfiguration
+            }
+        }
+
+        if desc.profile == AOT_ALS {
+            read_als_specific_config(&mut bits, &mut desc)?;
+            return Ok(desc);
+        }
+        if !is_ga_audio_object_type(desc.profile) || desc.chan_conf == 0 {
+            // Other specific configs and program_config_element are not parsed.
+            return Ok(desc);
+        }
+        read_ga_specific_config(&mut bits, desc.profile)?;
+
+        if desc.extension_profile.is_none() && bits.remaining() >= 16 {
+            // Backward compatible signaling of SBR and PS.
+            if bits.read(11)? == SYNC_EXTENSION_SBR {
+                let extension_profile = read_audio_object_type(&mut bits)?;
+                if extension_profile == AOT_SBR {
+                    desc.sbr_present = bits.read(1)? == 1;
+                    if desc.sbr_present {
+                        desc.extension_profile = Some(extension_profile);
+                        let (freq_index, freq) = read_freq(&mut bits)?;
+                        desc.extension_freq_index = Some(freq_index);
+                        desc.extension_sampling_frequency = freq;
+                        if bits.remaining() >= 12 && bits.read(11)? == SYNC_EXTENSION_PS {
+                            desc.ps_present = bits.read(1)? == 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(desc)
+    }
 }
 
 impl Descriptor for DecoderSpecificDescriptor {
@@ -504,71 +775,98 @@ impl Descriptor for DecoderSpecificDescriptor {
         0x05
     }
 
-    fn desc_size() -> u32 {
-        2
+    fn desc_size(&self) -> u32 {
+        self.to_bytes().len() as u32
     }
 }
 
-fn get_audio_object_type(byte_a: u8, byte_b: u8) -> u8 {
-    let mut profile = byte_a >> 3;
-    if profile == 31 {
-        profile = 32 + ((byte_a & 7) | (byte_b >> 5));
+fn is_ga_audio_object_type(aot: u8) -> bool {
+    matches!(aot, 1..=4 | 6 | 7 | 17 | 19..=23)
+}
+
+fn read_audio_object_type(bits: &mut BitReader) -> Result<u8> {
+    let mut aot = bits.read(5)? as u8;
+    if aot == 31 {
+        aot = 32 + bits.read(6)? as u8;
     }
+    Ok(aot)
+}
 
-    profile
+fn write_audio_object_type(bits: &mut BitWriter, aot: u8) {
+    if aot >= 32 {
+        bits.write(31, 5);
+        bits.write((aot - 32) as u32, 6);
+    } else {
+        bits.write(aot as u32, 5);
+    }
 }
 
-fn get_chan_conf<R: Read + Seek>(
-    reader: &mut R,
-    byte_b: u8,
-    freq_index: u8,
-    extended_profile: bool,
-) -> Result<u8> {
-    let chan_conf;
+fn read_freq(bits: &mut BitReader) -> Result<(u8, Option<u32>)> {
+    let freq_index = bits.read(4)? as u8;
     if freq_index == 15 {
-        // Skip the 24 bit sample rate
-        let sample_rate = reader.read_u24::<BigEndian>()?;
-        chan_conf = ((sample_rate >> 4) & 0x0F) as u8;
-    } else if extended_profile {
-        let byte_c = reader.read_u8()?;
-        chan_conf = (byte_b & 1) | (byte_c & 0xE0);
+        Ok((freq_index, Some(bits.read(24)?)))
     } else {
-        chan_conf = (byte_b >> 3) & 0x0F;
+        Ok((freq_index, None))
     }
+}
 
-    Ok(chan_conf)
+fn write_freq(bits: &mut BitWriter, freq_index: u8, freq: Option<u32>) {
+    bits.write(freq_index as u32, 4);
+    if freq_index == 15 {
+        bits.write(freq.unwrap_or(0), 24);
+    }
 }
 
-impl<R: Read + Seek> ReadDesc<&mut R> for DecoderSpecificDescriptor {
-    fn read_desc(reader: &mut R, _size: u32) -> Result<Self> {
-        let byte_a = reader.read_u8()?;
-        let byte_b = reader.read_u8()?;
-        let profile = get_audio_object_type(byte_a, byte_b);
-        let freq_index;
-        let chan_conf;
-        if profile > 31 {
-            freq_index = (byte_b >> 1) & 0x0F;
-            chan_conf = get_chan_conf(reader, byte_b, freq_index, true)?;
-        } else {
-            freq_index = ((byte_a & 0x07) << 1) + (byte_b >> 7);
-            chan_conf = get_chan_conf(reader, byte_b, freq_index, false)?;
+fn read_ga_specific_config(bits: &mut BitReader, aot: u8) -> Result<()> {
+    bits.read(1)?; // frameLengthFlag
+    if bits.read(1)? == 1 {
+        bits.read(14)?; // coreCoderDelay
+    }
+    let extension_flag = bits.read(1)?;
+    if aot == 6 || aot == 20 {
+        bits.read(3)?; // layerNr
+    }
+    if extension_flag == 1 {
+        if aot == AOT_ER_BSAC {
+            bits.read(16)?; // numOfSubFrame, layer_length
         }
+        if matches!(aot, 17 | 19 | 20 | 23) {
+            bits.read(3)?; // resilience flags
+        }
+        bits.read(1)?; // extensionFlag3
+    }
+    Ok(())
+}
 
-        Ok(DecoderSpecificDescriptor {
-            profile,
-            freq_index,
-            chan_conf,
-        })
+fn read_als_specific_config(
+    bits: &mut BitReader,
+    desc: &mut DecoderSpecificDescriptor,
+) -> Result<()> {
+    bits.read(5)?; // fillBits
+    if bits.remaining() < 112 || bits.read(32)? != ALS_ID {
+        return Ok(());
+    }
+    bits.read(32)?; // samp_freq
+    bits.read(32)?; // samples
+    desc.als_channel_count = Some(bits.read(16)? as u16 + 1);
+    Ok(())
+}
+
+impl<R: Read + Seek> ReadDesc<&mut R> for DecoderSpecificDescriptor {
+    fn read_desc(reader: &mut R, size: u32) -> Result<Self> {
+        let mut data = vec![0; size as usize];
+        reader.read_exact(&mut data)?;
+        DecoderSpecificDescriptor::parse(&data)
     }
 }
 
 impl<W: Write> WriteDesc<&mut W> for DecoderSpecificDescriptor {
     fn write_desc(&self, writer: &mut W) -> Result<u32> {
-        let size = Self::desc_size();
+        let data = self.to_bytes();
+        let size = data.len() as u32;
         write_desc(writer, Self::desc_tag(), size)?;
 
-        writer.write_u8((self.profile << 3) + (self.freq_index >> 1))?;
-        writer.write_u8((self.freq_index << 7) + (self.chan_conf << 3))?;
+        writer.write_all(&data)?;
 
         Ok(size)
     }
@@ -588,7 +886,7 @@ impl Descriptor for SLConfigDescriptor {
         0x06
     }
 
-    fn desc_size() -> u32 {
+    fn desc_size(&self) -> u32 {
         1
     }
 }
@@ -603,7 +901,7 @@ impl<R: Read + Seek> ReadDesc<&mut R> for SLConfigDescriptor {
 
 impl<W: Write> WriteDesc<&mut W> for SLConfigDescriptor {
     fn write_desc(&self, writer: &mut W) -> Result<u32> {
-        let size = Self::desc_size();
+        let size = self.desc_size();
         write_desc(writer, Self::desc_tag(), size)?;
 
         writer.write_u8(2)?; // pre-defined
@@ -640,9 +938,12 @@ mod tests {
                             profile: 2,
                             freq_index: 3,
                             chan_conf: 1,
+                            data: vec![0x11, 0x88],
+                            ..Default::default()
                         },
                     },
                     sl_config: SLConfigDescriptor::default(),
+                    ..Default::default()
                 },
             }),
         };
@@ -680,4 +981,168 @@ mod tests {
         let dst_box = Mp4aBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(src_box, dst_box);
     }
+
+    #[test]
+    fn test_esds_stream_flags() {
+        let mut es_desc = ESDescriptor::new(&AacConfig::default());
+        es_desc.stream_priority = 3;
+        es_desc.depends_on_es_id = Some(2);
+        es_desc.url = Some("abc".to_string());
+        let src_box = EsdsBox {
+            version: 0,
+            flags: 0,
+            es_desc,
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        // ES_ID, the flags, dependsOn_ES_ID and the URL precede the
+        // DecoderConfigDescriptor.
+        assert_eq!(&buf[14..23], &[0, 1, 0xC3, 0, 2, 3, b'a', b'b', b'c']);
+        assert_eq!(buf[23], DecoderConfigDescriptor::desc_tag());
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::EsdsBox);
+        let dst_box = EsdsBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+
+    #[test]
+    fn test_mp4a_qt_wave_esds() {
+        let flat_box = Mp4aBox::new(&AacConfig::default());
+        let esds = flat_box.esds.as_ref().unwrap();
+        let mut esds_buf = Vec::new();
+        esds.write_box(&mut esds_buf).unwrap();
+
+        let mut wave = Vec::new();
+        BoxHeader::new(
+            BoxType::WaveBox,
+            HEADER_SIZE + 12 + 12 + esds_buf.len() as u64 + 8,
+        )
+        .write(&mut wave)
+        .unwrap();
+        wave.extend_from_slice(&[0, 0, 0, 12, b'f', b'r', b'm', b'a', b'm', b'p', b'4', b'a']);
+        wave.extend_from_slice(&[0, 0, 0, 12, b'm', b'p', b'4', b'a', 0, 0, 0, 0]);
+        wave.extend_from_slice(&esds_buf);
+        wave.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0]);
+
+        // QuickTime sound description version 1 with the esds inside a wave.
+        let mut buf = Vec::new();
+        BoxHeader::new(BoxType::Mp4aBox, HEADER_SIZE + 28 + 16 + wave.len() as u64)
+            .write(&mut buf)
+            .unwrap();
+        buf.write_u32::<BigEndian>(0).unwrap(); // reserved
+        buf.write_u16::<BigEndian>(0).unwrap(); // reserved
+        buf.write_u16::<BigEndian>(1).unwrap(); // data_reference_index
+        buf.write_u16::<BigEndian>(1).unwrap(); // version
+        buf.write_u16::<BigEndian>(0).unwrap(); // revision level
+        buf.write_u32::<BigEndian>(0).unwrap(); // vendor
+        buf.write_u16::<BigEndian>(flat_box.channelcount).unwrap();
+        buf.write_u16::<BigEndian>(flat_box.samplesize).unwrap();
+        buf.write_u32::<BigEndian>(0).unwrap(); // compression id, packet size
+        buf.write_u32::<BigEndian>(flat_box.samplerate.raw_value())
+            .unwrap();
+        buf.write_u32::<BigEndian>(1024).unwrap(); // samples per packet
+        buf.write_u32::<BigEndian>(0).unwrap(); // bytes per packet
+        buf.write_u32::<BigEndian>(0).unwrap(); // bytes per frame
+        buf.write_u32::<BigEndian>(2).unwrap(); // bytes per sample
+        buf.extend_from_slice(&wave);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::Mp4aBox);
+        let dst_box = Mp4aBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(reader.position(), buf.len() as u64);
+        assert_eq!(dst_box, flat_box);
+    }
+
+    #[test]
+    fn test_aac_specific_config_he_aac_v2() {
+        // Explicit hierarchical signaling: PS, 24kHz core, mono, 48kHz SBR, AAC LC.
+        let desc = DecoderSpecificDescriptor::parse(&[0xEB, 0x09, 0x88, 0x00]).unwrap();
+        assert_eq!(desc.profile, 2);
+        assert_eq!(desc.freq_index, 6);
+        assert_eq!(desc.chan_conf, 1);
+        assert_eq!(desc.extension_profile, Some(5));
+        assert_eq!(desc.extension_freq_index, Some(3));
+        assert!(desc.sbr_present);
+        assert!(desc.ps_present);
+        assert_eq!(desc.output_sampling_frequency(), Some(48000));
+        assert_eq!(desc.channel_count(), Some(2));
+        assert_eq!(desc.to_bytes(), vec![0xEB, 0x09, 0x88, 0x00]);
+    }
+
+    #[test]
+    fn test_aac_specific_config_backward_compatible_sbr() {
+        // AAC LC, 24kHz, stereo, followed by the 0x2b7 SBR sync extension at 48kHz.
+        let desc = DecoderSpecificDescriptor::parse(&[0x13, 0x10, 0x56, 0xE5, 0x98]).unwrap();
+        assert_eq!(desc.profile, 2);
+        assert_eq!(desc.freq_index, 6);
+        assert_eq!(desc.chan_conf, 2);
+        assert_eq!(desc.extension_profile, Some(5));
+        assert_eq!(desc.extension_freq_index, Some(3));
+        assert!(desc.sbr_present);
+        assert!(!desc.ps_present);
+        assert_eq!(desc.output_sampling_frequency(), Some(48000));
+        assert_eq!(desc.channel_count(), Some(2));
+    }
+
+    #[test]
+    fn test_mp4a_he_aac() {
+        let src_box = Mp4aBox::new(&AacConfig {
+            bitrate: 48000,
+            profile: AudioObjectType::AacLowComplexity,
+            freq_index: SampleFreqIndex::Freq24000,
+            chan_conf: ChannelConfig::Stereo,
+            extension_freq_index: Some(SampleFreqIndex::Freq48000),
+            sbr_present: true,
+            ps_present: false,
+        });
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::Mp4aBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = Mp4aBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        let dec_specific = &dst_box.esds.unwrap().es_desc.dec_config.dec_specific;
+        assert_eq!(dec_specific.extension_profile, Some(5));
+        assert!(dec_specific.sbr_present);
+    }
+
+    #[test]
+    fn test_mp4a_he_aac_default_extension_freq() {
+        for (freq_index, output) in [
+            (SampleFreqIndex::Freq24000, 48000),
+            (SampleFreqIndex::Freq22050, 44100),
+            (SampleFreqIndex::Freq64000, 64000),
+        ] {
+            let src_box = Mp4aBox::new(&AacConfig {
+                bitrate: 48000,
+                profile: AudioObjectType::AacLowComplexity,
+                freq_index,
+                chan_conf: ChannelConfig::Stereo,
+                extension_freq_index: None,
+                sbr_present: true,
+                ps_present: false,
+            });
+            let mut buf = Vec::new();
+            src_box.write_box(&mut buf).unwrap();
+            assert_eq!(buf.len(), src_box.box_size() as usize);
+
+            let mut reader = Cursor::new(&buf);
+            let header = BoxHeader::read(&mut reader).unwrap();
+            let dst_box = Mp4aBox::read_box(&mut reader, header.size).unwrap();
+            assert_eq!(src_box, dst_box);
+            let dec_specific = &dst_box.esds.unwrap().es_desc.dec_config.dec_specific;
+            assert_eq!(dec_specific.freq_index, freq_index as u8);
+            assert_eq!(dec_specific.output_sampling_frequency(), Some(output));
+        }
+    }
 }
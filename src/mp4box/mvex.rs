@@ -2,21 +2,44 @@ use serde::Serialize;
 use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
-use crate::mp4box::{mehd::MehdBox, trex::TrexBox};
+use crate::mp4box::{leva::LevaBox, mehd::MehdBox, trep::TrepBox, trex::TrexBox};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct MvexBox {
     pub mehd: Option<MehdBox>,
-    pub trex: TrexBox,
+
+    #[serde(rename = "trex")]
+    pub trexs: Vec<TrexBox>,
+
+    #[serde(rename = "trep")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub treps: Vec<TrepBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leva: Option<LevaBox>,
 }
 
 impl MvexBox {
     pub fn get_type(&self) -> BoxType {
-        BoxType::MdiaBox
+        BoxType::MvexBox
     }
 
     pub fn get_size(&self) -> u64 {
-        HEADER_SIZE + self.mehd.as_ref().map(|x| x.box_size()).unwrap_or(0) + self.trex.box_size()
+        let mut size = HEADER_SIZE + self.mehd.as_ref().map(|x| x.box_size()).unwrap_or(0);
+        for trex in self.trexs.iter() {
+            size += trex.box_size();
+        }
+        for trep in self.treps.iter() {
+            size += trep.box_size();
+        }
+        if let Some(leva) = &self.leva {
+            size += leva.box_size();
+        }
+        size
+    }
+
+    pub fn trex(&self, track_id: u32) -> Option<&TrexBox> {
+        self.trexs.iter().find(|trex| trex.track_id == track_id)
     }
 }
 
@@ -44,14 +67,16 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
         let start = box_start(reader)?;
 
         let mut mehd = None;
-        let mut trex = None;
+        let mut trexs = Vec::new();
+        let mut treps = Vec::new();
+        let mut leva = None;
 
         let mut current = reader.stream_position()?;
         let end = start + size;
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "mvex box contains a box with a larger size than it",
@@ -63,7 +88,13 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
                     mehd = Some(MehdBox::read_box(reader, s)?);
                 }
                 BoxType::TrexBox => {
-                    trex = Some(TrexBox::read_box(reader, s)?);
+                    trexs.push(TrexBox::read_box(reader, s)?);
+                }
+                BoxType::TrepBox => {
+                    treps.push(TrepBox::read_box(reader, s)?);
+                }
+                BoxType::LevaBox => {
+                    leva = Some(LevaBox::read_box(reader, s)?);
                 }
                 _ => {
                     // XXX warn!()
@@ -71,10 +102,10 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
-        if trex.is_none() {
+        if trexs.is_empty() {
             return Err(Error::BoxNotFound(BoxType::TrexBox));
         }
 
@@ -82,7 +113,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
 
         Ok(MvexBox {
             mehd,
-            trex: trex.unwrap(),
+            trexs,
+            treps,
+            leva,
         })
     }
 }
@@ -95,8 +128,69 @@ impl<W: Write> WriteBox<&mut W> for MvexBox {
         if let Some(mehd) = &self.mehd {
             mehd.write_box(writer)?;
         }
-        self.trex.write_box(writer)?;
+        for trex in self.trexs.iter() {
+            trex.write_box(writer)?;
+        }
+        for trep in self.treps.iter() {
+            trep.write_box(writer)?;
+        }
+        if let Some(leva) = &self.leva {
+            leva.write_box(writer)?;
+        }
 
         Ok(size)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::leva::{LevaEntry, LevelAssignment};
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_mvex_levels() {
+        let src_box = MvexBox {
+            mehd: None,
+            trexs: vec![TrexBox {
+                track_id: 1,
+                default_sample_description_index: 1,
+                ..Default::default()
+            }],
+            treps: vec![TrepBox {
+                track_id: 1,
+                ..Default::default()
+            }],
+            leva: Some(LevaBox {
+                version: 0,
+                flags: 0,
+                levels: vec![
+                    LevaEntry {
+                        track_id: 1,
+                        padding_flag: false,
+                        assignment: LevelAssignment::Track,
+                    },
+                    LevaEntry {
+                        track_id: 1,
+                        padding_flag: false,
+                        assignment: LevelAssignment::SampleGroup {
+                            grouping_type: str::parse("tele").unwrap(),
+                        },
+                    },
+                ],
+            }),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::MvexBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = MvexBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
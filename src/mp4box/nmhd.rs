@@ -0,0 +1,63 @@
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// The media header of tracks with no header of their own, such as timed
+/// metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct NmhdBox {
+    pub version: u8,
+    pub flags: u32,
+}
+
+impl NmhdBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::NmhdBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE
+    }
+}
+
+impl Mp4Box for NmhdBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        Ok(String::new())
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for NmhdBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(NmhdBox { version, flags })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for NmhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        Ok(size)
+    }
+}
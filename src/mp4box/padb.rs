@@ -0,0 +1,126 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// The number of padding bits, 0 to 7, at the end of the last byte of each
+/// sample.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct PadbBox {
+    pub version: u8,
+    pub flags: u32,
+
+    /// One value per sample, stored two per byte; the sample count is the
+    /// length.
+    #[serde(skip_serializing)]
+    pub pads: Vec<u8>,
+}
+
+impl PadbBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::PadbBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + 4 + self.pads.len().div_ceil(2) as u64
+    }
+}
+
+impl Mp4Box for PadbBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("sample_count={}", self.pads.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for PadbBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let sample_count = reader.read_u32::<BigEndian>()? as usize;
+        if reader.stream_position()? + sample_count.div_ceil(2) as u64 > start + size {
+            return Err(Error::InvalidData(
+                "padb sample_count indicates more values than could fit in the box",
+            ));
+        }
+        let mut pads = Vec::with_capacity(sample_count);
+        for i in 0..sample_count.div_ceil(2) {
+            let byte = reader.read_u8()?;
+            pads.push((byte >> 4) & 0x07);
+            if 2 * i + 1 < sample_count {
+                pads.push(byte & 0x07);
+            }
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(PadbBox {
+            version,
+            flags,
+            pads,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for PadbBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        writer.write_u32::<BigEndian>(self.pads.len() as u32)?;
+        for pair in self.pads.chunks(2) {
+            if pair.iter().any(|&pad| pad > 7) {
+                return Err(Error::InvalidData("padb value does not fit in 3 bits"));
+            }
+            let second = pair.get(1).copied().unwrap_or(0);
+            writer.write_u8((pair[0] << 4) | second)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_padb() {
+        let src_box = PadbBox {
+            version: 0,
+            flags: 0,
+            pads: vec![1, 7, 0, 3, 5],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+        assert_eq!(buf[16..], [0x17, 0x03, 0x50]);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::PadbBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = PadbBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
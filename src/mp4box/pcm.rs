@@ -0,0 +1,495 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::mp4box::*;
+
+/// The sample entry types of uncompressed audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PcmFormat {
+    /// ISO/IEC 23003-5 integer PCM, byte order given by `pcmC`.
+    Ipcm,
+    /// ISO/IEC 23003-5 floating point PCM, byte order given by `pcmC`.
+    Fpcm,
+    /// QuickTime linear PCM, described by the version 2 sound description flags.
+    Lpcm,
+    /// QuickTime little-endian signed integer PCM.
+    Sowt,
+    /// QuickTime big-endian signed integer PCM.
+    Twos,
+}
+
+impl PcmFormat {
+    pub fn box_type(&self) -> BoxType {
+        match self {
+            PcmFormat::Ipcm => BoxType::IpcmBox,
+            PcmFormat::Fpcm => BoxType::FpcmBox,
+            PcmFormat::Lpcm => BoxType::LpcmBox,
+            PcmFormat::Sowt => BoxType::SowtBox,
+            PcmFormat::Twos => BoxType::TwosBox,
+        }
+    }
+
+    pub fn from_box_type(box_type: BoxType) -> Option<Self> {
+        match box_type {
+            BoxType::IpcmBox => Some(PcmFormat::Ipcm),
+            BoxType::FpcmBox => Some(PcmFormat::Fpcm),
+            BoxType::LpcmBox => Some(PcmFormat::Lpcm),
+            BoxType::SowtBox => Some(PcmFormat::Sowt),
+            BoxType::TwosBox => Some(PcmFormat::Twos),
+            _ => None,
+        }
+    }
+
+    // Whether the entry is a QuickTime sound description, whose version 1
+    // carries 16 more bytes than the ISO AudioSampleEntryV1.
+    fn is_quicktime(&self) -> bool {
+        matches!(self, PcmFormat::Lpcm | PcmFormat::Sowt | PcmFormat::Twos)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PcmBox {
+    pub format: PcmFormat,
+    pub data_reference_index: u16,
+    pub channelcount: u16,
+    pub samplesize: u16,
+    pub samplerate: u32,
+
+    /// The formatSpecificFlags of an `lpcm` entry.
+    pub lpcm_flags: u32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pcmc: Option<PcmCBox>,
+}
+
+impl Default for PcmBox {
+    fn default() -> Self {
+        Self::new(&PcmConfig::default())
+    }
+}
+
+impl PcmBox {
+    pub const LPCM_FLAG_IS_FLOAT: u32 = 0x01;
+    pub const LPCM_FLAG_IS_BIG_ENDIAN: u32 = 0x02;
+    pub const LPCM_FLAG_IS_SIGNED_INTEGER: u32 = 0x04;
+    pub const LPCM_FLAG_IS_PACKED: u32 = 0x08;
+
+    /// A sample entry for `config`: `ipcm` or `fpcm`, or `lpcm` for unsigned
+    /// integers and sample rates above 65535 Hz, which the others cannot
+    /// describe.
+    pub fn new(config: &PcmConfig) -> Self {
+        let format = if (!config.float && !config.signed) || config.sample_rate > 0xFFFF {
+            PcmFormat::Lpcm
+        } else if config.float {
+            PcmFormat::Fpcm
+        } else {
+            PcmFormat::Ipcm
+        };
+
+        let mut pcm = PcmBox {
+            format,
+            data_reference_index: 1,
+            channelcount: config.channel_count,
+            samplesize: config.bits_per_sample,
+            samplerate: config.sample_rate,
+            lpcm_flags: 0,
+            pcmc: None,
+        };
+        if format == PcmFormat::Lpcm {
+            pcm.lpcm_flags = PcmBox::LPCM_FLAG_IS_PACKED;
+            if config.float {
+                pcm.lpcm_flags |= PcmBox::LPCM_FLAG_IS_FLOAT;
+            } else if config.signed {
+                pcm.lpcm_flags |= PcmBox::LPCM_FLAG_IS_SIGNED_INTEGER;
+            }
+            if !config.little_endian {
+                pcm.lpcm_flags |= PcmBox::LPCM_FLAG_IS_BIG_ENDIAN;
+            }
+        } else {
+            pcm.pcmc = Some(PcmCBox {
+                format_flags: if config.little_endian {
+                    PcmCBox::FLAG_LITTLE_ENDIAN
+                } else {
+                    0
+                },
+                pcm_sample_size: config.bits_per_sample as u8,
+                ..Default::default()
+            });
+        }
+        pcm
+    }
+
+    pub fn get_type(&self) -> BoxType {
+        self.format.box_type()
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + 8 + 20;
+        if self.format == PcmFormat::Lpcm {
+            size += 36;
+        }
+        if let Some(ref pcmc) = self.pcmc {
+            size += pcmc.box_size();
+        }
+        size
+    }
+
+    pub fn is_float(&self) -> bool {
+        match self.format {
+            PcmFormat::Fpcm => true,
+            PcmFormat::Lpcm => self.lpcm_flags & PcmBox::LPCM_FLAG_IS_FLOAT != 0,
+            _ => false,
+        }
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        match self.format {
+            PcmFormat::Ipcm | PcmFormat::Fpcm => self
+                .pcmc
+                .as_ref()
+                .is_some_and(|pcmc| pcmc.format_flags & PcmCBox::FLAG_LITTLE_ENDIAN != 0),
+            PcmFormat::Lpcm => self.lpcm_flags & PcmBox::LPCM_FLAG_IS_BIG_ENDIAN == 0,
+            PcmFormat::Sowt => true,
+            PcmFormat::Twos => false,
+        }
+    }
+
+    /// Whether integer samples are signed. Floating point samples always are.
+    pub fn is_signed(&self) -> bool {
+        match self.format {
+            PcmFormat::Lpcm => {
+                self.lpcm_flags & (PcmBox::LPCM_FLAG_IS_FLOAT | PcmBox::LPCM_FLAG_IS_SIGNED_INTEGER)
+                    != 0
+            }
+            _ => true,
+        }
+    }
+
+    /// Bits per sample, from `pcmC` when present.
+    pub fn bits_per_sample(&self) -> u16 {
+        match self.pcmc {
+            Some(ref pcmc) if pcmc.pcm_sample_size > 0 => pcmc.pcm_sample_size as u16,
+            _ => self.samplesize,
+        }
+    }
+}
+
+impl Mp4Box for PcmBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "channel_count={} sample_size={} sample_rate={} float={} little_endian={} signed={}",
+            self.channelcount,
+            self.bits_per_sample(),
+            self.samplerate,
+            self.is_float(),
+            self.is_little_endian(),
+            self.is_signed()
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for PcmBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        // The format is the type of the box being read.
+        reader.seek(SeekFrom::Start(start + 4))?;
+        let format = PcmFormat::from_box_type(BoxType::from(reader.read_u32::<BigEndian>()?))
+            .ok_or(Error::InvalidData("not a pcm sample entry"))?;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        let version = reader.read_u16::<BigEndian>()?;
+        reader.read_u16::<BigEndian>()?; // revision level
+        reader.read_u32::<BigEndian>()?; // vendor
+        let mut channelcount = reader.read_u16::<BigEndian>()?;
+        let mut samplesize = reader.read_u16::<BigEndian>()?;
+        reader.read_u32::<BigEndian>()?; // compression id, packet size
+        let mut samplerate = reader.read_u32::<BigEndian>()? >> 16;
+
+        let mut lpcm_flags = 0;
+        if version == 1 && format.is_quicktime() {
+            // Skip QTFF
+            skip_bytes(reader, 16)?;
+        } else if version == 2 {
+            reader.read_u32::<BigEndian>()?; // size of struct only
+            samplerate = reader.read_f64::<BigEndian>()?.round() as u32;
+            channelcount = reader.read_u32::<BigEndian>()? as u16;
+            reader.read_u32::<BigEndian>()?; // always 0x7F000000
+            samplesize = reader.read_u32::<BigEndian>()? as u16;
+            lpcm_flags = reader.read_u32::<BigEndian>()?;
+            reader.read_u32::<BigEndian>()?; // bytes per audio packet
+            reader.read_u32::<BigEndian>()?; // frames per audio packet
+        }
+
+        let mut pcmc = None;
+        let end = start + size;
+        while reader.stream_position()? + HEADER_SIZE <= end {
+            let current = reader.stream_position()?;
+            let BoxHeader { name, size: s, .. } = BoxHeader::read(reader)?;
+            if s < HEADER_SIZE || current + s > end {
+                return Err(Error::InvalidData(
+                    "pcm sample entry contains a box with a larger size than it",
+                ));
+            }
+            if name == BoxType::PcmCBox {
+                pcmc = Some(PcmCBox::read_box(reader, s)?);
+            }
+            skip_bytes_to(reader, current + s)?;
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(PcmBox {
+            format,
+            data_reference_index,
+            channelcount,
+            samplesize,
+            samplerate,
+            lpcm_flags,
+            pcmc,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for PcmBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+
+        if self.format == PcmFormat::Lpcm {
+            writer.write_u16::<BigEndian>(2)?; // version
+            writer.write_u16::<BigEndian>(0)?; // revision level
+            writer.write_u32::<BigEndian>(0)?; // vendor
+            writer.write_u16::<BigEndian>(3)?; // always 3
+            writer.write_u16::<BigEndian>(16)?; // always 16
+            writer.write_u16::<BigEndian>(0xFFFE)?; // always -2
+            writer.write_u16::<BigEndian>(0)?; // always 0
+            writer.write_u32::<BigEndian>(0x00010000)?; // always 65536
+            writer.write_u32::<BigEndian>(72)?; // size of struct only
+            writer.write_f64::<BigEndian>(self.samplerate as f64)?;
+            writer.write_u32::<BigEndian>(self.channelcount as u32)?;
+            writer.write_u32::<BigEndian>(0x7F000000)?;
+            writer.write_u32::<BigEndian>(self.samplesize as u32)?;
+            writer.write_u32::<BigEndian>(self.lpcm_flags)?;
+            // Bytes per packet for packed samples, one frame per packet.
+            writer.write_u32::<BigEndian>(self.channelcount as u32 * self.samplesize as u32 / 8)?;
+            writer.write_u32::<BigEndian>(1)?;
+        } else {
+            if self.samplerate > 0xFFFF {
+                return Err(Error::InvalidData(
+                    "sample rate too high for a version 0 audio sample entry",
+                ));
+            }
+            writer.write_u64::<BigEndian>(0)?; // reserved
+            writer.write_u16::<BigEndian>(self.channelcount)?;
+            writer.write_u16::<BigEndian>(self.samplesize)?;
+            writer.write_u32::<BigEndian>(0)?; // reserved
+            writer.write_u32::<BigEndian>(self.samplerate << 16)?;
+        }
+
+        if let Some(ref pcmc) = self.pcmc {
+            pcmc.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct PcmCBox {
+    pub version: u8,
+    pub flags: u32,
+    pub format_flags: u8,
+    pub pcm_sample_size: u8,
+}
+
+impl PcmCBox {
+    pub const FLAG_LITTLE_ENDIAN: u8 = 0x01;
+
+    pub fn get_type(&self) -> BoxType {
+        BoxType::PcmCBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + 2
+    }
+}
+
+impl Mp4Box for PcmCBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "format_flags={} pcm_sample_size={}",
+            self.format_flags, self.pcm_sample_size
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for PcmCBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let format_flags = reader.read_u8()?;
+        let pcm_sample_size = reader.read_u8()?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(PcmCBox {
+            version,
+            flags,
+            format_flags,
+            pcm_sample_size,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for PcmCBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        writer.write_u8(self.format_flags)?;
+        writer.write_u8(self.pcm_sample_size)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    fn round_trip(src_box: &PcmBox) -> PcmBox {
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, src_box.format.box_type());
+        assert_eq!(src_box.box_size(), header.size);
+
+        PcmBox::read_box(&mut reader, header.size).unwrap()
+    }
+
+    #[test]
+    fn test_ipcm() {
+        for little_endian in [false, true] {
+            let config = PcmConfig {
+                sample_rate: 44100,
+                channel_count: 2,
+                bits_per_sample: 24,
+                float: false,
+                little_endian,
+                signed: true,
+            };
+            let src_box = PcmBox::new(&config);
+            assert_eq!(src_box.format, PcmFormat::Ipcm);
+
+            let dst_box = round_trip(&src_box);
+            assert_eq!(src_box, dst_box);
+            assert_eq!(dst_box.is_little_endian(), little_endian);
+            assert_eq!(dst_box.bits_per_sample(), 24);
+            assert!(dst_box.is_signed());
+            assert!(!dst_box.is_float());
+        }
+    }
+
+    #[test]
+    fn test_fpcm() {
+        let src_box = PcmBox::new(&PcmConfig {
+            bits_per_sample: 32,
+            float: true,
+            ..Default::default()
+        });
+        assert_eq!(src_box.format, PcmFormat::Fpcm);
+
+        let dst_box = round_trip(&src_box);
+        assert_eq!(src_box, dst_box);
+        assert!(dst_box.is_float());
+        assert!(dst_box.is_little_endian());
+    }
+
+    #[test]
+    fn test_lpcm() {
+        // Unsigned 8-bit samples.
+        let src_box = PcmBox::new(&PcmConfig {
+            bits_per_sample: 8,
+            signed: false,
+            little_endian: false,
+            ..Default::default()
+        });
+        assert_eq!(src_box.format, PcmFormat::Lpcm);
+        let dst_box = round_trip(&src_box);
+        assert_eq!(src_box, dst_box);
+        assert!(!dst_box.is_signed());
+        assert!(!dst_box.is_little_endian());
+
+        // Sample rates that do not fit the 16.16 field.
+        let src_box = PcmBox::new(&PcmConfig {
+            sample_rate: 96000,
+            ..Default::default()
+        });
+        assert_eq!(src_box.format, PcmFormat::Lpcm);
+        let dst_box = round_trip(&src_box);
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.samplerate, 96000);
+        assert!(dst_box.is_signed());
+        assert!(dst_box.is_little_endian());
+    }
+
+    #[test]
+    fn test_sowt_twos() {
+        for (format, little_endian) in [(PcmFormat::Sowt, true), (PcmFormat::Twos, false)] {
+            let src_box = PcmBox {
+                format,
+                data_reference_index: 1,
+                channelcount: 1,
+                samplesize: 16,
+                samplerate: 22050,
+                lpcm_flags: 0,
+                pcmc: None,
+            };
+            let dst_box = round_trip(&src_box);
+            assert_eq!(src_box, dst_box);
+            assert_eq!(dst_box.is_little_endian(), little_endian);
+            assert!(dst_box.is_signed());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Where the auxiliary information sized by the sibling `saiz` box is
+/// stored: one offset for the whole track fragment or for each chunk, or a
+/// single offset for all samples of a track. In a `traf`, offsets are
+/// relative to the same base as the sample data of the fragment; in a
+/// `stbl`, they are file offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct SaioBox {
+    /// 0 for 32-bit offsets, 1 for 64-bit ones.
+    pub version: u8,
+    pub flags: u32,
+    pub aux_info_type: Option<FourCC>,
+    pub aux_info_type_parameter: Option<u32>,
+
+    #[serde(skip_serializing)]
+    pub offsets: Vec<u64>,
+}
+
+impl SaioBox {
+    pub const FLAG_AUX_INFO_TYPE: u32 = 0x01;
+
+    pub fn get_type(&self) -> BoxType {
+        BoxType::SaioBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 4;
+        if SaioBox::FLAG_AUX_INFO_TYPE & self.flags > 0 {
+            size += 8;
+        }
+        let offset_size = if self.version == 1 { 8 } else { 4 };
+        size + offset_size * self.offsets.len() as u64
+    }
+}
+
+impl Mp4Box for SaioBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("entries={}", self.offsets.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SaioBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let mut aux_info_type = None;
+        let mut aux_info_type_parameter = None;
+        if SaioBox::FLAG_AUX_INFO_TYPE & flags > 0 {
+            aux_info_type = Some(FourCC::from(reader.read_u32::<BigEndian>()?));
+            aux_info_type_parameter = Some(reader.read_u32::<BigEndian>()?);
+        }
+        let entry_count = reader.read_u32::<BigEndian>()?;
+        let offset_size = if version == 1 { 8 } else { 4 };
+        if reader.stream_position()? + offset_size * entry_count as u64 > start + size {
+            return Err(Error::InvalidData(
+                "saio entry_count indicates more entries than could fit in the box",
+            ));
+        }
+        let mut offsets = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let offset = if version == 1 {
+                reader.read_u64::<BigEndian>()?
+            } else {
+                reader.read_u32::<BigEndian>()? as u64
+            };
+            offsets.push(offset);
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(SaioBox {
+            version,
+            flags,
+            aux_info_type,
+            aux_info_type_parameter,
+            offsets,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SaioBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if SaioBox::FLAG_AUX_INFO_TYPE & self.flags > 0 {
+            let aux_info_type = self.aux_info_type.unwrap_or_default();
+            writer.write_u32::<BigEndian>((&aux_info_type).into())?;
+            writer.write_u32::<BigEndian>(self.aux_info_type_parameter.unwrap_or(0))?;
+        }
+        writer.write_u32::<BigEndian>(self.offsets.len() as u32)?;
+        for offset in self.offsets.iter() {
+            if self.version == 1 {
+                writer.write_u64::<BigEndian>(*offset)?;
+            } else if *offset <= u32::MAX as u64 {
+                writer.write_u32::<BigEndian>(*offset as u32)?;
+            } else {
+                return Err(Error::InvalidData(
+                    "offset does not fit in a version 0 saio box",
+                ));
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_saio() {
+        for version in [0, 1] {
+            let src_box = SaioBox {
+                version,
+                offsets: vec![1024, 8192],
+                ..Default::default()
+            };
+            let mut buf = Vec::new();
+            src_box.write_box(&mut buf).unwrap();
+            assert_eq!(buf.len(), src_box.box_size() as usize);
+
+            let mut reader = Cursor::new(&buf);
+            let header = BoxHeader::read(&mut reader).unwrap();
+            assert_eq!(header.name, BoxType::SaioBox);
+            assert_eq!(src_box.box_size(), header.size);
+
+            let dst_box = SaioBox::read_box(&mut reader, header.size).unwrap();
+            assert_eq!(src_box, dst_box);
+        }
+    }
+}
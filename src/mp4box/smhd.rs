@@ -14,6 +14,13 @@ pub struct SmhdBox {
 }
 
 impl SmhdBox {
+    pub fn new(config: &TrackConfig) -> Self {
+        SmhdBox {
+            balance: config.balance,
+            ..Default::default()
+        }
+    }
+
     pub fn get_type(&self) -> BoxType {
         BoxType::SmhdBox
     }
@@ -1,10 +1,21 @@
 use serde::Serialize;
+use std::convert::TryFrom;
 use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
 use crate::mp4box::{
-    co64::Co64Box, ctts::CttsBox, stco::StcoBox, stsc::StscBox, stsd::StsdBox, stss::StssBox,
-    stsz::StszBox, stts::SttsBox,
+    co64::Co64Box,
+    ctts::{CttsBox, CttsEntry},
+    padb::PadbBox,
+    saio::SaioBox,
+    saiz::SaizBox,
+    stco::StcoBox,
+    stsc::StscBox,
+    stsd::StsdBox,
+    stss::StssBox,
+    stsz::StszBox,
+    stts::{SttsBox, SttsEntry},
+    subs::SubsBox,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
@@ -25,6 +36,18 @@ pub struct StblBox {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub co64: Option<Co64Box>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subs: Option<SubsBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padb: Option<PadbBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saiz: Option<SaizBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saio: Option<SaioBox>,
 }
 
 impl StblBox {
@@ -50,8 +73,123 @@ impl StblBox {
         if let Some(ref co64) = self.co64 {
             size += co64.box_size();
         }
+        if let Some(ref subs) = self.subs {
+            size += subs.box_size();
+        }
+        if let Some(ref padb) = self.padb {
+            size += padb.box_size();
+        }
+        if let Some(ref saiz) = self.saiz {
+            size += saiz.box_size();
+        }
+        if let Some(ref saio) = self.saio {
+            size += saio.box_size();
+        }
         size
     }
+
+    /// Add `delta` to every chunk offset, as after moving the media data of
+    /// the track by that many bytes. A track whose offsets no longer fit in
+    /// `stco` is switched to `co64`. Fails without changing anything if an
+    /// offset would become negative or overflow.
+    pub fn rebase_chunk_offsets(&mut self, delta: i64) -> Result<()> {
+        let offsets = self.rebased_chunk_offsets(delta)?;
+        self.set_chunk_offsets(offsets);
+        Ok(())
+    }
+
+    // The chunk offsets of `stco`, or else of `co64`.
+    pub(crate) fn chunk_offsets(&self) -> Vec<u64> {
+        match (&self.stco, &self.co64) {
+            (Some(stco), _) => stco.entries.iter().map(|&o| o as u64).collect(),
+            (None, Some(co64)) => co64.entries.clone(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    pub(crate) fn rebased_chunk_offsets(&self, delta: i64) -> Result<Vec<u64>> {
+        self.chunk_offsets()
+            .iter()
+            .map(|&o| u64::try_from(o as i128 + delta as i128).ok())
+            .collect::<Option<Vec<u64>>>()
+            .ok_or(Error::InvalidData(
+                "chunk offset out of range after rebasing",
+            ))
+    }
+
+    // Replace the chunk offsets, switching from `stco` to `co64` if they no
+    // longer fit in 32 bits. Returns whether the box grew by switching.
+    pub(crate) fn set_chunk_offsets(&mut self, offsets: Vec<u64>) -> bool {
+        match self.stco {
+            Some(ref mut stco) if offsets.iter().all(|&o| o <= u32::MAX as u64) => {
+                stco.entries = offsets.iter().map(|&o| o as u32).collect();
+                false
+            }
+            Some(ref stco) => {
+                self.co64 = Some(Co64Box {
+                    version: stco.version,
+                    flags: stco.flags,
+                    entries: offsets,
+                });
+                self.stco = None;
+                true
+            }
+            None => {
+                if let Some(ref mut co64) = self.co64 {
+                    co64.entries = offsets;
+                }
+                false
+            }
+        }
+    }
+
+    /// Merge runs of adjacent `stts`, `ctts` and `stsc` entries with the same
+    /// values and drop entries covering no samples. The timing and chunk of
+    /// every sample are unchanged.
+    pub fn optimize_sample_tables(&mut self) {
+        let mut stts: Vec<SttsEntry> = Vec::with_capacity(self.stts.entries.len());
+        for entry in self.stts.entries.drain(..) {
+            if entry.sample_count == 0 {
+                continue;
+            }
+            match stts.last_mut() {
+                Some(last)
+                    if last.sample_delta == entry.sample_delta
+                        && last.sample_count.checked_add(entry.sample_count).is_some() =>
+                {
+                    last.sample_count += entry.sample_count;
+                }
+                _ => stts.push(entry),
+            }
+        }
+        self.stts.entries = stts;
+
+        if let Some(ref mut ctts) = self.ctts {
+            let mut entries: Vec<CttsEntry> = Vec::with_capacity(ctts.entries.len());
+            for entry in ctts.entries.drain(..) {
+                if entry.sample_count == 0 {
+                    continue;
+                }
+                match entries.last_mut() {
+                    Some(last)
+                        if last.sample_offset == entry.sample_offset
+                            && last.sample_count.checked_add(entry.sample_count).is_some() =>
+                    {
+                        last.sample_count += entry.sample_count;
+                    }
+                    _ => entries.push(entry),
+                }
+            }
+            ctts.entries = entries;
+        }
+
+        // An stsc entry applies until the next one, so an entry repeating the
+        // previous one is redundant.
+        self.stsc.entries.dedup_by(|entry, prev| {
+            entry.samples_per_chunk == prev.samples_per_chunk
+                && entry.sample_description_index == prev.sample_description_index
+        });
+    }
 }
 
 impl Mp4Box for StblBox {
@@ -85,13 +223,17 @@ impl<R: Read + Seek> ReadBox<&mut R> for StblBox {
         let mut stsz = None;
         let mut stco = None;
         let mut co64 = None;
+        let mut subs = None;
+        let mut padb = None;
+        let mut saiz = None;
+        let mut saio = None;
 
         let mut current = reader.stream_position()?;
         let end = start + size;
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "stbl box contains a box with a larger size than it",
@@ -123,12 +265,24 @@ impl<R: Read + Seek> ReadBox<&mut R> for StblBox {
                 BoxType::Co64Box => {
                     co64 = Some(Co64Box::read_box(reader, s)?);
                 }
+                BoxType::SubsBox => {
+                    subs = Some(SubsBox::read_box(reader, s)?);
+                }
+                BoxType::PadbBox => {
+                    padb = Some(PadbBox::read_box(reader, s)?);
+                }
+                BoxType::SaizBox => {
+                    saiz = Some(SaizBox::read_box(reader, s)?);
+                }
+                BoxType::SaioBox => {
+                    saio = Some(SaioBox::read_box(reader, s)?);
+                }
                 _ => {
                     // XXX warn!()
                     skip_box(reader, s)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if stsd.is_none() {
@@ -158,6 +312,10 @@ impl<R: Read + Seek> ReadBox<&mut R> for StblBox {
             stsz: stsz.unwrap(),
             stco,
             co64,
+            subs,
+            padb,
+            saiz,
+            saio,
         })
     }
 }
@@ -183,6 +341,18 @@ impl<W: Write> WriteBox<&mut W> for StblBox {
         if let Some(ref co64) = self.co64 {
             co64.write_box(writer)?;
         }
+        if let Some(ref subs) = self.subs {
+            subs.write_box(writer)?;
+        }
+        if let Some(ref padb) = self.padb {
+            padb.write_box(writer)?;
+        }
+        if let Some(ref saiz) = self.saiz {
+            saiz.write_box(writer)?;
+        }
+        if let Some(ref saio) = self.saio {
+            saio.write_box(writer)?;
+        }
 
         Ok(size)
     }
@@ -0,0 +1,63 @@
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// The media header of subtitle tracks, such as TTML in `stpp` sample
+/// entries.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct SthdBox {
+    pub version: u8,
+    pub flags: u32,
+}
+
+impl SthdBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::SthdBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE
+    }
+}
+
+impl Mp4Box for SthdBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        Ok(String::new())
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SthdBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(SthdBox { version, flags })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SthdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,235 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Subtitle sample entry for XML samples such as TTML and IMSC1 documents.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct StppBox {
+    pub data_reference_index: u16,
+
+    /// Space-separated XML namespaces the samples conform to, such as
+    /// `http://www.w3.org/ns/ttml`.
+    pub namespace: String,
+
+    /// Space-separated URLs of the schemas of the namespaces; may be empty.
+    pub schema_location: String,
+
+    /// Space-separated MIME types of the images and fonts the samples may
+    /// carry as subsamples; may be empty.
+    pub auxiliary_mime_types: String,
+}
+
+impl StppBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::StppBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + self.namespace.len() as u64
+            + 1
+            + self.schema_location.len() as u64
+            + 1
+            + self.auxiliary_mime_types.len() as u64
+            + 1
+    }
+}
+
+impl Mp4Box for StppBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "namespace={} schema_location={} auxiliary_mime_types={}",
+            self.namespace, self.schema_location, self.auxiliary_mime_types
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for StppBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let end = start + size;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        let namespace = read_cstring(reader, end)?;
+        let schema_location = read_cstring(reader, end)?;
+        let auxiliary_mime_types = read_cstring(reader, end)?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(StppBox {
+            data_reference_index,
+            namespace,
+            schema_location,
+            auxiliary_mime_types,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for StppBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        write_cstring(writer, &self.namespace)?;
+        write_cstring(writer, &self.schema_location)?;
+        write_cstring(writer, &self.auxiliary_mime_types)?;
+
+        Ok(size)
+    }
+}
+
+/// Sample entry for plain text samples in some MIME format, such as WebVTT
+/// without its boxes or timed text markup of an application.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct StxtBox {
+    pub data_reference_index: u16,
+
+    /// The content encoding of the samples, such as `gzip`; empty if they
+    /// are not encoded.
+    pub content_encoding: String,
+    pub mime_format: String,
+}
+
+impl StxtBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::StxtBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8 + self.content_encoding.len() as u64 + 1 + self.mime_format.len() as u64 + 1
+    }
+}
+
+impl Mp4Box for StxtBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!(
+            "content_encoding={} mime_format={}",
+            self.content_encoding, self.mime_format
+        );
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for StxtBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let end = start + size;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+        let content_encoding = read_cstring(reader, end)?;
+        let mime_format = read_cstring(reader, end)?;
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(StxtBox {
+            data_reference_index,
+            content_encoding,
+            mime_format,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for StxtBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        write_cstring(writer, &self.content_encoding)?;
+        write_cstring(writer, &self.mime_format)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stpp() {
+        let src_box = StsdBox {
+            stpp: Some(StppBox {
+                data_reference_index: 1,
+                namespace: String::from(
+                    "http://www.w3.org/ns/ttml http://www.w3.org/ns/ttml/profile/imsc1/text",
+                ),
+                schema_location: String::from(
+                    "http://www.w3.org/ns/ttml http://www.w3.org/ns/ttml/ttaf1.xsd",
+                ),
+                auxiliary_mime_types: String::new(),
+            }),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::StsdBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = StsdBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+
+    #[test]
+    fn test_stxt() {
+        let src_box = StxtBox {
+            data_reference_index: 1,
+            content_encoding: String::new(),
+            mime_format: String::from("text/vtt"),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::StxtBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = StxtBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
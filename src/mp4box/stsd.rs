@@ -4,7 +4,17 @@ use std::io::{Read, Seek, Write};
 
 use crate::mp4box::vp09::Vp09Box;
 use crate::mp4box::*;
-use crate::mp4box::{avc1::Avc1Box, hev1::Hev1Box, mp4a::Mp4aBox, tx3g::Tx3gBox};
+use crate::mp4box::{
+    ac4::Ac4Box,
+    avc1::Avc1Box,
+    hev1::Hev1Box,
+    mett::{MettBox, MetxBox},
+    mp4a::Mp4aBox,
+    pcm::PcmBox,
+    stpp::{StppBox, StxtBox},
+    tx3g::Tx3gBox,
+    urim::UrimBox,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct StsdBox {
@@ -23,8 +33,169 @@ pub struct StsdBox {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mp4a: Option<Mp4aBox>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pcm: Option<PcmBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ac4: Option<Ac4Box>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tx3g: Option<Tx3gBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mett: Option<MettBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metx: Option<MetxBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub urim: Option<UrimBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stpp: Option<StppBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stxt: Option<StxtBox>,
+
+    /// Sample entries following the first one, in file order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_entries: Vec<SampleEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SampleEntry {
+    Avc1(Avc1Box),
+    Hev1(Hev1Box),
+    Vp09(Vp09Box),
+    Mp4a(Mp4aBox),
+    Pcm(PcmBox),
+    Ac4(Ac4Box),
+    Tx3g(Tx3gBox),
+    Mett(MettBox),
+    Metx(MetxBox),
+    Urim(UrimBox),
+    Stpp(StppBox),
+    Stxt(StxtBox),
+
+    #[serde(skip)]
+    Unknown(BoxType, Vec<u8>),
+}
+
+impl SampleEntry {
+    pub fn box_type(&self) -> BoxType {
+        match self {
+            SampleEntry::Avc1(avc1) => avc1.box_type(),
+            SampleEntry::Hev1(hev1) => hev1.box_type(),
+            SampleEntry::Vp09(vp09) => vp09.box_type(),
+            SampleEntry::Mp4a(mp4a) => mp4a.box_type(),
+            SampleEntry::Pcm(pcm) => pcm.box_type(),
+            SampleEntry::Ac4(ac4) => ac4.box_type(),
+            SampleEntry::Tx3g(tx3g) => tx3g.box_type(),
+            SampleEntry::Mett(mett) => mett.box_type(),
+            SampleEntry::Metx(metx) => metx.box_type(),
+            SampleEntry::Urim(urim) => urim.box_type(),
+            SampleEntry::Stpp(stpp) => stpp.box_type(),
+            SampleEntry::Stxt(stxt) => stxt.box_type(),
+            SampleEntry::Unknown(box_type, _) => *box_type,
+        }
+    }
+
+    pub fn box_size(&self) -> u64 {
+        match self {
+            SampleEntry::Avc1(avc1) => avc1.box_size(),
+            SampleEntry::Hev1(hev1) => hev1.box_size(),
+            SampleEntry::Vp09(vp09) => vp09.box_size(),
+            SampleEntry::Mp4a(mp4a) => mp4a.box_size(),
+            SampleEntry::Pcm(pcm) => pcm.box_size(),
+            SampleEntry::Ac4(ac4) => ac4.box_size(),
+            SampleEntry::Tx3g(tx3g) => tx3g.box_size(),
+            SampleEntry::Mett(mett) => mett.box_size(),
+            SampleEntry::Metx(metx) => metx.box_size(),
+            SampleEntry::Urim(urim) => urim.box_size(),
+            SampleEntry::Stpp(stpp) => stpp.box_size(),
+            SampleEntry::Stxt(stxt) => stxt.box_size(),
+            SampleEntry::Unknown(_, data) => HEADER_SIZE + data.len() as u64,
+        }
+    }
+
+    /// Index into the `dref` box of the data reference holding the samples
+    /// that use this description. `None` if an unrecognized entry is too
+    /// short to carry one.
+    pub fn data_reference_index(&self) -> Option<u16> {
+        match self {
+            SampleEntry::Avc1(avc1) => Some(avc1.data_reference_index),
+            SampleEntry::Hev1(hev1) => Some(hev1.data_reference_index),
+            SampleEntry::Vp09(vp09) => Some(vp09.data_reference_index),
+            SampleEntry::Mp4a(mp4a) => Some(mp4a.data_reference_index),
+            SampleEntry::Pcm(pcm) => Some(pcm.data_reference_index),
+            SampleEntry::Ac4(ac4) => Some(ac4.data_reference_index),
+            SampleEntry::Tx3g(tx3g) => Some(tx3g.data_reference_index),
+            SampleEntry::Mett(mett) => Some(mett.data_reference_index),
+            SampleEntry::Metx(metx) => Some(metx.data_reference_index),
+            SampleEntry::Urim(urim) => Some(urim.data_reference_index),
+            SampleEntry::Stpp(stpp) => Some(stpp.data_reference_index),
+            SampleEntry::Stxt(stxt) => Some(stxt.data_reference_index),
+            // Six reserved bytes precede it in every SampleEntry.
+            SampleEntry::Unknown(_, data) => data
+                .get(6..8)
+                .map(|index| u16::from_be_bytes([index[0], index[1]])),
+        }
+    }
+
+    fn read_entry<R: Read + Seek>(reader: &mut R, name: BoxType, size: u64) -> Result<Self> {
+        let entry = match name {
+            BoxType::Avc1Box => SampleEntry::Avc1(Avc1Box::read_box(reader, size)?),
+            BoxType::Hev1Box => {
+                SampleEntry::Hev1(Hev1Box::read_entry(reader, HevcFormat::Hev1, size)?)
+            }
+            BoxType::Hvc1Box => {
+                SampleEntry::Hev1(Hev1Box::read_entry(reader, HevcFormat::Hvc1, size)?)
+            }
+            BoxType::Vp09Box => SampleEntry::Vp09(Vp09Box::read_box(reader, size)?),
+            BoxType::Mp4aBox => SampleEntry::Mp4a(Mp4aBox::read_box(reader, size)?),
+            BoxType::IpcmBox
+            | BoxType::FpcmBox
+            | BoxType::LpcmBox
+            | BoxType::SowtBox
+            | BoxType::TwosBox => SampleEntry::Pcm(PcmBox::read_box(reader, size)?),
+            BoxType::Ac4Box => SampleEntry::Ac4(Ac4Box::read_box(reader, size)?),
+            BoxType::Tx3gBox => SampleEntry::Tx3g(Tx3gBox::read_box(reader, size)?),
+            BoxType::MettBox => SampleEntry::Mett(MettBox::read_box(reader, size)?),
+            BoxType::MetxBox => SampleEntry::Metx(MetxBox::read_box(reader, size)?),
+            BoxType::UrimBox => SampleEntry::Urim(UrimBox::read_box(reader, size)?),
+            BoxType::StppBox => SampleEntry::Stpp(StppBox::read_box(reader, size)?),
+            BoxType::StxtBox => SampleEntry::Stxt(StxtBox::read_box(reader, size)?),
+            _ => {
+                let mut data = vec![0; (size - HEADER_SIZE) as usize];
+                reader.read_exact(&mut data)?;
+                SampleEntry::Unknown(name, data)
+            }
+        };
+        Ok(entry)
+    }
+
+    fn write_entry<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        match self {
+            SampleEntry::Avc1(avc1) => avc1.write_box(writer),
+            SampleEntry::Hev1(hev1) => hev1.write_box(writer),
+            SampleEntry::Vp09(vp09) => vp09.write_box(writer),
+            SampleEntry::Mp4a(mp4a) => mp4a.write_box(writer),
+            SampleEntry::Pcm(pcm) => pcm.write_box(writer),
+            SampleEntry::Ac4(ac4) => ac4.write_box(writer),
+            SampleEntry::Tx3g(tx3g) => tx3g.write_box(writer),
+            SampleEntry::Mett(mett) => mett.write_box(writer),
+            SampleEntry::Metx(metx) => metx.write_box(writer),
+            SampleEntry::Urim(urim) => urim.write_box(writer),
+            SampleEntry::Stpp(stpp) => stpp.write_box(writer),
+            SampleEntry::Stxt(stxt) => stxt.write_box(writer),
+            SampleEntry::Unknown(box_type, data) => {
+                let size = self.box_size();
+                BoxHeader::new(*box_type, size).write(writer)?;
+                writer.write_all(data)?;
+                Ok(size)
+            }
+        }
+    }
 }
 
 impl StsdBox {
@@ -42,11 +213,95 @@ impl StsdBox {
             size += vp09.box_size();
         } else if let Some(ref mp4a) = self.mp4a {
             size += mp4a.box_size();
+        } else if let Some(ref pcm) = self.pcm {
+            size += pcm.box_size();
+        } else if let Some(ref ac4) = self.ac4 {
+            size += ac4.box_size();
         } else if let Some(ref tx3g) = self.tx3g {
             size += tx3g.box_size();
+        } else if let Some(ref mett) = self.mett {
+            size += mett.box_size();
+        } else if let Some(ref metx) = self.metx {
+            size += metx.box_size();
+        } else if let Some(ref urim) = self.urim {
+            size += urim.box_size();
+        } else if let Some(ref stpp) = self.stpp {
+            size += stpp.box_size();
+        } else if let Some(ref stxt) = self.stxt {
+            size += stxt.box_size();
+        }
+        for entry in self.extra_entries.iter() {
+            size += entry.box_size();
         }
         size
     }
+
+    fn has_first_entry(&self) -> bool {
+        self.avc1.is_some()
+            || self.hev1.is_some()
+            || self.vp09.is_some()
+            || self.mp4a.is_some()
+            || self.pcm.is_some()
+            || self.ac4.is_some()
+            || self.tx3g.is_some()
+            || self.mett.is_some()
+            || self.metx.is_some()
+            || self.urim.is_some()
+            || self.stpp.is_some()
+            || self.stxt.is_some()
+    }
+
+    fn first_entry(&self) -> Option<SampleEntry> {
+        if let Some(ref avc1) = self.avc1 {
+            Some(SampleEntry::Avc1(avc1.clone()))
+        } else if let Some(ref hev1) = self.hev1 {
+            Some(SampleEntry::Hev1(hev1.clone()))
+        } else if let Some(ref vp09) = self.vp09 {
+            Some(SampleEntry::Vp09(vp09.clone()))
+        } else if let Some(ref mp4a) = self.mp4a {
+            Some(SampleEntry::Mp4a(mp4a.clone()))
+        } else if let Some(ref pcm) = self.pcm {
+            Some(SampleEntry::Pcm(pcm.clone()))
+        } else if let Some(ref ac4) = self.ac4 {
+            Some(SampleEntry::Ac4(ac4.clone()))
+        } else if let Some(ref tx3g) = self.tx3g {
+            Some(SampleEntry::Tx3g(tx3g.clone()))
+        } else if let Some(ref mett) = self.mett {
+            Some(SampleEntry::Mett(mett.clone()))
+        } else if let Some(ref metx) = self.metx {
+            Some(SampleEntry::Metx(metx.clone()))
+        } else if let Some(ref urim) = self.urim {
+            Some(SampleEntry::Urim(urim.clone()))
+        } else if let Some(ref stpp) = self.stpp {
+            Some(SampleEntry::Stpp(stpp.clone()))
+        } else {
+            self.stxt
+                .as_ref()
+                .map(|stxt| SampleEntry::Stxt(stxt.clone()))
+        }
+    }
+
+    /// All sample entries in file order, so that `entries()[i]` is the
+    /// description referenced by `sample_description_index` `i + 1`.
+    pub fn entries(&self) -> Vec<SampleEntry> {
+        let mut entries = Vec::with_capacity(1 + self.extra_entries.len());
+        entries.extend(self.first_entry());
+        entries.extend(self.extra_entries.iter().cloned());
+        entries
+    }
+
+    /// The sample entry referenced by the 1-based `sample_description_index`
+    /// `index`, if any.
+    pub fn entry(&self, index: u32) -> Option<SampleEntry> {
+        let index = index.checked_sub(1)? as usize;
+        if !self.has_first_entry() {
+            self.extra_entries.get(index).cloned()
+        } else if index == 0 {
+            self.first_entry()
+        } else {
+            self.extra_entries.get(index - 1).cloned()
+        }
+    }
 }
 
 impl Mp4Box for StsdBox {
@@ -74,40 +329,55 @@ impl<R: Read + Seek> ReadBox<&mut R> for StsdBox {
 
         let (version, flags) = read_box_header_ext(reader)?;
 
-        reader.read_u32::<BigEndian>()?; // XXX entry_count
+        let entry_count = reader.read_u32::<BigEndian>()?;
 
         let mut avc1 = None;
         let mut hev1 = None;
         let mut vp09 = None;
         let mut mp4a = None;
+        let mut pcm = None;
+        let mut ac4 = None;
         let mut tx3g = None;
+        let mut mett = None;
+        let mut metx = None;
+        let mut urim = None;
+        let mut stpp = None;
+        let mut stxt = None;
+        let mut extra_entries = Vec::new();
 
-        // Get box header.
-        let header = BoxHeader::read(reader)?;
-        let BoxHeader { name, size: s } = header;
-        if s > size {
-            return Err(Error::InvalidData(
-                "stsd box contains a box with a larger size than it",
-            ));
-        }
-
-        match name {
-            BoxType::Avc1Box => {
-                avc1 = Some(Avc1Box::read_box(reader, s)?);
+        let end = start + size;
+        for i in 0..entry_count {
+            if reader.stream_position()? + HEADER_SIZE > end {
+                break;
             }
-            BoxType::Hev1Box => {
-                hev1 = Some(Hev1Box::read_box(reader, s)?);
-            }
-            BoxType::Vp09Box => {
-                vp09 = Some(Vp09Box::read_box(reader, s)?);
+
+            // Get box header.
+            let header = BoxHeader::read(reader)?;
+            let BoxHeader { name, size: s, .. } = header;
+            if s > size {
+                return Err(Error::InvalidData(
+                    "stsd box contains a box with a larger size than it",
+                ));
             }
-            BoxType::Mp4aBox => {
-                mp4a = Some(Mp4aBox::read_box(reader, s)?);
+            if s < HEADER_SIZE {
+                return Err(Error::InvalidData("sample entry size too small"));
             }
-            BoxType::Tx3gBox => {
-                tx3g = Some(Tx3gBox::read_box(reader, s)?);
+
+            match SampleEntry::read_entry(reader, name, s)? {
+                SampleEntry::Avc1(entry) if i == 0 => avc1 = Some(entry),
+                SampleEntry::Hev1(entry) if i == 0 => hev1 = Some(entry),
+                SampleEntry::Vp09(entry) if i == 0 => vp09 = Some(entry),
+                SampleEntry::Mp4a(entry) if i == 0 => mp4a = Some(entry),
+                SampleEntry::Pcm(entry) if i == 0 => pcm = Some(entry),
+                SampleEntry::Ac4(entry) if i == 0 => ac4 = Some(entry),
+                SampleEntry::Tx3g(entry) if i == 0 => tx3g = Some(entry),
+                SampleEntry::Mett(entry) if i == 0 => mett = Some(entry),
+                SampleEntry::Metx(entry) if i == 0 => metx = Some(entry),
+                SampleEntry::Urim(entry) if i == 0 => urim = Some(entry),
+                SampleEntry::Stpp(entry) if i == 0 => stpp = Some(entry),
+                SampleEntry::Stxt(entry) if i == 0 => stxt = Some(entry),
+                entry => extra_entries.push(entry),
             }
-            _ => {}
         }
 
         skip_bytes_to(reader, start + size)?;
@@ -119,7 +389,15 @@ impl<R: Read + Seek> ReadBox<&mut R> for StsdBox {
             hev1,
             vp09,
             mp4a,
+            pcm,
+            ac4,
             tx3g,
+            mett,
+            metx,
+            urim,
+            stpp,
+            stxt,
+            extra_entries,
         })
     }
 }
@@ -131,7 +409,8 @@ impl<W: Write> WriteBox<&mut W> for StsdBox {
 
         write_box_header_ext(writer, self.version, self.flags)?;
 
-        writer.write_u32::<BigEndian>(1)?; // entry_count
+        let entry_count = self.has_first_entry() as usize + self.extra_entries.len();
+        writer.write_u32::<BigEndian>(entry_count as u32)?;
 
         if let Some(ref avc1) = self.avc1 {
             avc1.write_box(writer)?;
@@ -141,10 +420,85 @@ impl<W: Write> WriteBox<&mut W> for StsdBox {
             vp09.write_box(writer)?;
         } else if let Some(ref mp4a) = self.mp4a {
             mp4a.write_box(writer)?;
+        } else if let Some(ref pcm) = self.pcm {
+            pcm.write_box(writer)?;
+        } else if let Some(ref ac4) = self.ac4 {
+            ac4.write_box(writer)?;
         } else if let Some(ref tx3g) = self.tx3g {
             tx3g.write_box(writer)?;
+        } else if let Some(ref mett) = self.mett {
+            mett.write_box(writer)?;
+        } else if let Some(ref metx) = self.metx {
+            metx.write_box(writer)?;
+        } else if let Some(ref urim) = self.urim {
+            urim.write_box(writer)?;
+        } else if let Some(ref stpp) = self.stpp {
+            stpp.write_box(writer)?;
+        } else if let Some(ref stxt) = self.stxt {
+            stxt.write_box(writer)?;
+        }
+        for entry in self.extra_entries.iter() {
+            entry.write_entry(writer)?;
         }
 
         Ok(size)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stsd_multiple_entries() {
+        let src_box = StsdBox {
+            avc1: Some(Avc1Box {
+                width: 320,
+                height: 240,
+                ..Default::default()
+            }),
+            extra_entries: vec![
+                SampleEntry::Avc1(Avc1Box {
+                    data_reference_index: 2,
+                    width: 640,
+                    height: 480,
+                    ..Default::default()
+                }),
+                SampleEntry::Unknown(BoxType::UnknownBox(0x61626364), vec![1, 2, 3, 4]),
+                SampleEntry::Unknown(
+                    BoxType::UnknownBox(0x61626365),
+                    vec![0, 0, 0, 0, 0, 0, 0, 3],
+                ),
+            ],
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::StsdBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = StsdBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        let entries = dst_box.entries();
+        assert_eq!(entries.len(), 4);
+        let data_reference_indexes: Vec<Option<u16>> = entries
+            .iter()
+            .map(|entry| entry.data_reference_index())
+            .collect();
+        assert_eq!(
+            data_reference_indexes,
+            vec![Some(0), Some(2), None, Some(3)]
+        );
+        for (i, entry) in entries.iter().enumerate() {
+            assert_eq!(dst_box.entry(i as u32 + 1).as_ref(), Some(entry));
+        }
+        assert_eq!(dst_box.entry(0), None);
+        assert_eq!(dst_box.entry(5), None);
+    }
+}
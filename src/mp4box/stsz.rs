@@ -22,7 +22,32 @@ impl StszBox {
     }
 
     pub fn get_size(&self) -> u64 {
-        HEADER_SIZE + HEADER_EXT_SIZE + 8 + (4 * self.sample_sizes.len() as u64)
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 8;
+        if self.sample_size == 0 {
+            size += 4 * self.sample_sizes.len() as u64;
+        }
+        size
+    }
+
+    /// The size of a sample, by 1-based id. When `sample_size` is non-zero
+    /// every sample has that size and `sample_sizes` is empty.
+    pub fn entry_size(&self, sample_id: u32) -> Option<u32> {
+        if sample_id == 0 || sample_id > self.sample_count {
+            None
+        } else if self.sample_size > 0 {
+            Some(self.sample_size)
+        } else {
+            self.sample_sizes.get(sample_id as usize - 1).copied()
+        }
+    }
+
+    /// The sum of the sizes of all samples.
+    pub fn total_size(&self) -> u64 {
+        if self.sample_size > 0 {
+            self.sample_size as u64 * self.sample_count as u64
+        } else {
+            self.sample_sizes.iter().map(|size| *size as u64).sum()
+        }
     }
 }
 
@@ -146,6 +171,33 @@ mod tests {
         assert_eq!(src_box, dst_box);
     }
 
+    #[test]
+    fn test_stsz_constant_size_many_samples() {
+        let src_box = StszBox {
+            version: 0,
+            flags: 0,
+            sample_size: 4096,
+            sample_count: 1_000_000,
+            sample_sizes: vec![],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), 20);
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let dst_box = StszBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.sample_sizes.capacity(), 0);
+
+        assert_eq!(dst_box.entry_size(0), None);
+        assert_eq!(dst_box.entry_size(1), Some(4096));
+        assert_eq!(dst_box.entry_size(1_000_000), Some(4096));
+        assert_eq!(dst_box.entry_size(1_000_001), None);
+        assert_eq!(dst_box.total_size(), 4096 * 1_000_000);
+    }
+
     #[test]
     fn test_stsz_many_sizes() {
         let src_box = StszBox {
@@ -166,5 +218,8 @@ mod tests {
 
         let dst_box = StszBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(src_box, dst_box);
+        assert_eq!(dst_box.entry_size(4), Some(8545));
+        assert_eq!(dst_box.entry_size(10), None);
+        assert_eq!(dst_box.total_size(), 57448);
     }
 }
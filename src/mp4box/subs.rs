@@ -0,0 +1,238 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Sub-sample information, such as the NAL units of each sample or its
+/// clear and encrypted ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct SubsBox {
+    /// 0 for 16-bit sub-sample sizes, 1 for 32-bit ones.
+    pub version: u8,
+    pub flags: u32,
+
+    #[serde(skip_serializing)]
+    pub entries: Vec<SubsEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct SubsEntry {
+    /// The difference between the number of the sample described and that
+    /// of the previous entry, or of sample 0 for the first entry.
+    pub sample_delta: u32,
+    pub subsamples: Vec<Subsample>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct Subsample {
+    pub subsample_size: u32,
+    pub subsample_priority: u8,
+    pub discardable: u8,
+    pub codec_specific_parameters: u32,
+}
+
+impl SubsBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::SubsBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let subsample_size = if self.version == 1 { 10 } else { 8 };
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 4;
+        for entry in self.entries.iter() {
+            size += 6 + subsample_size * entry.subsamples.len() as u64;
+        }
+        size
+    }
+}
+
+impl Mp4Box for SubsBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("entries={}", self.entries.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SubsBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let entry_count = reader.read_u32::<BigEndian>()?;
+        let max_entries = size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE + 4) / 6;
+        if u64::from(entry_count) > max_entries {
+            return Err(Error::InvalidData(
+                "subs entry_count indicates more entries than could fit in the box",
+            ));
+        }
+        let end = start + size;
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let sample_delta = reader.read_u32::<BigEndian>()?;
+            let subsample_count = reader.read_u16::<BigEndian>()?;
+            let subsample_size = if version == 1 { 10 } else { 8 };
+            if reader.stream_position()? + subsample_size * subsample_count as u64 > end {
+                return Err(Error::InvalidData(
+                    "subs subsample_count indicates more subsamples than could fit in the box",
+                ));
+            }
+            let mut subsamples = Vec::with_capacity(subsample_count as usize);
+            for _ in 0..subsample_count {
+                let subsample_size = if version == 1 {
+                    reader.read_u32::<BigEndian>()?
+                } else {
+                    reader.read_u16::<BigEndian>()? as u32
+                };
+                subsamples.push(Subsample {
+                    subsample_size,
+                    subsample_priority: reader.read_u8()?,
+                    discardable: reader.read_u8()?,
+                    codec_specific_parameters: reader.read_u32::<BigEndian>()?,
+                });
+            }
+            entries.push(SubsEntry {
+                sample_delta,
+                subsamples,
+            });
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(SubsBox {
+            version,
+            flags,
+            entries,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SubsBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        writer.write_u32::<BigEndian>(self.entries.len() as u32)?;
+        for entry in self.entries.iter() {
+            writer.write_u32::<BigEndian>(entry.sample_delta)?;
+            if entry.subsamples.len() > u16::MAX as usize {
+                return Err(Error::InvalidData("too many subsamples in a subs entry"));
+            }
+            writer.write_u16::<BigEndian>(entry.subsamples.len() as u16)?;
+            for subsample in entry.subsamples.iter() {
+                if self.version == 1 {
+                    writer.write_u32::<BigEndian>(subsample.subsample_size)?;
+                } else if subsample.subsample_size <= u16::MAX as u32 {
+                    writer.write_u16::<BigEndian>(subsample.subsample_size as u16)?;
+                } else {
+                    return Err(Error::InvalidData(
+                        "subsample size does not fit in a version 0 subs box",
+                    ));
+                }
+                writer.write_u8(subsample.subsample_priority)?;
+                writer.write_u8(subsample.discardable)?;
+                writer.write_u32::<BigEndian>(subsample.codec_specific_parameters)?;
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    fn entries(large: bool) -> Vec<SubsEntry> {
+        let size = if large { 0x12345 } else { 0x1234 };
+        vec![
+            SubsEntry {
+                sample_delta: 1,
+                subsamples: vec![
+                    Subsample {
+                        subsample_size: 24,
+                        subsample_priority: 255,
+                        discardable: 0,
+                        codec_specific_parameters: 0,
+                    },
+                    Subsample {
+                        subsample_size: size,
+                        subsample_priority: 0,
+                        discardable: 1,
+                        codec_specific_parameters: 0x8000_0000,
+                    },
+                ],
+            },
+            SubsEntry {
+                sample_delta: 2,
+                subsamples: vec![Subsample {
+                    subsample_size: size,
+                    ..Default::default()
+                }],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_subs_v0() {
+        let src_box = SubsBox {
+            version: 0,
+            flags: 0,
+            entries: entries(false),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::SubsBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = SubsBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+
+        let large = SubsBox {
+            entries: entries(true),
+            ..src_box
+        };
+        assert!(large.write_box(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_subs_v1() {
+        let src_box = SubsBox {
+            version: 1,
+            flags: 0,
+            entries: entries(true),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::SubsBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = SubsBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
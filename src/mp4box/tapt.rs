@@ -0,0 +1,296 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// QuickTime track aperture mode dimensions, as written by editing software
+/// alongside the track header dimensions.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct TaptBox {
+    /// The clean aperture: the picture after cropping to `clap`, scaled to
+    /// square pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clef: Option<ClefBox>,
+
+    /// The production aperture: the full picture scaled to square pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prof: Option<ProfBox>,
+
+    /// The encoded pixels, neither cropped nor scaled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enof: Option<EnofBox>,
+}
+
+impl TaptBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::TaptBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        if let Some(ref clef) = self.clef {
+            size += clef.box_size();
+        }
+        if let Some(ref prof) = self.prof {
+            size += prof.box_size();
+        }
+        if let Some(ref enof) = self.enof {
+            size += enof.box_size();
+        }
+        size
+    }
+}
+
+impl Mp4Box for TaptBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let dimensions = |name: &str, width: &FixedPointU16, height: &FixedPointU16| {
+            format!(
+                "{}={}x{}",
+                name,
+                width.raw_value() as f64 / 65536.0,
+                height.raw_value() as f64 / 65536.0
+            )
+        };
+        let mut parts = Vec::new();
+        if let Some(ref clef) = self.clef {
+            parts.push(dimensions("clef", &clef.width, &clef.height));
+        }
+        if let Some(ref prof) = self.prof {
+            parts.push(dimensions("prof", &prof.width, &prof.height));
+        }
+        if let Some(ref enof) = self.enof {
+            parts.push(dimensions("enof", &enof.width, &enof.height));
+        }
+        Ok(parts.join(" "))
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TaptBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut clef = None;
+        let mut prof = None;
+        let mut enof = None;
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+        while current < end {
+            // Get box header.
+            let header = BoxHeader::read(reader)?;
+            let BoxHeader { name, size: s, .. } = header;
+            if s > size {
+                return Err(Error::InvalidData(
+                    "tapt box contains a box with a larger size than it",
+                ));
+            }
+
+            match name {
+                BoxType::ClefBox => {
+                    clef = Some(ClefBox::read_box(reader, s)?);
+                }
+                BoxType::ProfBox => {
+                    prof = Some(ProfBox::read_box(reader, s)?);
+                }
+                BoxType::EnofBox => {
+                    enof = Some(EnofBox::read_box(reader, s)?);
+                }
+                _ => {
+                    // XXX warn!()
+                    skip_box(reader, s)?;
+                }
+            }
+
+            current = next_box_position(reader, current)?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(TaptBox { clef, prof, enof })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TaptBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(ref clef) = self.clef {
+            clef.write_box(writer)?;
+        }
+        if let Some(ref prof) = self.prof {
+            prof.write_box(writer)?;
+        }
+        if let Some(ref enof) = self.enof {
+            enof.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+// The children of tapt only differ by their type.
+macro_rules! aperture_box {
+    ($name:ident) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+        pub struct $name {
+            pub version: u8,
+            pub flags: u32,
+
+            #[serde(with = "value_u32")]
+            pub width: FixedPointU16,
+
+            #[serde(with = "value_u32")]
+            pub height: FixedPointU16,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    version: 0,
+                    flags: 0,
+                    width: FixedPointU16::new(0),
+                    height: FixedPointU16::new(0),
+                }
+            }
+        }
+
+        impl $name {
+            pub fn get_type(&self) -> BoxType {
+                BoxType::$name
+            }
+
+            pub fn get_size(&self) -> u64 {
+                HEADER_SIZE + HEADER_EXT_SIZE + 8
+            }
+        }
+
+        impl Mp4Box for $name {
+            fn box_type(&self) -> BoxType {
+                self.get_type()
+            }
+
+            fn box_size(&self) -> u64 {
+                self.get_size()
+            }
+
+            fn to_json(&self) -> Result<String> {
+                Ok(serde_json::to_string(&self).unwrap())
+            }
+
+            fn summary(&self) -> Result<String> {
+                let s = format!(
+                    "width={} height={}",
+                    self.width.raw_value() as f64 / 65536.0,
+                    self.height.raw_value() as f64 / 65536.0
+                );
+                Ok(s)
+            }
+        }
+
+        impl<R: Read + Seek> ReadBox<&mut R> for $name {
+            fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+                let start = box_start(reader)?;
+
+                let (version, flags) = read_box_header_ext(reader)?;
+                let width = FixedPointU16::new_raw(reader.read_u32::<BigEndian>()?);
+                let height = FixedPointU16::new_raw(reader.read_u32::<BigEndian>()?);
+
+                skip_bytes_to(reader, start + size)?;
+
+                Ok($name {
+                    version,
+                    flags,
+                    width,
+                    height,
+                })
+            }
+        }
+
+        impl<W: Write> WriteBox<&mut W> for $name {
+            fn write_box(&self, writer: &mut W) -> Result<u64> {
+                let size = self.box_size();
+                BoxHeader::new(self.box_type(), size).write(writer)?;
+
+                write_box_header_ext(writer, self.version, self.flags)?;
+                writer.write_u32::<BigEndian>(self.width.raw_value())?;
+                writer.write_u32::<BigEndian>(self.height.raw_value())?;
+
+                Ok(size)
+            }
+        }
+    };
+}
+
+aperture_box!(ClefBox);
+aperture_box!(ProfBox);
+aperture_box!(EnofBox);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_tapt() {
+        // 1920x1080 anamorphic 1440x1080 HDV with a 1888x1062 clean aperture.
+        let dimensions = |width: f64, height: f64| {
+            (
+                FixedPointU16::new_raw((width * 65536.0) as u32),
+                FixedPointU16::new_raw((height * 65536.0) as u32),
+            )
+        };
+        let (width, height) = dimensions(1888.0, 1062.0);
+        let clef = ClefBox {
+            width,
+            height,
+            ..Default::default()
+        };
+        let (width, height) = dimensions(1920.0, 1080.0);
+        let prof = ProfBox {
+            width,
+            height,
+            ..Default::default()
+        };
+        let (width, height) = dimensions(1440.0, 1080.0);
+        let enof = EnofBox {
+            width,
+            height,
+            ..Default::default()
+        };
+        let src_box = TaptBox {
+            clef: Some(clef),
+            prof: Some(prof),
+            enof: Some(enof),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::TaptBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = TaptBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+        assert_eq!(
+            dst_box.summary().unwrap(),
+            "clef=1888x1062 prof=1920x1080 enof=1440x1080"
+        );
+    }
+}
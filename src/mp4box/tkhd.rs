@@ -114,6 +114,19 @@ impl TkhdBox {
     pub fn set_height(&mut self, height: u16) {
         self.height = FixedPointU16::new(height);
     }
+
+    pub fn is_enabled(&self) -> bool {
+        self.flags & TrackFlag::TrackEnabled as u32 != 0
+    }
+
+    /// Set or clear the track_enabled flag; players ignore disabled tracks.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.flags |= TrackFlag::TrackEnabled as u32;
+        } else {
+            self.flags &= !(TrackFlag::TrackEnabled as u32);
+        }
+    }
 }
 
 impl Mp4Box for TkhdBox {
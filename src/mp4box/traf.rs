@@ -2,13 +2,18 @@ use serde::Serialize;
 use std::io::{Read, Seek, Write};
 
 use crate::mp4box::*;
-use crate::mp4box::{tfdt::TfdtBox, tfhd::TfhdBox, trun::TrunBox};
+use crate::mp4box::{
+    saio::SaioBox, saiz::SaizBox, subs::SubsBox, tfdt::TfdtBox, tfhd::TfhdBox, trun::TrunBox,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct TrafBox {
     pub tfhd: TfhdBox,
     pub tfdt: Option<TfdtBox>,
     pub trun: Option<TrunBox>,
+    pub subs: Option<SubsBox>,
+    pub saiz: Option<SaizBox>,
+    pub saio: Option<SaioBox>,
 }
 
 impl TrafBox {
@@ -25,6 +30,15 @@ impl TrafBox {
         if let Some(ref trun) = self.trun {
             size += trun.box_size();
         }
+        if let Some(ref subs) = self.subs {
+            size += subs.box_size();
+        }
+        if let Some(ref saiz) = self.saiz {
+            size += saiz.box_size();
+        }
+        if let Some(ref saio) = self.saio {
+            size += saio.box_size();
+        }
         size
     }
 }
@@ -55,13 +69,16 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrafBox {
         let mut tfhd = None;
         let mut tfdt = None;
         let mut trun = None;
+        let mut subs = None;
+        let mut saiz = None;
+        let mut saio = None;
 
         let mut current = reader.stream_position()?;
         let end = start + size;
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "traf box contains a box with a larger size than it",
@@ -78,13 +95,22 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrafBox {
                 BoxType::TrunBox => {
                     trun = Some(TrunBox::read_box(reader, s)?);
                 }
+                BoxType::SubsBox => {
+                    subs = Some(SubsBox::read_box(reader, s)?);
+                }
+                BoxType::SaizBox => {
+                    saiz = Some(SaizBox::read_box(reader, s)?);
+                }
+                BoxType::SaioBox => {
+                    saio = Some(SaioBox::read_box(reader, s)?);
+                }
                 _ => {
                     // XXX warn!()
                     skip_box(reader, s)?;
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if tfhd.is_none() {
@@ -97,6 +123,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrafBox {
             tfhd: tfhd.unwrap(),
             tfdt,
             trun,
+            subs,
+            saiz,
+            saio,
         })
     }
 }
@@ -113,6 +142,15 @@ impl<W: Write> WriteBox<&mut W> for TrafBox {
         if let Some(ref trun) = self.trun {
             trun.write_box(writer)?;
         }
+        if let Some(ref subs) = self.subs {
+            subs.write_box(writer)?;
+        }
+        if let Some(ref saiz) = self.saiz {
+            saiz.write_box(writer)?;
+        }
+        if let Some(ref saio) = self.saio {
+            saio.write_box(writer)?;
+        }
 
         Ok(size)
     }
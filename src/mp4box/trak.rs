@@ -3,12 +3,15 @@ use std::io::{Read, Seek, Write};
 
 use crate::meta::MetaBox;
 use crate::mp4box::*;
-use crate::mp4box::{edts::EdtsBox, mdia::MdiaBox, tkhd::TkhdBox};
+use crate::mp4box::{edts::EdtsBox, mdia::MdiaBox, tapt::TaptBox, tkhd::TkhdBox};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct TrakBox {
     pub tkhd: TkhdBox,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tapt: Option<TaptBox>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub edts: Option<EdtsBox>,
 
@@ -26,6 +29,9 @@ impl TrakBox {
     pub fn get_size(&self) -> u64 {
         let mut size = HEADER_SIZE;
         size += self.tkhd.box_size();
+        if let Some(ref tapt) = self.tapt {
+            size += tapt.box_size();
+        }
         if let Some(ref edts) = self.edts {
             size += edts.box_size();
         }
@@ -58,6 +64,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrakBox {
         let start = box_start(reader)?;
 
         let mut tkhd = None;
+        let mut tapt = None;
         let mut edts = None;
         let mut meta = None;
         let mut mdia = None;
@@ -67,7 +74,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrakBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "trak box contains a box with a larger size than it",
@@ -78,6 +85,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrakBox {
                 BoxType::TkhdBox => {
                     tkhd = Some(TkhdBox::read_box(reader, s)?);
                 }
+                BoxType::TaptBox => {
+                    tapt = Some(TaptBox::read_box(reader, s)?);
+                }
                 BoxType::EdtsBox => {
                     edts = Some(EdtsBox::read_box(reader, s)?);
                 }
@@ -93,7 +103,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrakBox {
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         if tkhd.is_none() {
@@ -107,6 +117,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrakBox {
 
         Ok(TrakBox {
             tkhd: tkhd.unwrap(),
+            tapt,
             edts,
             meta,
             mdia: mdia.unwrap(),
@@ -120,6 +131,9 @@ impl<W: Write> WriteBox<&mut W> for TrakBox {
         BoxHeader::new(self.box_type(), size).write(writer)?;
 
         self.tkhd.write_box(writer)?;
+        if let Some(ref tapt) = self.tapt {
+            tapt.write_box(writer)?;
+        }
         if let Some(ref edts) = self.edts {
             edts.write_box(writer)?;
         }
@@ -0,0 +1,137 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct TrepBox {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+
+    #[serde(skip)]
+    pub data: Vec<(BoxType, Vec<u8>)>,
+}
+
+impl TrepBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::TrepBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + HEADER_EXT_SIZE
+            + 4
+            + self
+                .data
+                .iter()
+                .map(|(_, data)| data.len() as u64 + HEADER_SIZE)
+                .sum::<u64>()
+    }
+}
+
+impl Mp4Box for TrepBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("track_id={} data_len={}", self.track_id, self.data.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TrepBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let track_id = reader.read_u32::<BigEndian>()?;
+
+        let mut data = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+        while current < end {
+            // Get box header.
+            let header = BoxHeader::read(reader)?;
+            let BoxHeader { name, size: s, .. } = header;
+            if s > size {
+                return Err(Error::InvalidData(
+                    "trep box contains a box with a larger size than it",
+                ));
+            }
+            if s < HEADER_SIZE {
+                return Err(Error::InvalidData("box size smaller than its header"));
+            }
+
+            let mut box_data = vec![0; (s - HEADER_SIZE) as usize];
+            reader.read_exact(&mut box_data)?;
+            data.push((name, box_data));
+
+            current = next_box_position(reader, current)?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(TrepBox {
+            version,
+            flags,
+            track_id,
+            data,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TrepBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        writer.write_u32::<BigEndian>(self.track_id)?;
+        for (box_type, data) in self.data.iter() {
+            BoxHeader::new(*box_type, data.len() as u64 + HEADER_SIZE).write(writer)?;
+            writer.write_all(data)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_trep() {
+        let src_box = TrepBox {
+            version: 0,
+            flags: 0,
+            track_id: 1,
+            data: vec![(BoxType::UnknownBox(0x61737263), vec![0, 0, 0, 0, 1, 2])],
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::TrepBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = TrepBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
@@ -185,7 +185,17 @@ impl<W: Write> WriteBox<&mut W> for TrunBox {
         if let Some(v) = self.first_sample_flags {
             writer.write_u32::<BigEndian>(v)?;
         }
-        if self.sample_count != self.sample_sizes.len() as u32 {
+        // Only the values the flags say are present, as the others come
+        // from the tfhd or trex defaults.
+        let values = [
+            (TrunBox::FLAG_SAMPLE_DURATION, &self.sample_durations),
+            (TrunBox::FLAG_SAMPLE_SIZE, &self.sample_sizes),
+            (TrunBox::FLAG_SAMPLE_FLAGS, &self.sample_flags),
+            (TrunBox::FLAG_SAMPLE_CTS, &self.sample_cts),
+        ];
+        if values.iter().any(|(flag, values)| {
+            flag & self.flags > 0 && values.len() != self.sample_count as usize
+        }) {
             return Err(Error::InvalidData("sample count out of sync"));
         }
         for i in 0..self.sample_count as usize {
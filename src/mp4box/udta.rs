@@ -1,10 +1,16 @@
+use std::convert::TryFrom;
 use std::io::{Read, Seek};
 
 use serde::Serialize;
 
+use crate::mp4box::cprt::CprtBox;
 use crate::mp4box::meta::MetaBox;
 use crate::mp4box::*;
 
+/// A `udta` child the crate does not parse, kept as its raw body.
+///
+/// The `name` holds one character per byte of the box type, so that types
+/// such as `©xyz` survive a round-trip.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct UserDefinedBox {
     pub name: String,
@@ -12,10 +18,41 @@ pub struct UserDefinedBox {
     pub data: Vec<u8>,
 }
 
+impl UserDefinedBox {
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + self.data.len() as u64
+    }
+
+    fn box_type(&self) -> Result<BoxType> {
+        let mut value = [0; 4];
+        let mut chars = self.name.chars();
+        for byte in value.iter_mut() {
+            *byte = chars
+                .next()
+                .and_then(|c| u8::try_from(c as u32).ok())
+                .ok_or(Error::InvalidData(
+                    "user defined box name is not a box type",
+                ))?;
+        }
+        if chars.next().is_some() {
+            return Err(Error::InvalidData(
+                "user defined box name is not a box type",
+            ));
+        }
+        Ok(BoxType::from(u32::from_be_bytes(value)))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct UdtaBox {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<MetaBox>,
+
+    /// The first copyright notice; any others, in other languages, are kept
+    /// in `children`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cprt: Option<CprtBox>,
+
     pub children: Vec<UserDefinedBox>,
 }
 
@@ -29,6 +66,12 @@ impl UdtaBox {
         if let Some(meta) = &self.meta {
             size += meta.box_size();
         }
+        if let Some(cprt) = &self.cprt {
+            size += cprt.box_size();
+        }
+        for child in self.children.iter() {
+            size += child.get_size();
+        }
         size
     }
 
@@ -60,6 +103,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for UdtaBox {
         let start = box_start(reader)?;
 
         let mut meta = None;
+        let mut cprt = None;
         let mut children = Vec::new();
 
         let mut current = reader.stream_position()?;
@@ -67,31 +111,50 @@ impl<R: Read + Seek> ReadBox<&mut R> for UdtaBox {
         while current < end {
             // Get box header.
             let header = BoxHeader::read(reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "udta box contains a box with a larger size than it",
                 ));
             }
+            if s < HEADER_SIZE {
+                return Err(Error::InvalidData("box size smaller than its header"));
+            }
 
             match name {
                 BoxType::MetaBox => {
                     meta = Some(MetaBox::read_box(reader, s)?);
                 }
+                BoxType::CprtBox if cprt.is_none() => {
+                    cprt = Some(CprtBox::read_box(reader, s)?);
+                }
                 _ => {
                     // XXX warn!()
                     let mut data = vec![0; (s - 8) as usize];
                     reader.read_exact(&mut data)?;
-                    children.push(UserDefinedBox { name: name.to_string(), size: s, data });
+                    let name = u32::from(name)
+                        .to_be_bytes()
+                        .iter()
+                        .map(|&b| b as char)
+                        .collect();
+                    children.push(UserDefinedBox {
+                        name,
+                        size: s,
+                        data,
+                    });
                 }
             }
 
-            current = reader.stream_position()?;
+            current = next_box_position(reader, current)?;
         }
 
         skip_bytes_to(reader, start + size)?;
 
-        Ok(UdtaBox { meta, children })
+        Ok(UdtaBox {
+            meta,
+            cprt,
+            children,
+        })
     }
 }
 
@@ -103,6 +166,13 @@ impl<W: Write> WriteBox<&mut W> for UdtaBox {
         if let Some(meta) = &self.meta {
             meta.write_box(writer)?;
         }
+        if let Some(cprt) = &self.cprt {
+            cprt.write_box(writer)?;
+        }
+        for child in self.children.iter() {
+            BoxHeader::new(child.box_type()?, child.get_size()).write(writer)?;
+            writer.write_all(&child.data)?;
+        }
         Ok(size)
     }
 }
@@ -115,7 +185,11 @@ mod tests {
 
     #[test]
     fn test_udta_empty() {
-        let src_box = UdtaBox { meta: None, children: Vec::new() };
+        let src_box = UdtaBox {
+            meta: None,
+            cprt: None,
+            children: Vec::new(),
+        };
 
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
@@ -134,6 +208,7 @@ mod tests {
     fn test_udta() {
         let src_box = UdtaBox {
             meta: Some(MetaBox::default()),
+            cprt: None,
             children: Vec::new(),
         };
 
@@ -149,4 +224,49 @@ mod tests {
         let dst_box = UdtaBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(dst_box, src_box);
     }
+
+    #[test]
+    fn test_udta_children() {
+        let mut src = Vec::new();
+        src.extend_from_slice(&[0, 0, 0, 31]);
+        src.extend_from_slice(b"udta");
+        src.extend_from_slice(&[0, 0, 0, 12]);
+        src.extend_from_slice(b"name");
+        src.extend_from_slice(b"clip");
+        src.extend_from_slice(&[0, 0, 0, 11]);
+        src.extend_from_slice(&[0xA9, b'x', b'y', b'z']);
+        src.extend_from_slice(&[0, 0, 0x2B]);
+
+        let mut reader = Cursor::new(&src);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let udta = UdtaBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(udta.children.len(), 2);
+        assert_eq!(udta.children[0].name, "name");
+        assert_eq!(udta.children[1].name, "©xyz");
+        assert_eq!(udta.children[1].data, vec![0, 0, 0x2B]);
+        assert_eq!(udta.box_size(), src.len() as u64);
+
+        let mut buf = Vec::new();
+        udta.write_box(&mut buf).unwrap();
+        assert_eq!(buf, src);
+    }
+
+    #[test]
+    fn test_udta_child_size_too_small() {
+        for child_size in [0u32, 3] {
+            let mut src = Vec::new();
+            src.extend_from_slice(&[0, 0, 0, 20]);
+            src.extend_from_slice(b"udta");
+            src.extend_from_slice(&child_size.to_be_bytes());
+            src.extend_from_slice(b"name");
+            src.extend_from_slice(b"clip");
+
+            let mut reader = Cursor::new(&src);
+            let header = BoxHeader::read(&mut reader).unwrap();
+            assert!(matches!(
+                UdtaBox::read_box(&mut reader, header.size),
+                Err(Error::InvalidData(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,275 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use serde::Serialize;
+use std::io::{Read, Seek, Write};
+
+use crate::mp4box::*;
+
+/// Timed metadata sample entry for samples whose format is identified by a
+/// URI, such as ID3 tags in CMAF.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct UrimBox {
+    pub data_reference_index: u16,
+    pub uri: UriBox,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_init: Option<UriInitBox>,
+}
+
+impl UrimBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::UrimBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + 8 + self.uri.box_size();
+        if let Some(ref uri_init) = self.uri_init {
+            size += uri_init.box_size();
+        }
+        size
+    }
+}
+
+impl Mp4Box for UrimBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("uri={}", self.uri.uri);
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for UrimBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        reader.read_u32::<BigEndian>()?; // reserved
+        reader.read_u16::<BigEndian>()?; // reserved
+        let data_reference_index = reader.read_u16::<BigEndian>()?;
+
+        let mut uri = None;
+        let mut uri_init = None;
+        let end = start + size;
+        while reader.stream_position()? + HEADER_SIZE <= end {
+            let current = reader.stream_position()?;
+            let BoxHeader { name, size: s, .. } = BoxHeader::read(reader)?;
+            if s < HEADER_SIZE || current + s > end {
+                return Err(Error::InvalidData(
+                    "urim box contains a box with a larger size than it",
+                ));
+            }
+            match name {
+                BoxType::UriBox => uri = Some(UriBox::read_box(reader, s)?),
+                BoxType::UriInitBox => uri_init = Some(UriInitBox::read_box(reader, s)?),
+                _ => {}
+            }
+            skip_bytes_to(reader, current + s)?;
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(UrimBox {
+            data_reference_index,
+            uri: uri.ok_or(Error::BoxNotFound(BoxType::UriBox))?,
+            uri_init,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for UrimBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        writer.write_u32::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(0)?; // reserved
+        writer.write_u16::<BigEndian>(self.data_reference_index)?;
+        self.uri.write_box(writer)?;
+        if let Some(ref uri_init) = self.uri_init {
+            uri_init.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+/// A URI, here the format of the samples of a `urim` sample entry.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct UriBox {
+    pub version: u8,
+    pub flags: u32,
+    pub uri: String,
+}
+
+impl UriBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::UriBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + self.uri.len() as u64 + 1
+    }
+}
+
+impl Mp4Box for UriBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("uri={}", self.uri);
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for UriBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let uri = read_cstring(reader, start + size)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(UriBox {
+            version,
+            flags,
+            uri,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for UriBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        write_cstring(writer, &self.uri)?;
+
+        Ok(size)
+    }
+}
+
+/// Initialization data for the format named by a [UriBox], kept as raw
+/// bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct UriInitBox {
+    pub version: u8,
+    pub flags: u32,
+
+    #[serde(skip_serializing)]
+    pub data: Vec<u8>,
+}
+
+impl UriInitBox {
+    pub fn get_type(&self) -> BoxType {
+        BoxType::UriInitBox
+    }
+
+    pub fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + self.data.len() as u64
+    }
+}
+
+impl Mp4Box for UriInitBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self).unwrap())
+    }
+
+    fn summary(&self) -> Result<String> {
+        let s = format!("size={}", self.data.len());
+        Ok(s)
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for UriInitBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let (version, flags) = read_box_header_ext(reader)?;
+        let data_size = size
+            .checked_sub(HEADER_SIZE + HEADER_EXT_SIZE)
+            .ok_or(Error::InvalidData("uriI size too small"))?;
+        let mut data = vec![0; data_size as usize];
+        reader.read_exact(&mut data)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(UriInitBox {
+            version,
+            flags,
+            data,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for UriInitBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        write_box_header_ext(writer, self.version, self.flags)?;
+        writer.write_all(&self.data)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mp4box::BoxHeader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_urim() {
+        let src_box = UrimBox {
+            data_reference_index: 1,
+            uri: UriBox {
+                uri: String::from("https://aomedia.org/emsg/ID3"),
+                ..Default::default()
+            },
+            uri_init: Some(UriInitBox {
+                data: vec![1, 2, 3],
+                ..Default::default()
+            }),
+        };
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::UrimBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = UrimBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
+}
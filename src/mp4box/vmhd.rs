@@ -20,6 +20,14 @@ pub struct RgbColor {
 }
 
 impl VmhdBox {
+    pub fn new(config: &TrackConfig) -> Self {
+        VmhdBox {
+            graphics_mode: config.graphics_mode,
+            op_color: config.op_color.clone(),
+            ..Default::default()
+        }
+    }
+
     pub fn get_type(&self) -> BoxType {
         BoxType::VmhdBox
     }
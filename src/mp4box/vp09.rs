@@ -20,6 +20,15 @@ pub struct Vp09Box {
     pub depth: u16,
     pub end_code: u16,
     pub vpcc: VpccBox,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mdcv: Option<MdcvBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clli: Option<ClliBox>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amve: Option<AmveBox>,
 }
 
 impl Vp09Box {
@@ -48,19 +57,10 @@ impl Vp09Box {
             compressorname: Vp09Box::DEFAULT_COMPRESSORNAME,
             depth: Vp09Box::DEFAULT_DEPTH,
             end_code: Vp09Box::DEFAULT_END_CODE,
-            vpcc: VpccBox {
-                version: VpccBox::DEFAULT_VERSION,
-                flags: 0,
-                profile: 0,
-                level: 0x1F,
-                bit_depth: VpccBox::DEFAULT_BIT_DEPTH,
-                chroma_subsampling: 0,
-                video_full_range_flag: false,
-                color_primaries: 0,
-                transfer_characteristics: 0,
-                matrix_coefficients: 0,
-                codec_initialization_data_size: 0,
-            },
+            vpcc: VpccBox::new(config),
+            mdcv: None,
+            clli: None,
+            amve: None,
         }
     }
 }
@@ -71,7 +71,7 @@ impl Mp4Box for Vp09Box {
     }
 
     fn box_size(&self) -> u64 {
-        0x6A
+        0x56 + self.vpcc.box_size() + hdr_boxes_size(&self.mdcv, &self.clli, &self.amve)
     }
 
     fn to_json(&self) -> Result<String> {
@@ -129,7 +129,30 @@ impl<R: Read + Seek> ReadBox<&mut R> for Vp09Box {
             VpccBox::read_box(reader, header.size)?
         };
 
-        skip_bytes_to(reader, start + size)?;
+        let mut mdcv = None;
+        let mut clli = None;
+        let mut amve = None;
+
+        let end = start + size;
+        let mut current = reader.stream_position()?;
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            let BoxHeader { name, size: s, .. } = header;
+            if s > size {
+                return Err(Error::InvalidData(
+                    "vp09 box contains a box with a larger size than it",
+                ));
+            }
+            match name {
+                BoxType::MdcvBox => mdcv = Some(MdcvBox::read_box(reader, s)?),
+                BoxType::ClliBox => clli = Some(ClliBox::read_box(reader, s)?),
+                BoxType::AmveBox => amve = Some(AmveBox::read_box(reader, s)?),
+                _ => skip_bytes_to(reader, current + s)?,
+            }
+            current = next_box_position(reader, current)?;
+        }
+
+        skip_bytes_to(reader, end)?;
 
         Ok(Self {
             version,
@@ -147,6 +170,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for Vp09Box {
             depth,
             end_code,
             vpcc,
+            mdcv,
+            clli,
+            amve,
         })
     }
 }
@@ -173,6 +199,7 @@ impl<W: Write> WriteBox<&mut W> for Vp09Box {
         writer.write_u16::<BigEndian>(self.depth)?;
         writer.write_u16::<BigEndian>(self.end_code)?;
         VpccBox::write_box(&self.vpcc, writer)?;
+        write_hdr_boxes(writer, &self.mdcv, &self.clli, &self.amve)?;
 
         Ok(size)
     }
@@ -189,6 +216,7 @@ mod tests {
         let src_box = Vp09Box::new(&Vp9Config {
             width: 1920,
             height: 1080,
+            ..Default::default()
         });
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
@@ -14,12 +14,28 @@ pub struct VpccBox {
     pub color_primaries: u8,
     pub transfer_characteristics: u8,
     pub matrix_coefficients: u8,
-    pub codec_initialization_data_size: u16,
+    pub codec_initialization_data: Vec<u8>,
 }
 
 impl VpccBox {
     pub const DEFAULT_VERSION: u8 = 1;
     pub const DEFAULT_BIT_DEPTH: u8 = 8;
+
+    pub fn new(config: &Vp9Config) -> Self {
+        VpccBox {
+            version: VpccBox::DEFAULT_VERSION,
+            flags: 0,
+            profile: config.profile,
+            level: config.level,
+            bit_depth: config.bit_depth,
+            chroma_subsampling: config.chroma_subsampling,
+            video_full_range_flag: config.video_full_range_flag,
+            color_primaries: config.color_primaries,
+            transfer_characteristics: config.transfer_characteristics,
+            matrix_coefficients: config.matrix_coefficients,
+            codec_initialization_data: config.codec_initialization_data.clone(),
+        }
+    }
 }
 
 impl Mp4Box for VpccBox {
@@ -28,7 +44,7 @@ impl Mp4Box for VpccBox {
     }
 
     fn box_size(&self) -> u64 {
-        HEADER_SIZE + HEADER_EXT_SIZE + 8
+        HEADER_SIZE + HEADER_EXT_SIZE + 8 + self.codec_initialization_data.len() as u64
     }
 
     fn to_json(&self) -> Result<String> {
@@ -51,9 +67,17 @@ impl<R: Read + Seek> ReadBox<&mut R> for VpccBox {
             let b = reader.read_u8()?;
             (b >> 4, b << 4 >> 5, b & 0x01 == 1)
         };
+        let color_primaries: u8 = reader.read_u8()?;
         let transfer_characteristics: u8 = reader.read_u8()?;
         let matrix_coefficients: u8 = reader.read_u8()?;
         let codec_initialization_data_size: u16 = reader.read_u16::<BigEndian>()?;
+        if HEADER_SIZE + HEADER_EXT_SIZE + 8 + codec_initialization_data_size as u64 > size {
+            return Err(Error::InvalidData(
+                "vpcC codec initialization data is larger than the box",
+            ));
+        }
+        let mut codec_initialization_data = vec![0; codec_initialization_data_size as usize];
+        reader.read_exact(&mut codec_initialization_data)?;
 
         skip_bytes_to(reader, start + size)?;
 
@@ -65,10 +89,10 @@ impl<R: Read + Seek> ReadBox<&mut R> for VpccBox {
             bit_depth,
             chroma_subsampling,
             video_full_range_flag,
-            color_primaries: 0,
+            color_primaries,
             transfer_characteristics,
             matrix_coefficients,
-            codec_initialization_data_size,
+            codec_initialization_data,
         })
     }
 }
@@ -90,7 +114,13 @@ impl<W: Write> WriteBox<&mut W> for VpccBox {
         writer.write_u8(self.color_primaries)?;
         writer.write_u8(self.transfer_characteristics)?;
         writer.write_u8(self.matrix_coefficients)?;
-        writer.write_u16::<BigEndian>(self.codec_initialization_data_size)?;
+        if self.codec_initialization_data.len() > u16::MAX as usize {
+            return Err(Error::InvalidData(
+                "vpcC codec initialization data is too large",
+            ));
+        }
+        writer.write_u16::<BigEndian>(self.codec_initialization_data.len() as u16)?;
+        writer.write_all(&self.codec_initialization_data)?;
 
         Ok(size)
     }
@@ -107,15 +137,15 @@ mod tests {
         let src_box = VpccBox {
             version: VpccBox::DEFAULT_VERSION,
             flags: 0,
-            profile: 0,
-            level: 0x1F,
-            bit_depth: VpccBox::DEFAULT_BIT_DEPTH,
-            chroma_subsampling: 0,
-            video_full_range_flag: false,
-            color_primaries: 0,
-            transfer_characteristics: 0,
-            matrix_coefficients: 0,
-            codec_initialization_data_size: 0,
+            profile: 2,
+            level: 41,
+            bit_depth: 10,
+            chroma_subsampling: 1,
+            video_full_range_flag: true,
+            color_primaries: 9,
+            transfer_characteristics: 16,
+            matrix_coefficients: 9,
+            codec_initialization_data: vec![],
         };
         let mut buf = Vec::new();
         src_box.write_box(&mut buf).unwrap();
@@ -129,4 +159,23 @@ mod tests {
         let dst_box = VpccBox::read_box(&mut reader, header.size).unwrap();
         assert_eq!(src_box, dst_box);
     }
+
+    #[test]
+    fn test_vpcc_codec_initialization_data() {
+        let src_box = VpccBox::new(&Vp9Config {
+            codec_initialization_data: vec![1, 2, 3],
+            ..Default::default()
+        });
+        let mut buf = Vec::new();
+        src_box.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), src_box.box_size() as usize);
+
+        let mut reader = Cursor::new(&buf);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        assert_eq!(header.name, BoxType::VpccBox);
+        assert_eq!(src_box.box_size(), header.size);
+
+        let dst_box = VpccBox::read_box(&mut reader, header.size).unwrap();
+        assert_eq!(src_box, dst_box);
+    }
 }
@@ -0,0 +1,210 @@
+//! Packaging progressive files into fragmented segments for adaptive
+//! streaming, such as DASH or HLS.
+
+use std::io::{Read, Seek};
+use std::time::Duration;
+
+use crate::mp4box::*;
+use crate::writer::{write_fragment, FragmentTrack};
+use crate::*;
+
+/// A segment produced by [segment].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// The init segment: an `ftyp` and a `moov` with `mvex` describing the
+    /// tracks but holding no samples.
+    Init(Vec<u8>),
+
+    /// A media segment: a `moof` and its `mdat`.
+    Media(MediaSegment),
+}
+
+/// A `moof` and its `mdat`, with the span of the reference track it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSegment {
+    /// The `mfhd` sequence number, starting at 1.
+    pub sequence_number: u32,
+
+    /// The decode time of the first sample of the reference track.
+    pub start_time: Duration,
+    pub duration: Duration,
+    pub data: Vec<u8>,
+}
+
+/// Split the progressive file in `input` into an init segment followed by
+/// media segments of about `target` each, handing them to `sink` in order.
+///
+/// Segments are cut at the sync samples of the reference track, the first
+/// video track or else the first track, that are nearest to each multiple of
+/// `target`; the samples of the other tracks go to the segment their decode
+/// time falls in. Each segment holds one `traf` per track with samples in
+/// it, or per run of them sharing a sample description, whose `tfdt` is the
+/// decode time of its first sample in the input.
+///
+/// The init segment keeps the `trak`s of the input, with their sample
+/// entries, edit lists and track ids, but empty sample tables. It carries
+/// the `cmfc` brand, although the segments multiplex every track rather than
+/// holding one track each as CMAF requires.
+pub fn segment<R, F>(input: R, size: u64, target: Duration, mut sink: F) -> Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(Segment),
+{
+    if target.is_zero() {
+        return Err(Error::InvalidData("segment target duration is zero"));
+    }
+
+    let mut mp4 = Mp4Reader::read_header(input, size)?;
+    let mut track_ids: Vec<u32> = mp4.tracks().keys().copied().collect();
+    track_ids.sort_unstable();
+    let reference_id = track_ids
+        .iter()
+        .copied()
+        .find(|id| matches!(mp4.tracks()[id].track_type(), Ok(TrackType::Video)))
+        .or_else(|| track_ids.first().copied())
+        .ok_or(Error::InvalidData("no tracks to segment"))?;
+
+    let reference = &mp4.tracks()[&reference_id];
+    let reference_timescale = reference.timescale() as u128;
+    if reference_timescale == 0 {
+        return Err(Error::InvalidData("reference track timescale is zero"));
+    }
+    let reference_timing = reference.timing_table();
+    let end = match (
+        reference_timing.dts.last(),
+        reference_timing.duration.last(),
+    ) {
+        (Some(&dts), Some(&duration)) => dts + duration as u64,
+        _ => 0,
+    };
+    let sync_times: Vec<u64> = reference
+        .sync_samples()
+        .into_iter()
+        .filter_map(|id| reference_timing.dts.get(id as usize - 1).copied())
+        .collect();
+
+    // The reference decode times each segment after the first starts at.
+    let start = reference_timing.dts.first().copied().unwrap_or(0);
+    let mut cuts: Vec<u64> = Vec::new();
+    let target = (target.as_nanos() * reference_timescale / 1_000_000_000).max(1);
+    for k in 1.. {
+        let boundary = start as u128 + k * target;
+        if boundary >= end as u128 {
+            break;
+        }
+        // The sync times either side of the boundary, the earlier on a tie.
+        let i = sync_times.partition_point(|&time| (time as u128) < boundary);
+        let nearest = match (i.checked_sub(1).map(|i| sync_times[i]), sync_times.get(i)) {
+            (Some(before), Some(&after))
+                if after as u128 - boundary < boundary - before as u128 =>
+            {
+                Some(after)
+            }
+            (Some(before), _) => Some(before),
+            (None, after) => after.copied(),
+        };
+        if let Some(time) = nearest {
+            if time > *cuts.last().unwrap_or(&start) {
+                cuts.push(time);
+            }
+        }
+    }
+
+    // The first sample id of each segment, and one past the last, per track.
+    let mut ranges = Vec::new();
+    for &track_id in track_ids.iter() {
+        let track = &mp4.tracks()[&track_id];
+        let timescale = track.timescale() as u128;
+        let dts = track.timing_table().dts;
+        let mut bounds = vec![1];
+        for &cut in cuts.iter() {
+            let index =
+                dts.partition_point(|&d| d as u128 * reference_timescale < cut as u128 * timescale);
+            bounds.push(index as u32 + 1);
+        }
+        bounds.push(dts.len() as u32 + 1);
+        ranges.push(bounds);
+    }
+
+    let mut init = Vec::new();
+    write_box_checked(&init_ftyp(), &mut init)?;
+    write_box_checked(&init_moov(&mp4.moov), &mut init)?;
+    sink(Segment::Init(init));
+
+    let to_duration = |ticks: u64| {
+        Duration::from_nanos((ticks as u128 * 1_000_000_000 / reference_timescale) as u64)
+    };
+    for index in 0..=cuts.len() {
+        let mut tracks = Vec::new();
+        for (&track_id, bounds) in track_ids.iter().zip(ranges.iter()) {
+            // A traf for each run of samples with the same description.
+            let mut trafs: Vec<(u32, FragmentTrack)> = Vec::new();
+            for sample_id in bounds[index]..bounds[index + 1] {
+                let sample = match mp4.read_sample(track_id, sample_id)? {
+                    Some(sample) => sample,
+                    None => continue,
+                };
+                let description_index =
+                    mp4.tracks()[&track_id].sample_description_index(sample_id)?;
+                match trafs.last_mut() {
+                    Some((last_index, traf)) if *last_index == description_index => {
+                        traf.add_sample(&sample)
+                    }
+                    _ => {
+                        let mut traf = FragmentTrack::new(track_id, sample.start_time);
+                        traf.set_sample_description_index(description_index);
+                        traf.add_sample(&sample);
+                        trafs.push((description_index, traf));
+                    }
+                }
+            }
+            tracks.extend(trafs.into_iter().map(|(_, traf)| traf));
+        }
+
+        let sequence_number = index as u32 + 1;
+        let mut data = Vec::new();
+        write_fragment(&mut data, sequence_number, &mut tracks)?;
+        let segment_start = if index == 0 { start } else { cuts[index - 1] };
+        let segment_end = cuts.get(index).copied().unwrap_or(end);
+        sink(Segment::Media(MediaSegment {
+            sequence_number,
+            start_time: to_duration(segment_start),
+            duration: to_duration(segment_end.saturating_sub(segment_start)),
+            data,
+        }));
+    }
+
+    Ok(())
+}
+
+fn init_ftyp() -> FtypBox {
+    FtypBox {
+        major_brand: FourCC::from(*b"iso6"),
+        minor_version: 0,
+        compatible_brands: vec![FourCC::from(*b"iso6"), FourCC::from(*b"cmfc")],
+    }
+}
+
+// The `moov` of the input with its samples moved out to fragments.
+fn init_moov(moov: &MoovBox) -> MoovBox {
+    let mut moov = moov.clone();
+    moov.mvhd.duration = 0;
+    let mut mvex = MvexBox::default();
+    for trak in moov.traks.iter_mut() {
+        trak.tkhd.duration = 0;
+        trak.mdia.mdhd.duration = 0;
+        let stbl = &mut trak.mdia.minf.stbl;
+        *stbl = StblBox {
+            stsd: stbl.stsd.clone(),
+            stco: Some(StcoBox::default()),
+            ..Default::default()
+        };
+        mvex.trexs.push(TrexBox {
+            track_id: trak.tkhd.track_id,
+            default_sample_description_index: 1,
+            ..Default::default()
+        });
+    }
+    moov.mvex = Some(mvex);
+    moov
+}
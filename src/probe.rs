@@ -0,0 +1,165 @@
+//! Fast extraction of a file's summary metadata.
+
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use crate::mp4box::*;
+use crate::*;
+
+/// The summary of a file returned by [probe].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaInfo {
+    pub major_brand: FourCC,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<FourCC>,
+    pub timescale: u32,
+    pub duration: Duration,
+
+    /// Whether `moov` has an `mvex` or any `moof` follows it. The sample
+    /// counts of the tracks then only cover the `moov` sample tables.
+    pub is_fragmented: bool,
+    pub tracks: Vec<TrackInfo>,
+}
+
+/// The summary of a track, in the order of the `trak` boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    pub track_id: u32,
+    pub handler_type: HandlerType,
+
+    /// The type of the first sample entry, and the media type for those the
+    /// crate supports.
+    pub box_type: Option<FourCC>,
+    pub media_type: Option<MediaType>,
+    pub width: u16,
+    pub height: u16,
+    pub language: String,
+    pub timescale: u32,
+    pub duration: Duration,
+    pub sample_count: u32,
+}
+
+/// Read the summary metadata of a file: its brands, duration and tracks with
+/// their codecs and dimensions.
+///
+/// Unlike [Mp4Reader::read_header], only the headers and sample descriptions
+/// are parsed; the sample tables are skipped over, except for the sample
+/// count of `stsz`, so reading many files is much faster but no samples can
+/// be read.
+pub fn probe<R: Read + Seek>(reader: &mut R, size: u64) -> Result<MediaInfo> {
+    let mut ftyp = None;
+    let mut moov = None;
+    let mut is_fragmented = false;
+    for child in child_boxes(reader, 0, size)? {
+        reader.seek(SeekFrom::Start(child.body.start))?;
+        match child.name {
+            BoxType::FtypBox if ftyp.is_none() => {
+                ftyp = Some(FtypBox::read_box(reader, child.read_size())?)
+            }
+            BoxType::MoovBox if moov.is_none() => moov = Some(child),
+            BoxType::MoofBox => is_fragmented = true,
+            _ => {}
+        }
+    }
+    let ftyp = ftyp.ok_or(Error::BoxNotFound(BoxType::FtypBox))?;
+    let moov = moov.ok_or(Error::BoxNotFound(BoxType::MoovBox))?;
+
+    let mut mvhd = None;
+    let mut tracks = Vec::new();
+    for child in child_boxes(reader, moov.body.start, moov.body.end)? {
+        reader.seek(SeekFrom::Start(child.body.start))?;
+        match child.name {
+            BoxType::MvhdBox => mvhd = Some(MvhdBox::read_box(reader, child.read_size())?),
+            BoxType::MvexBox => is_fragmented = true,
+            BoxType::TrakBox => tracks.push(probe_trak(reader, &child)?),
+            _ => {}
+        }
+    }
+    let mvhd = mvhd.ok_or(Error::BoxNotFound(BoxType::MvhdBox))?;
+
+    Ok(MediaInfo {
+        major_brand: ftyp.major_brand,
+        minor_version: ftyp.minor_version,
+        compatible_brands: ftyp.compatible_brands,
+        timescale: mvhd.timescale,
+        duration: duration(mvhd.duration, mvhd.timescale),
+        is_fragmented,
+        tracks,
+    })
+}
+
+fn probe_trak<R: Read + Seek>(reader: &mut R, trak_box: &ChildBox) -> Result<TrackInfo> {
+    // Only the boxes the track summary needs are filled in.
+    let mut trak = TrakBox::default();
+    let mut mdia = None;
+    for child in child_boxes(reader, trak_box.body.start, trak_box.body.end)? {
+        reader.seek(SeekFrom::Start(child.body.start))?;
+        match child.name {
+            BoxType::TkhdBox => trak.tkhd = TkhdBox::read_box(reader, child.read_size())?,
+            BoxType::MdiaBox => mdia = Some(child),
+            _ => {}
+        }
+    }
+    let mdia = mdia.ok_or(Error::BoxNotFound(BoxType::MdiaBox))?;
+
+    let mut minf = None;
+    for child in child_boxes(reader, mdia.body.start, mdia.body.end)? {
+        reader.seek(SeekFrom::Start(child.body.start))?;
+        match child.name {
+            BoxType::MdhdBox => trak.mdia.mdhd = MdhdBox::read_box(reader, child.read_size())?,
+            BoxType::HdlrBox => trak.mdia.hdlr = HdlrBox::read_box(reader, child.read_size())?,
+            BoxType::MinfBox => minf = Some(child),
+            _ => {}
+        }
+    }
+
+    let stbl = match minf {
+        Some(minf) => child_boxes(reader, minf.body.start, minf.body.end)?
+            .into_iter()
+            .find(|child| child.name == BoxType::StblBox),
+        None => None,
+    };
+    if let Some(stbl) = stbl {
+        for child in child_boxes(reader, stbl.body.start, stbl.body.end)? {
+            reader.seek(SeekFrom::Start(child.body.start))?;
+            match child.name {
+                BoxType::StsdBox => {
+                    trak.mdia.minf.stbl.stsd = StsdBox::read_box(reader, child.read_size())?
+                }
+                BoxType::StszBox => {
+                    // Version and flags, then sample_size.
+                    reader.read_u32::<BigEndian>()?;
+                    reader.read_u32::<BigEndian>()?;
+                    trak.mdia.minf.stbl.stsz.sample_count = reader.read_u32::<BigEndian>()?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let track = Mp4Track::from(&trak);
+    let mdhd = &trak.mdia.mdhd;
+    Ok(TrackInfo {
+        track_id: track.track_id(),
+        handler_type: track.handler_type(),
+        box_type: track
+            .sample_descriptions()
+            .first()
+            .map(|entry| FourCC::from(entry.box_type())),
+        media_type: track.media_type().ok(),
+        width: track.width(),
+        height: track.height(),
+        language: track.language().to_string(),
+        timescale: mdhd.timescale,
+        duration: duration(mdhd.duration, mdhd.timescale),
+        sample_count: trak.mdia.minf.stbl.stsz.sample_count,
+    })
+}
+
+fn duration(duration: u64, timescale: u32) -> Duration {
+    if timescale == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_micros((duration as u128 * 1_000_000 / timescale as u128) as u64)
+}
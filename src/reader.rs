@@ -1,10 +1,75 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::io::{Read, Seek};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::time::Duration;
 
 use crate::meta::MetaBox;
 use crate::*;
 
+/// Classify a file from its leading bytes without a full parse.
+///
+/// The buffer must start with `ftyp`. Any further top-level boxes present in
+/// the buffer are scanned for `moof` or `moov/mvex` to detect fragmented files
+/// whose brands do not say so; a truncated buffer is not an error.
+pub fn probe_container(bytes: &[u8]) -> Result<ContainerKind> {
+    let size = bytes.len() as u64;
+    let mut reader = Cursor::new(bytes);
+
+    let header = BoxHeader::read(&mut reader)?;
+    if header.name != BoxType::FtypBox {
+        return Err(Error::BoxNotFound(BoxType::FtypBox));
+    }
+    if header.size > size {
+        return Err(Error::InvalidData("ftyp box is truncated"));
+    }
+    let ftyp = FtypBox::read_box(&mut reader, header.size)?;
+
+    let kind = ftyp.container_kind();
+    if kind != ContainerKind::Mp4 {
+        return Ok(kind);
+    }
+
+    // The offset past a box starting at `start`, which must lie past it.
+    let box_end = |start: u64, header: &BoxHeader| {
+        start
+            .checked_add(header.size - HEADER_SIZE + header.header_size)
+            .filter(|&end| end > start)
+            .ok_or(Error::InvalidData("box size overflows its offset"))
+    };
+
+    let mut current = reader.position();
+    while size.saturating_sub(current) >= HEADER_SIZE {
+        let header = BoxHeader::read(&mut reader)?;
+        if header.size < HEADER_SIZE {
+            break;
+        }
+        let next = box_end(current, &header)?;
+        match header.name {
+            BoxType::MoofBox => return Ok(ContainerKind::FragmentedMp4),
+            BoxType::MoovBox => {
+                let end = next.min(size);
+                let mut child = reader.position();
+                while end.saturating_sub(child) >= HEADER_SIZE {
+                    let child_header = BoxHeader::read(&mut reader)?;
+                    if child_header.name == BoxType::MvexBox {
+                        return Ok(ContainerKind::FragmentedMp4);
+                    }
+                    if child_header.size < HEADER_SIZE {
+                        break;
+                    }
+                    child = box_end(child, &child_header)?;
+                    reader.set_position(child);
+                }
+            }
+            _ => {}
+        }
+        current = next;
+        reader.set_position(current);
+    }
+
+    Ok(kind)
+}
+
 #[derive(Debug)]
 pub struct Mp4Reader<R> {
     reader: R,
@@ -13,12 +78,37 @@ pub struct Mp4Reader<R> {
     pub moofs: Vec<MoofBox>,
     pub emsgs: Vec<EmsgBox>,
 
+    /// The file-level `meta` box, if any and it could be parsed.
+    pub meta: Option<MetaBox>,
+
     tracks: HashMap<u32, Mp4Track>,
     size: u64,
+
+    // Total size of the mdat boxes, headers included.
+    mdat_size: u64,
+
+    // End of the data present, set when reading in recovery mode.
+    data_end: Option<u64>,
 }
 
 impl<R: Read + Seek> Mp4Reader<R> {
-    pub fn read_header(mut reader: R, size: u64) -> Result<Self> {
+    pub fn read_header(reader: R, size: u64) -> Result<Self> {
+        Self::read_header_inner(reader, size, false)
+    }
+
+    /// Read a file that may be truncated, e.g. by an interrupted recording.
+    ///
+    /// Parsing stops at the first top-level box that extends past `size`
+    /// instead of failing. If no `moov` was found, the file is scanned for
+    /// `moof` boxes and a minimal `moov` is rebuilt from them with
+    /// [recover::rebuild_moov](crate::recover::rebuild_moov). `read_sample`
+    /// then returns `None` for samples that are not fully present; see
+    /// [Mp4Reader::first_truncated_sample].
+    pub fn read_header_recover(reader: R, size: u64) -> Result<Self> {
+        Self::read_header_inner(reader, size, true)
+    }
+
+    fn read_header_inner(mut reader: R, size: u64, recover: bool) -> Result<Self> {
         let start = reader.stream_position()?;
 
         let mut ftyp = None;
@@ -26,12 +116,25 @@ impl<R: Read + Seek> Mp4Reader<R> {
         let mut moofs = Vec::new();
         let mut moof_offsets = Vec::new();
         let mut emsgs = Vec::new();
+        let mut meta = None;
+        let mut mdat_size = 0;
 
         let mut current = start;
         while current < size {
             // Get box header.
-            let header = BoxHeader::read(&mut reader)?;
-            let BoxHeader { name, size: s } = header;
+            let header = match BoxHeader::read(&mut reader) {
+                Ok(header) => header,
+                Err(_) if recover => break,
+                Err(err) => return Err(err),
+            };
+            let BoxHeader { name, size: s, .. } = header;
+            if recover
+                && current
+                    .checked_add(s.saturating_sub(HEADER_SIZE) + header.header_size)
+                    .is_none_or(|end| end > size)
+            {
+                break;
+            }
             if s > size {
                 return Err(Error::InvalidData(
                     "file contains a box with a larger size than it",
@@ -40,6 +143,10 @@ impl<R: Read + Seek> Mp4Reader<R> {
 
             // Break if size zero BoxHeader, which can result in dead-loop.
             if s == 0 {
+                if name == BoxType::MdatBox {
+                    mdat_size += size - current;
+                    current = size;
+                }
                 break;
             }
 
@@ -52,13 +159,14 @@ impl<R: Read + Seek> Mp4Reader<R> {
                     skip_box(&mut reader, s)?;
                 }
                 BoxType::MdatBox => {
+                    mdat_size += s + header.header_size - HEADER_SIZE;
                     skip_box(&mut reader, s)?;
                 }
                 BoxType::MoovBox => {
                     moov = Some(MoovBox::read_box(&mut reader, s)?);
                 }
                 BoxType::MoofBox => {
-                    let moof_offset = reader.stream_position()? - 8;
+                    let moof_offset = reader.stream_position()? - header.header_size;
                     let moof = MoofBox::read_box(&mut reader, s)?;
                     moofs.push(moof);
                     moof_offsets.push(moof_offset);
@@ -67,21 +175,41 @@ impl<R: Read + Seek> Mp4Reader<R> {
                     let emsg = EmsgBox::read_box(&mut reader, s)?;
                     emsgs.push(emsg);
                 }
+                BoxType::MetaBox if meta.is_none() => {
+                    // Skipped rather than failing the file if it cannot be
+                    // parsed, such as when it has no hdlr.
+                    let body_start = reader.stream_position()?;
+                    meta = MetaBox::read_box(&mut reader, s).ok();
+                    reader.seek(SeekFrom::Start(body_start + s - HEADER_SIZE))?;
+                }
                 _ => {
                     // XXX warn!()
                     skip_box(&mut reader, s)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(&mut reader, current)?;
         }
 
         if ftyp.is_none() {
             return Err(Error::BoxNotFound(BoxType::FtypBox));
         }
+        if moov.is_none() && recover {
+            let found = recover::scan_moofs(&mut reader, start, size)?;
+            if !found.is_empty() {
+                moofs.clear();
+                moof_offsets.clear();
+                for (moof_offset, moof) in found {
+                    moofs.push(moof);
+                    moof_offsets.push(moof_offset);
+                }
+                moov = Some(recover::moov_from_moofs(moofs.iter()));
+            }
+        }
         if moov.is_none() {
             return Err(Error::BoxNotFound(BoxType::MoovBox));
         }
 
+        let data_end = if recover { Some(size) } else { None };
         let size = current - start;
         let mut tracks = if let Some(ref moov) = moov {
             if moov.traks.iter().any(|trak| trak.tkhd.track_id == 0) {
@@ -89,7 +217,7 @@ impl<R: Read + Seek> Mp4Reader<R> {
             }
             moov.traks
                 .iter()
-                .map(|trak| (trak.tkhd.track_id, Mp4Track::from(trak)))
+                .map(|trak| (trak.tkhd.track_id, track_with_trex(trak, moov)))
                 .collect()
         } else {
             HashMap::new()
@@ -97,18 +225,10 @@ impl<R: Read + Seek> Mp4Reader<R> {
 
         // Update tracks if any fragmented (moof) boxes are found.
         if !moofs.is_empty() {
-            let mut default_sample_duration = 0;
-            if let Some(ref moov) = moov {
-                if let Some(ref mvex) = &moov.mvex {
-                    default_sample_duration = mvex.trex.default_sample_duration
-                }
-            }
-
             for (moof, moof_offset) in moofs.iter().zip(moof_offsets) {
                 for traf in moof.trafs.iter() {
                     let track_id = traf.tfhd.track_id;
                     if let Some(track) = tracks.get_mut(&track_id) {
-                        track.default_sample_duration = default_sample_duration;
                         track.moof_offsets.push(moof_offset);
                         track.trafs.push(traf.clone())
                     } else {
@@ -124,8 +244,11 @@ impl<R: Read + Seek> Mp4Reader<R> {
             moov: moov.unwrap(),
             moofs,
             emsgs,
+            meta,
             size,
+            mdat_size,
             tracks,
+            data_end,
         })
     }
 
@@ -138,12 +261,13 @@ impl<R: Read + Seek> Mp4Reader<R> {
 
         let mut moofs = Vec::new();
         let mut moof_offsets = Vec::new();
+        let mut mdat_size = 0;
 
         let mut current = start;
         while current < size {
             // Get box header.
             let header = BoxHeader::read(&mut reader)?;
-            let BoxHeader { name, size: s } = header;
+            let BoxHeader { name, size: s, .. } = header;
             if s > size {
                 return Err(Error::InvalidData(
                     "file contains a box with a larger size than it",
@@ -152,16 +276,21 @@ impl<R: Read + Seek> Mp4Reader<R> {
 
             // Break if size zero BoxHeader, which can result in dead-loop.
             if s == 0 {
+                if name == BoxType::MdatBox {
+                    mdat_size += size - current;
+                    current = size;
+                }
                 break;
             }
 
             // Match and parse the atom boxes.
             match name {
                 BoxType::MdatBox => {
+                    mdat_size += s + header.header_size - HEADER_SIZE;
                     skip_box(&mut reader, s)?;
                 }
                 BoxType::MoofBox => {
-                    let moof_offset = reader.stream_position()? - 8;
+                    let moof_offset = reader.stream_position()? - header.header_size;
                     let moof = MoofBox::read_box(&mut reader, s)?;
                     moofs.push(moof);
                     moof_offsets.push(moof_offset);
@@ -171,7 +300,7 @@ impl<R: Read + Seek> Mp4Reader<R> {
                     skip_box(&mut reader, s)?;
                 }
             }
-            current = reader.stream_position()?;
+            current = next_box_position(&mut reader, current)?;
         }
 
         if moofs.is_empty() {
@@ -183,19 +312,13 @@ impl<R: Read + Seek> Mp4Reader<R> {
             .moov
             .traks
             .iter()
-            .map(|trak| (trak.tkhd.track_id, Mp4Track::from(trak)))
+            .map(|trak| (trak.tkhd.track_id, track_with_trex(trak, &self.moov)))
             .collect();
 
-        let mut default_sample_duration = 0;
-        if let Some(ref mvex) = &self.moov.mvex {
-            default_sample_duration = mvex.trex.default_sample_duration
-        }
-
         for (moof, moof_offset) in moofs.iter().zip(moof_offsets) {
             for traf in moof.trafs.iter() {
                 let track_id = traf.tfhd.track_id;
                 if let Some(track) = tracks.get_mut(&track_id) {
-                    track.default_sample_duration = default_sample_duration;
                     track.moof_offsets.push(moof_offset);
                     track.trafs.push(traf.clone())
                 } else {
@@ -210,8 +333,11 @@ impl<R: Read + Seek> Mp4Reader<R> {
             moov: self.moov.clone(),
             moofs,
             emsgs: Vec::new(),
+            meta: self.meta.clone(),
             tracks,
             size,
+            mdat_size,
+            data_end: None,
         })
     }
 
@@ -219,10 +345,59 @@ impl<R: Read + Seek> Mp4Reader<R> {
         self.size
     }
 
+    /// The bytes of the file that are not media data: every box but `mdat`,
+    /// including the `moof` of each fragment.
+    pub fn overhead_bytes(&self) -> u64 {
+        self.size.saturating_sub(self.mdat_size)
+    }
+
+    /// The bitrate of the media data, in bits per second: the size of the
+    /// file without its [overhead](Mp4Reader::overhead_bytes), over the
+    /// duration of the movie, or of its longest track if the `mvhd` has none
+    /// as in fragmented files. 0 if the duration is unknown.
+    pub fn total_bitrate(&self) -> u32 {
+        let mut duration = self.moov.mvhd.duration as f64 / self.moov.mvhd.timescale as f64;
+        if !duration.is_normal() {
+            duration = self
+                .tracks
+                .values()
+                .map(|track| {
+                    let timing = track.timing_table();
+                    let end = timing
+                        .dts
+                        .iter()
+                        .zip(timing.duration.iter())
+                        .map(|(dts, dur)| dts + *dur as u64)
+                        .max()
+                        .unwrap_or(0);
+                    end as f64 / track.timescale() as f64
+                })
+                .filter(|duration| duration.is_normal())
+                .fold(0.0, f64::max);
+        }
+        if duration == 0.0 {
+            return 0;
+        }
+        ((self.size - self.overhead_bytes()) as f64 * 8.0 / duration) as u32
+    }
+
     pub fn major_brand(&self) -> &FourCC {
         &self.ftyp.major_brand
     }
 
+    pub fn major_brand_is<B: Into<FourCC>>(&self, brand: B) -> bool {
+        self.ftyp.major_brand_is(brand)
+    }
+
+    /// Classify the file from its `ftyp` brands, treating it as fragmented
+    /// when `mvex` or any `moof` is present.
+    pub fn container_kind(&self) -> ContainerKind {
+        match self.ftyp.container_kind() {
+            ContainerKind::Mp4 if self.is_fragmented() => ContainerKind::FragmentedMp4,
+            kind => kind,
+        }
+    }
+
     pub fn minor_version(&self) -> u32 {
         self.ftyp.minor_version
     }
@@ -239,14 +414,43 @@ impl<R: Read + Seek> Mp4Reader<R> {
         self.moov.mvhd.timescale
     }
 
+    /// Whether the movie is fragmented, i.e. `moov` has an `mvex` or any
+    /// `moof` was found.
+    ///
+    /// Samples of fragmented files are read with the same
+    /// [Mp4Reader::read_sample] and [Mp4Reader::sample_count] as progressive
+    /// ones: the samples of the `moof` boxes follow those of the `moov` sample
+    /// table, in file order.
     pub fn is_fragmented(&self) -> bool {
-        !self.moofs.is_empty()
+        self.moov.mvex.is_some() || !self.moofs.is_empty()
     }
 
     pub fn tracks(&self) -> &HashMap<u32, Mp4Track> {
         &self.tracks
     }
 
+    /// The tracks of a type, in the order of their `trak` boxes in the
+    /// `moov`.
+    pub fn tracks_by_type(&self, track_type: TrackType) -> Vec<&Mp4Track> {
+        self.moov
+            .traks
+            .iter()
+            .filter_map(|trak| self.tracks.get(&trak.tkhd.track_id))
+            .filter(|track| track.track_type().ok() == Some(track_type))
+            .collect()
+    }
+
+    /// The first video track, by track order; files may carry more than
+    /// one, such as a thumbnail track, see [Mp4Reader::tracks_by_type].
+    pub fn video_track(&self) -> Option<&Mp4Track> {
+        self.tracks_by_type(TrackType::Video).into_iter().next()
+    }
+
+    /// The first audio track, by track order; see [Mp4Reader::tracks_by_type].
+    pub fn audio_track(&self) -> Option<&Mp4Track> {
+        self.tracks_by_type(TrackType::Audio).into_iter().next()
+    }
+
     pub fn sample_count(&self, track_id: u32) -> Result<u32> {
         if let Some(track) = self.tracks.get(&track_id) {
             Ok(track.sample_count())
@@ -257,12 +461,107 @@ impl<R: Read + Seek> Mp4Reader<R> {
 
     pub fn read_sample(&mut self, track_id: u32, sample_id: u32) -> Result<Option<Mp4Sample>> {
         if let Some(track) = self.tracks.get(&track_id) {
+            if let Some(data_end) = self.data_end {
+                if !track.is_sample_present(sample_id, data_end) {
+                    return Ok(None);
+                }
+            }
             track.read_sample(&mut self.reader, sample_id)
         } else {
             Err(Error::TrakNotFound(track_id))
         }
     }
 
+    /// The samples of all tracks, with their track ids, in the order of
+    /// their data in the file, as a player reading it front to back would
+    /// demux them. Samples of fragments come in fragment order, as their
+    /// data follows the `moof` they are described in.
+    ///
+    /// Samples missing from a truncated file read with
+    /// [Mp4Reader::read_header_recover] are skipped.
+    pub fn sample_iter_all(&mut self) -> impl Iterator<Item = Result<(u32, Mp4Sample)>> + '_ {
+        let (order, error) = match self.samples_in_file_order() {
+            Ok(order) => (order, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+        error
+            .map(Err)
+            .into_iter()
+            .chain(
+                order
+                    .into_iter()
+                    .filter_map(move |(_, track_id, sample_id)| {
+                        self.read_sample(track_id, sample_id)
+                            .map(|sample| sample.map(|sample| (track_id, sample)))
+                            .transpose()
+                    }),
+            )
+    }
+
+    // The offset, track id and sample id of every sample, sorted by offset.
+    fn samples_in_file_order(&self) -> Result<Vec<(u64, u32, u32)>> {
+        let mut order = Vec::new();
+        for track in self.tracks.values() {
+            for sample_id in 1..=track.sample_count() {
+                order.push((track.sample_offset(sample_id)?, track.track_id(), sample_id));
+            }
+        }
+        order.sort_unstable();
+        Ok(order)
+    }
+
+    /// A sample of a TTML subtitle track, such as one with an `stpp` sample
+    /// entry, as its XML document. The whole sample must be UTF-8, so
+    /// documents followed by images as subsamples are not supported.
+    pub fn read_xml_sample(&mut self, track_id: u32, sample_id: u32) -> Result<Option<String>> {
+        match self.read_sample(track_id, sample_id)? {
+            Some(sample) => String::from_utf8(sample.bytes.to_vec())
+                .map(Some)
+                .map_err(|_| Error::InvalidData("xml sample is not valid UTF-8")),
+            None => Ok(None),
+        }
+    }
+
+    /// The payloads of the samples of a timed metadata track, such as GPMF
+    /// telemetry, with their decoding times.
+    pub fn read_metadata_samples(&mut self, track_id: u32) -> Result<Vec<(Duration, Vec<u8>)>> {
+        let track = self
+            .tracks
+            .get(&track_id)
+            .ok_or(Error::TrakNotFound(track_id))?;
+        if track.handler_type() != HandlerType::Metadata {
+            return Err(Error::InvalidData("not a timed metadata track"));
+        }
+        let timescale = track.timescale();
+        if timescale == 0 {
+            return Err(Error::InvalidData("track timescale is 0"));
+        }
+
+        let mut samples = Vec::new();
+        for sample_id in 1..=track.sample_count() {
+            if let Some(sample) = self.read_sample(track_id, sample_id)? {
+                let time = sample.start_time as u128 * 1_000_000 / timescale as u128;
+                samples.push((Duration::from_micros(time as u64), sample.bytes.to_vec()));
+            }
+        }
+        Ok(samples)
+    }
+
+    /// The id of the first sample of a track whose data extends past the end
+    /// of the file, if any. Only reported for readers opened with
+    /// [Mp4Reader::read_header_recover].
+    pub fn first_truncated_sample(&self, track_id: u32) -> Result<Option<u32>> {
+        let track = match self.tracks.get(&track_id) {
+            Some(track) => track,
+            None => return Err(Error::TrakNotFound(track_id)),
+        };
+        let data_end = match self.data_end {
+            Some(data_end) => data_end,
+            None => return Ok(None),
+        };
+        Ok((1..=track.sample_count()).find(|id| !track.is_sample_present(*id, data_end)))
+    }
+
     pub fn sample_offset(&mut self, track_id: u32, sample_id: u32) -> Result<u64> {
         if let Some(track) = self.tracks.get(&track_id) {
             track.sample_offset(sample_id)
@@ -270,15 +569,126 @@ impl<R: Read + Seek> Mp4Reader<R> {
             Err(Error::TrakNotFound(track_id))
         }
     }
+
+    /// The Common Encryption parameters of a sample, read from the auxiliary
+    /// information its `saiz` and `saio` boxes point to. `iv_size` is the
+    /// per-sample IV size of the track, 0 for a constant IV. Returns `None`
+    /// if the sample has no such information.
+    pub fn sample_encryption(
+        &mut self,
+        track_id: u32,
+        sample_id: u32,
+        iv_size: u8,
+    ) -> Result<Option<SampleEncryption>> {
+        let track = match self.tracks.get(&track_id) {
+            Some(track) => track,
+            None => return Err(Error::TrakNotFound(track_id)),
+        };
+        let (offset, size) = match track.aux_info_location(sample_id)? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+        if offset
+            .checked_add(size as u64)
+            .is_none_or(|end| end > self.size)
+        {
+            return Err(Error::InvalidData(
+                "sample auxiliary information extends past the end of the file",
+            ));
+        }
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut data = vec![0; size as usize];
+        self.reader.read_exact(&mut data)?;
+
+        SampleEncryption::parse(&data, iv_size).map(Some)
+    }
+}
+
+// A track of `moov`, with the fragment sample defaults of its `trex`.
+fn track_with_trex(trak: &TrakBox, moov: &MoovBox) -> Mp4Track {
+    let mut track = Mp4Track::from(trak);
+    let mvex = moov.mvex.as_ref();
+    track.set_trex(mvex.and_then(|mvex| mvex.trex(trak.tkhd.track_id)));
+    track
+}
+
+impl<T: AsRef<[u8]>> Mp4Reader<Cursor<T>> {
+    /// Like [Mp4Reader::read_sample], but the sample data is borrowed from
+    /// the in-memory buffer instead of copied.
+    pub fn read_sample_ref(
+        &self,
+        track_id: u32,
+        sample_id: u32,
+    ) -> Result<Option<Mp4SampleRef<'_>>> {
+        if let Some(track) = self.tracks.get(&track_id) {
+            if let Some(data_end) = self.data_end {
+                if !track.is_sample_present(sample_id, data_end) {
+                    return Ok(None);
+                }
+            }
+            track.read_sample_ref(self.reader.get_ref().as_ref(), sample_id)
+        } else {
+            Err(Error::TrakNotFound(track_id))
+        }
+    }
 }
 
 impl<R> Mp4Reader<R> {
+    /// The movie's metadata, from the iTunes-style `moov/udta/meta` box or,
+    /// for fields it lacks, the QuickTime-style `moov/meta` box.
     pub fn metadata(&self) -> impl Metadata<'_> {
-        self.moov.udta.as_ref().and_then(|udta| {
-            udta.meta.as_ref().and_then(|meta| match meta {
-                MetaBox::Mdir { ilst } => ilst.as_ref(),
-                _ => None,
-            })
-        })
+        MovieMetadata {
+            udta: self.moov.udta.as_ref().and_then(|udta| udta.meta.as_ref()),
+            moov: self.moov.meta.as_ref(),
+        }
+    }
+
+    /// The language and text of the copyright notice in `moov/udta/cprt`.
+    pub fn copyright(&self) -> Option<(String, String)> {
+        let cprt = self.moov.udta.as_ref()?.cprt.as_ref()?;
+        Some((cprt.language_string(), cprt.notice.clone()))
+    }
+
+    /// Where the movie was recorded, from the `©xyz` item of an iTunes-style
+    /// `meta` or the `com.apple.quicktime.location.ISO6709` item iOS writes.
+    pub fn location(&self) -> Option<GeoLocation> {
+        self.metadata().location()
+    }
+}
+
+struct MovieMetadata<'a> {
+    udta: Option<&'a MetaBox>,
+    moov: Option<&'a MetaBox>,
+}
+
+impl<'a> Metadata<'a> for MovieMetadata<'a> {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.udta.title().or_else(|| self.moov.title())
+    }
+
+    fn year(&self) -> Option<u32> {
+        self.udta.year().or_else(|| self.moov.year())
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        self.udta.poster().or_else(|| self.moov.poster())
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        self.udta.summary().or_else(|| self.moov.summary())
+    }
+
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        let cover_art = self.udta.cover_art();
+        if cover_art.is_empty() {
+            self.moov.cover_art()
+        } else {
+            cover_art
+        }
+    }
+
+    fn location(&self) -> Option<GeoLocation> {
+        self.udta.location().or_else(|| self.moov.location())
     }
 }
@@ -0,0 +1,104 @@
+//! Best-effort recovery of damaged or truncated files.
+//!
+//! See [Mp4Reader::read_header_recover](crate::Mp4Reader::read_header_recover)
+//! for reading the samples that are still present in a truncated file.
+
+use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::mp4box::*;
+use crate::*;
+
+const SCAN_BLOCK_SIZE: usize = 1 << 20;
+
+/// Scan `reader` byte by byte between `start` and `size` for complete `moof`
+/// boxes, ignoring the top-level box structure.
+///
+/// A candidate is accepted when its `moof` type is followed by an `mfhd` box
+/// and it parses in full. Returns the parsed boxes with the offsets of their
+/// headers.
+pub fn scan_moofs<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    size: u64,
+) -> Result<Vec<(u64, MoofBox)>> {
+    let mut moofs = Vec::new();
+    let mut block = vec![0u8; SCAN_BLOCK_SIZE];
+
+    let mut pos = start;
+    while pos + 16 <= size {
+        reader.seek(SeekFrom::Start(pos))?;
+        let len = std::cmp::min(SCAN_BLOCK_SIZE as u64, size - pos) as usize;
+        reader.read_exact(&mut block[..len])?;
+
+        let mut moof_end = None;
+        let mut i = 0;
+        while i + 16 <= len {
+            if &block[i + 4..i + 8] == b"moof" && &block[i + 12..i + 16] == b"mfhd" {
+                let box_start = pos + i as u64;
+                let s = u32::from_be_bytes(block[i..i + 4].try_into().unwrap()) as u64;
+                if s >= HEADER_SIZE + 16 && box_start + s <= size {
+                    reader.seek(SeekFrom::Start(box_start + HEADER_SIZE))?;
+                    if let Ok(moof) = MoofBox::read_box(reader, s) {
+                        moofs.push((box_start, moof));
+                        moof_end = Some(box_start + s);
+                        break;
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        // Resume after an accepted moof, or re-examine the bytes at the end
+        // of this block that could start a header spanning into the next.
+        pos = moof_end.unwrap_or(pos + i as u64);
+    }
+
+    Ok(moofs)
+}
+
+/// Build a minimal `moov` for a fragmented file whose own `moov` is missing,
+/// from the `moof` boxes found by [scan_moofs].
+///
+/// The result has one `trak` and one `trex` per track id referenced by a
+/// `traf`. Sample descriptions and timescales cannot be recovered, so the
+/// tracks only give access to sample data and timing in their own units.
+pub fn rebuild_moov<R: Read + Seek>(reader: &mut R, size: u64) -> Result<MoovBox> {
+    let moofs = scan_moofs(reader, 0, size)?;
+    if moofs.is_empty() {
+        return Err(Error::BoxNotFound(BoxType::MoofBox));
+    }
+    Ok(moov_from_moofs(moofs.iter().map(|(_, moof)| moof)))
+}
+
+pub(crate) fn moov_from_moofs<'a, I: Iterator<Item = &'a MoofBox>>(moofs: I) -> MoovBox {
+    let mut track_ids: Vec<u32> = Vec::new();
+    for traf in moofs.flat_map(|moof| moof.trafs.iter()) {
+        if !track_ids.contains(&traf.tfhd.track_id) {
+            track_ids.push(traf.tfhd.track_id);
+        }
+    }
+    track_ids.sort_unstable();
+
+    let mut moov = MoovBox::default();
+    let mut mvex = MvexBox::default();
+    for track_id in track_ids {
+        let mut trak = TrakBox::default();
+        trak.tkhd.track_id = track_id;
+        trak.mdia.minf.stbl.stco = Some(StcoBox::default());
+        moov.traks.push(trak);
+        mvex.trexs.push(TrexBox {
+            track_id,
+            default_sample_description_index: 1,
+            ..Default::default()
+        });
+    }
+    moov.mvhd.next_track_id = moov
+        .traks
+        .iter()
+        .map(|t| t.tkhd.track_id + 1)
+        .max()
+        .unwrap_or(1);
+    moov.mvex = Some(mvex);
+    moov
+}
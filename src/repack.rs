@@ -0,0 +1,303 @@
+//! Rewriting files into a canonical box layout, or editing them in place.
+
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+
+use crate::mp4box::*;
+use crate::*;
+
+/// Re-emit the file in `input` with `ftyp` first and `moov` right after it,
+/// so it can be played before it is fully downloaded ("faststart").
+///
+/// The `moov` is re-serialized, which writes its children in the crate's
+/// canonical order. Children of the `moov` and of its `trak`s that the crate
+/// does not parse, such as `iods`, `tref` or vendor boxes, follow the ones it
+/// writes, in their order in the input. Every
+/// other top-level box, including `mdat`, `free` and unknown or vendor boxes,
+/// is copied byte for byte after the `moov`, keeping its order in the input.
+/// Chunk offsets are moved along with the data they point to, switching a
+/// track from `stco` to `co64` if they no longer fit in 32 bits, and so are
+/// explicit `tfhd` base data offsets of `moof` boxes.
+///
+/// Only the first `ftyp` and `moov` are used; later ones are kept as if they
+/// were unknown boxes.
+pub fn normalize<R: Read + Seek, W: Write>(input: &mut R, size: u64, output: &mut W) -> Result<()> {
+    let mut ftyp = None;
+    let mut moov = None;
+    let mut others = Vec::new();
+
+    for b in child_boxes(input, 0, size)? {
+        input.seek(SeekFrom::Start(b.body.start))?;
+        match b.name {
+            BoxType::FtypBox if ftyp.is_none() => {
+                ftyp = Some(FtypBox::read_box(input, b.read_size())?)
+            }
+            BoxType::MoovBox if moov.is_none() => {
+                let moov_box = MoovBox::read_box(input, b.read_size())?;
+                let unparsed = UnparsedChildren::read(input, &b, &moov_box)?;
+                moov = Some((moov_box, unparsed))
+            }
+            _ => others.push(b),
+        }
+    }
+
+    let ftyp = ftyp.ok_or(Error::BoxNotFound(BoxType::FtypBox))?;
+    let (mut moov, unparsed) = moov.ok_or(Error::BoxNotFound(BoxType::MoovBox))?;
+
+    let chunk_offsets: Vec<Vec<u64>> = moov
+        .traks
+        .iter()
+        .map(|trak| trak.mdia.minf.stbl.chunk_offsets())
+        .collect();
+
+    // Moving a track to co64 grows the moov, so lay it out until every
+    // offset fits the box it is written to.
+    let new_starts = loop {
+        let mut pos = ftyp.box_size() + unparsed.write_moov(&moov)?.len() as u64;
+        let new_starts: Vec<u64> = others
+            .iter()
+            .map(|b| {
+                let start = pos;
+                pos += b.size();
+                start
+            })
+            .collect();
+
+        let mut grown = false;
+        for (trak, offsets) in moov.traks.iter_mut().zip(&chunk_offsets) {
+            let offsets = offsets
+                .iter()
+                .map(|&o| rebase(&others, &new_starts, o))
+                .collect::<Result<Vec<u64>>>()?;
+            grown |= trak.mdia.minf.stbl.set_chunk_offsets(offsets);
+        }
+        if !grown {
+            break new_starts;
+        }
+    };
+
+    write_box_checked(&ftyp, output)?;
+    output.write_all(&unparsed.write_moov(&moov)?)?;
+
+    for b in &others {
+        if b.name == BoxType::MoofBox {
+            input.seek(SeekFrom::Start(b.body.start))?;
+            let mut moof = MoofBox::read_box(input, b.read_size())?;
+            if moof
+                .trafs
+                .iter()
+                .any(|traf| traf.tfhd.base_data_offset.is_some())
+            {
+                for traf in moof.trafs.iter_mut() {
+                    if let Some(base) = traf.tfhd.base_data_offset {
+                        traf.tfhd.base_data_offset = Some(rebase(&others, &new_starts, base)?);
+                    }
+                }
+                // The layout above assumes every box keeps its size.
+                if moof.box_size() != b.size() {
+                    return Err(Error::InvalidData("moof changes size when rewritten"));
+                }
+                write_box_checked(&moof, output)?;
+                continue;
+            }
+        }
+        input.seek(SeekFrom::Start(b.start))?;
+        io::copy(&mut (&mut *input).take(b.size()), output)?;
+    }
+
+    Ok(())
+}
+
+/// Edit the `tkhd` of a track in `file` in place, such as its
+/// [enabled](TkhdBox::set_enabled) flag or its `alternate_group`, without
+/// rewriting the rest of the file.
+///
+/// `update` must not change the size of the box, by changing its version.
+pub fn update_track_header<F, U>(file: &mut F, size: u64, track_id: u32, update: U) -> Result<()>
+where
+    F: Read + Write + Seek,
+    U: FnOnce(&mut TkhdBox),
+{
+    let moov = child_boxes(file, 0, size)?
+        .into_iter()
+        .find(|b| b.name == BoxType::MoovBox)
+        .ok_or(Error::BoxNotFound(BoxType::MoovBox))?;
+    for trak in child_boxes(file, moov.body.start, moov.body.end)? {
+        if trak.name != BoxType::TrakBox {
+            continue;
+        }
+        let tkhd = child_boxes(file, trak.body.start, trak.body.end)?
+            .into_iter()
+            .find(|b| b.name == BoxType::TkhdBox)
+            .ok_or(Error::BoxNotFound(BoxType::TkhdBox))?;
+        file.seek(SeekFrom::Start(tkhd.body.start))?;
+        let mut tkhd_box = TkhdBox::read_box(file, tkhd.read_size())?;
+        if tkhd_box.track_id != track_id {
+            continue;
+        }
+
+        update(&mut tkhd_box);
+        if tkhd_box.box_size() != tkhd.read_size() {
+            return Err(Error::InvalidData("tkhd changes size when rewritten"));
+        }
+        let mut buf = Vec::new();
+        write_box_checked(&tkhd_box, &mut buf)?;
+        file.seek(SeekFrom::Start(tkhd.body.start))?;
+        file.write_all(&buf[HEADER_SIZE as usize..])?;
+        return Ok(());
+    }
+    Err(Error::TrakNotFound(track_id))
+}
+
+/// Edit the `moov` of `file` in place, such as its `udta` metadata, without
+/// rewriting the rest of the file.
+///
+/// The `moov` may grow into a `free` box right after it, as reserved by
+/// [Mp4WriterConfig::reserved_free_space], and gives the room back to it
+/// when it shrinks. It must not grow past that space, nor shrink by less
+/// than the size of a box header if there is no `free` box to absorb the
+/// difference, unless it is the last box of the file apart from that `free`
+/// box: then it may grow past the end of the file. Nothing else moves, so
+/// chunk offsets stay valid without being updated.
+///
+/// Children of the `moov` and of its `trak`s that the crate does not parse,
+/// such as `iods`, `tref` or vendor boxes, are kept as they are after the ones
+/// it writes.
+pub fn update_moov<F, U>(file: &mut F, size: u64, update: U) -> Result<()>
+where
+    F: Read + Write + Seek,
+    U: FnOnce(&mut MoovBox),
+{
+    let boxes = child_boxes(file, 0, size)?;
+    let index = boxes
+        .iter()
+        .position(|b| b.name == BoxType::MoovBox)
+        .ok_or(Error::BoxNotFound(BoxType::MoovBox))?;
+    let moov = &boxes[index];
+    let mut room = moov.size();
+    let mut next = index + 1;
+    if let Some(free) = boxes.get(next).filter(|b| b.name == BoxType::FreeBox) {
+        room += free.size();
+        next += 1;
+    }
+    let is_last = next == boxes.len();
+
+    file.seek(SeekFrom::Start(moov.body.start))?;
+    let mut moov_box = MoovBox::read_box(file, moov.read_size())?;
+    let unparsed = UnparsedChildren::read(file, moov, &moov_box)?;
+    update(&mut moov_box);
+    let buf = unparsed.write_moov(&moov_box)?;
+
+    let free_size = match room.checked_sub(buf.len() as u64) {
+        Some(free_size) if free_size == 0 || free_size >= HEADER_SIZE => free_size,
+        // At the end of the file, an empty free box may run past the room.
+        Some(_) if is_last => HEADER_SIZE,
+        None if is_last => 0,
+        _ => return Err(Error::InvalidData("moov does not fit in place")),
+    };
+    file.seek(SeekFrom::Start(moov.start))?;
+    file.write_all(&buf)?;
+    if free_size > 0 {
+        let mut header = Vec::new();
+        BoxHeader::new(BoxType::FreeBox, free_size).write(&mut header)?;
+        file.write_all(&header)?;
+    }
+    Ok(())
+}
+
+// The children of a moov, and of each of its traks by track id, that MoovBox
+// and TrakBox do not write back, as they are in the file.
+#[derive(Default)]
+struct UnparsedChildren {
+    moov: Vec<u8>,
+    traks: HashMap<u32, Vec<u8>>,
+}
+
+impl UnparsedChildren {
+    // The unparsed children of `moov`, which `moov_box` was read from.
+    fn read<R: Read + Seek>(reader: &mut R, moov: &ChildBox, moov_box: &MoovBox) -> Result<Self> {
+        let mut unparsed = Self::default();
+        let mut traks = moov_box.traks.iter();
+        for child in child_boxes(reader, moov.body.start, moov.body.end)? {
+            match child.name {
+                BoxType::TrakBox => {
+                    let mut data = Vec::new();
+                    for trak_child in child_boxes(reader, child.body.start, child.body.end)? {
+                        match trak_child.name {
+                            BoxType::TkhdBox
+                            | BoxType::TaptBox
+                            | BoxType::EdtsBox
+                            | BoxType::MdiaBox => {}
+                            _ => copy_box(reader, &trak_child, &mut data)?,
+                        }
+                    }
+                    if let Some(trak) = traks.next() {
+                        unparsed.traks.insert(trak.tkhd.track_id, data);
+                    }
+                }
+                BoxType::MvhdBox | BoxType::MetaBox | BoxType::MvexBox | BoxType::UdtaBox => {}
+                _ => copy_box(reader, &child, &mut unparsed.moov)?,
+            }
+        }
+        Ok(unparsed)
+    }
+
+    // `moov` serialized with the unparsed children after the ones it writes,
+    // in it and in each of its traks.
+    fn write_moov(&self, moov: &MoovBox) -> Result<Vec<u8>> {
+        let mut written = Vec::new();
+        write_box_checked(moov, &mut written)?;
+        let mut reader = Cursor::new(&written);
+        let moov_child = &child_boxes(&mut reader, 0, written.len() as u64)?[0];
+
+        let mut body = Vec::new();
+        let mut traks = moov.traks.iter();
+        for child in child_boxes(&mut reader, moov_child.body.start, moov_child.body.end)? {
+            let extra = match child.name {
+                BoxType::TrakBox => traks
+                    .next()
+                    .and_then(|trak| self.traks.get(&trak.tkhd.track_id))
+                    .filter(|extra| !extra.is_empty()),
+                _ => None,
+            };
+            match extra {
+                Some(extra) => {
+                    BoxHeader::new(BoxType::TrakBox, child.read_size() + extra.len() as u64)
+                        .write(&mut body)?;
+                    body.extend_from_slice(
+                        &written[child.body.start as usize..child.body.end as usize],
+                    );
+                    body.extend_from_slice(extra);
+                }
+                None => {
+                    body.extend_from_slice(&written[child.start as usize..child.body.end as usize])
+                }
+            }
+        }
+        body.extend_from_slice(&self.moov);
+
+        let mut buf = Vec::new();
+        BoxHeader::new(BoxType::MoovBox, HEADER_SIZE + body.len() as u64).write(&mut buf)?;
+        buf.extend_from_slice(&body);
+        Ok(buf)
+    }
+}
+
+fn copy_box<R: Read + Seek>(reader: &mut R, b: &ChildBox, data: &mut Vec<u8>) -> Result<()> {
+    reader.seek(SeekFrom::Start(b.start))?;
+    (&mut *reader).take(b.size()).read_to_end(data)?;
+    Ok(())
+}
+
+// The output offset of the input byte at `offset`, which must lie within one
+// of the boxes copied after the moov.
+fn rebase(others: &[ChildBox], new_starts: &[u64], offset: u64) -> Result<u64> {
+    others
+        .iter()
+        .zip(new_starts)
+        .find(|(b, _)| b.start <= offset && offset < b.body.end)
+        .map(|(b, &new_start)| new_start + offset - b.start)
+        .ok_or(Error::InvalidData(
+            "data offset outside of the copied boxes",
+        ))
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::io::{self, Cursor, Read};
+
+use crate::*;
+
+/// Reads the samples of a faststart file, whose `moov` comes before its
+/// `mdat`, in a single forward pass over a reader that cannot seek, such as
+/// a pipe.
+///
+/// The stream must start at the beginning of the file. Samples are returned
+/// in the order of their offsets in the file, interleaving tracks as they
+/// are stored; the gaps between them are read and discarded.
+#[derive(Debug)]
+pub struct Mp4StreamReader<R> {
+    reader: R,
+    pub ftyp: FtypBox,
+    pub moov: MoovBox,
+
+    tracks: HashMap<u32, Mp4Track>,
+    // Track id, sample id, offset and size of every sample, in offset order.
+    samples: Vec<(u32, u32, u64, u32)>,
+    next: usize,
+    // Bytes consumed from the reader.
+    position: u64,
+}
+
+impl<R: Read> Mp4StreamReader<R> {
+    /// Read the boxes up to and including the `moov`.
+    ///
+    /// Fails if an `mdat` comes first, or if the file is fragmented, as the
+    /// samples could not then be read without seeking.
+    pub fn read_header(mut reader: R) -> Result<Self> {
+        let mut ftyp = None;
+        let mut position = 0;
+
+        let moov = loop {
+            let BoxHeader {
+                name,
+                size: s,
+                header_size,
+                ..
+            } = BoxHeader::read(&mut reader)?;
+            if s == 0 {
+                return Err(Error::BoxNotFound(BoxType::MoovBox));
+            }
+            if s < HEADER_SIZE {
+                return Err(Error::InvalidData("box size smaller than its header"));
+            }
+
+            match name {
+                BoxType::FtypBox | BoxType::MoovBox => {
+                    // The box readers expect to be positioned after an 8-byte
+                    // header. The buffer grows with the bytes actually read, so
+                    // a bogus size cannot allocate more than the stream holds.
+                    let mut buf = vec![0u8; HEADER_SIZE as usize];
+                    (&mut reader).take(s - HEADER_SIZE).read_to_end(&mut buf)?;
+                    if (buf.len() as u64) < s {
+                        return Err(Error::IoError(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                    let mut cursor = Cursor::new(buf);
+                    cursor.set_position(HEADER_SIZE);
+                    if name == BoxType::FtypBox {
+                        ftyp = Some(FtypBox::read_box(&mut cursor, s)?);
+                    } else {
+                        position += header_size + s - HEADER_SIZE;
+                        break MoovBox::read_box(&mut cursor, s)?;
+                    }
+                }
+                BoxType::MdatBox => {
+                    return Err(Error::InvalidData(
+                        "mdat comes before moov, the file is not faststart",
+                    ));
+                }
+                _ => skip_forward(&mut reader, s - HEADER_SIZE)?,
+            }
+            position += header_size + s - HEADER_SIZE;
+        };
+
+        let ftyp = ftyp.ok_or(Error::BoxNotFound(BoxType::FtypBox))?;
+        if moov.mvex.is_some() {
+            return Err(Error::InvalidData(
+                "fragmented files cannot be read forward only",
+            ));
+        }
+        if moov.traks.iter().any(|trak| trak.tkhd.track_id == 0) {
+            return Err(Error::InvalidData("illegal track id 0"));
+        }
+        let tracks: HashMap<u32, Mp4Track> = moov
+            .traks
+            .iter()
+            .map(|trak| (trak.tkhd.track_id, Mp4Track::from(trak)))
+            .collect();
+
+        let mut samples = Vec::new();
+        for (&track_id, track) in tracks.iter() {
+            for sample_id in 1..=track.sample_count() {
+                if let Some((offset, size)) = track.sample_location(sample_id)? {
+                    samples.push((track_id, sample_id, offset, size));
+                }
+            }
+        }
+        samples.sort_by_key(|&(track_id, sample_id, offset, _)| (offset, track_id, sample_id));
+
+        Ok(Mp4StreamReader {
+            reader,
+            ftyp,
+            moov,
+            tracks,
+            samples,
+            next: 0,
+            position,
+        })
+    }
+
+    /// The next sample in file order and the id of its track, or `None` once
+    /// every sample has been read.
+    pub fn next_sample(&mut self) -> Result<Option<(u32, Mp4Sample)>> {
+        let (track_id, sample_id, offset, size) = match self.samples.get(self.next) {
+            Some(&sample) => sample,
+            None => return Ok(None),
+        };
+        if offset < self.position {
+            return Err(Error::InvalidData("sample data overlaps data already read"));
+        }
+        skip_forward(&mut self.reader, offset - self.position)?;
+
+        let mut buffer = vec![0x0u8; size as usize];
+        self.reader.read_exact(&mut buffer)?;
+        self.position = offset + size as u64;
+        self.next += 1;
+
+        let track = &self.tracks[&track_id];
+        Ok(Some((
+            track_id,
+            track.sample_with_bytes(sample_id, Bytes::from(buffer))?,
+        )))
+    }
+
+    pub fn tracks(&self) -> &HashMap<u32, Mp4Track> {
+        &self.tracks
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn skip_forward<R: Read>(reader: &mut R, size: u64) -> Result<()> {
+    let skipped = io::copy(&mut reader.take(size), &mut io::sink())?;
+    if skipped < size {
+        return Err(Error::IoError(io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(())
+}
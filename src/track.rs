@@ -8,18 +8,58 @@ use crate::mp4box::traf::TrafBox;
 use crate::mp4box::trak::TrakBox;
 use crate::mp4box::trun::TrunBox;
 use crate::mp4box::{
-    avc1::Avc1Box, co64::Co64Box, ctts::CttsBox, ctts::CttsEntry, hev1::Hev1Box, mp4a::Mp4aBox,
-    smhd::SmhdBox, stco::StcoBox, stsc::StscEntry, stss::StssBox, stts::SttsEntry, tx3g::Tx3gBox,
-    vmhd::VmhdBox, vp09::Vp09Box,
+    avc1::Avc1Box, co64::Co64Box, ctts::CttsBox, ctts::CttsEntry, elst::ElstEntry, hev1::Hev1Box,
+    mp4a::Mp4aBox, pcm::PcmBox, smhd::SmhdBox, stco::StcoBox, stsc::StscEntry, stss::StssBox,
+    stts::SttsEntry, tx3g::Tx3gBox, vmhd::VmhdBox, vp09::Vp09Box,
 };
 use crate::*;
 
+// The sample_is_non_sync_sample bit of trex, tfhd and trun sample flags.
+const SAMPLE_IS_NON_SYNC_SAMPLE: u32 = 0x00010000;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TrackConfig {
     pub track_type: TrackType,
     pub timescale: u32,
     pub language: String,
     pub media_conf: MediaConfig,
+
+    /// A BCP 47 language tag written to an `elng` box, for languages the
+    /// ISO 639-2/T code of `language` cannot tell apart, such as `pt-BR`.
+    pub extended_language: Option<String>,
+
+    /// Tracks sharing a non-zero alternate group, such as the audio of a
+    /// movie in several languages, are alternatives to one another.
+    pub alternate_group: u16,
+
+    /// Whether the track plays by default. It is written as the enabled flag
+    /// of the `tkhd`, so only one track of an alternate group should set it.
+    pub is_default: bool,
+
+    /// The z-order of the track among overlapping video tracks; lower
+    /// layers are drawn in front.
+    pub layer: i16,
+
+    /// The relative volume of an audio track, where 1.0 is full volume; 0
+    /// for other tracks.
+    pub volume: FixedPointU8,
+
+    /// The transformation applied to the decoded video, such as a rotation.
+    pub matrix: Matrix,
+
+    /// Priming samples at the start of the media, such as the encoder delay
+    /// of AAC, in media timescale units. When non-zero, the writer adds an
+    /// edit list whose single edit starts presentation after them.
+    pub encoder_delay: u32,
+
+    /// The stereo balance written to the `smhd` of an audio track, from -1.0
+    /// (full left) to 1.0 (full right); 0 is centered.
+    pub balance: FixedPointI8,
+
+    /// The transfer mode written to the `vmhd` of a video track, 0 for copy,
+    /// and the color some of the modes use.
+    pub graphics_mode: u16,
+    pub op_color: RgbColor,
 }
 
 impl From<MediaConfig> for TrackConfig {
@@ -30,65 +70,93 @@ impl From<MediaConfig> for TrackConfig {
             MediaConfig::AacConfig(aac_conf) => Self::from(aac_conf),
             MediaConfig::TtxtConfig(ttxt_conf) => Self::from(ttxt_conf),
             MediaConfig::Vp9Config(vp9_config) => Self::from(vp9_config),
+            MediaConfig::PcmConfig(pcm_conf) => Self::from(pcm_conf),
+            MediaConfig::Ac4Config(ac4_conf) => Self::from(ac4_conf),
+            MediaConfig::StppConfig(stpp_conf) => Self::from(stpp_conf),
         }
     }
 }
 
-impl From<AvcConfig> for TrackConfig {
-    fn from(avc_conf: AvcConfig) -> Self {
+impl TrackConfig {
+    // The defaults of a track of `track_type`, which the From impls of the
+    // media configs build on.
+    fn with_defaults(track_type: TrackType, media_conf: MediaConfig) -> Self {
         Self {
-            track_type: TrackType::Video,
+            track_type,
             timescale: 1000,               // XXX
             language: String::from("und"), // XXX
-            media_conf: MediaConfig::AvcConfig(avc_conf),
+            media_conf,
+            extended_language: None,
+            alternate_group: 0,
+            is_default: true,
+            layer: 0,
+            volume: FixedPointU8::new(0),
+            matrix: Matrix::default(),
+            encoder_delay: 0,
+            balance: FixedPointI8::new(0),
+            graphics_mode: 0,
+            op_color: RgbColor::default(),
         }
     }
 }
 
+impl From<AvcConfig> for TrackConfig {
+    fn from(avc_conf: AvcConfig) -> Self {
+        Self::with_defaults(TrackType::Video, MediaConfig::AvcConfig(avc_conf))
+    }
+}
+
 impl From<HevcConfig> for TrackConfig {
     fn from(hevc_conf: HevcConfig) -> Self {
-        Self {
-            track_type: TrackType::Video,
-            timescale: 1000,               // XXX
-            language: String::from("und"), // XXX
-            media_conf: MediaConfig::HevcConfig(hevc_conf),
-        }
+        Self::with_defaults(TrackType::Video, MediaConfig::HevcConfig(hevc_conf))
     }
 }
 
 impl From<AacConfig> for TrackConfig {
     fn from(aac_conf: AacConfig) -> Self {
         Self {
-            track_type: TrackType::Audio,
-            timescale: 1000,               // XXX
-            language: String::from("und"), // XXX
-            media_conf: MediaConfig::AacConfig(aac_conf),
+            volume: FixedPointU8::new(1),
+            ..Self::with_defaults(TrackType::Audio, MediaConfig::AacConfig(aac_conf))
         }
     }
 }
 
-impl From<TtxtConfig> for TrackConfig {
-    fn from(txtt_conf: TtxtConfig) -> Self {
+impl From<PcmConfig> for TrackConfig {
+    fn from(pcm_conf: PcmConfig) -> Self {
         Self {
-            track_type: TrackType::Subtitle,
-            timescale: 1000,               // XXX
-            language: String::from("und"), // XXX
-            media_conf: MediaConfig::TtxtConfig(txtt_conf),
+            volume: FixedPointU8::new(1),
+            ..Self::with_defaults(TrackType::Audio, MediaConfig::PcmConfig(pcm_conf))
         }
     }
 }
 
-impl From<Vp9Config> for TrackConfig {
-    fn from(vp9_conf: Vp9Config) -> Self {
+impl From<Ac4Config> for TrackConfig {
+    fn from(ac4_conf: Ac4Config) -> Self {
         Self {
-            track_type: TrackType::Video,
-            timescale: 1000,               // XXX
-            language: String::from("und"), // XXX
-            media_conf: MediaConfig::Vp9Config(vp9_conf),
+            volume: FixedPointU8::new(1),
+            ..Self::with_defaults(TrackType::Audio, MediaConfig::Ac4Config(ac4_conf))
         }
     }
 }
 
+impl From<TtxtConfig> for TrackConfig {
+    fn from(txtt_conf: TtxtConfig) -> Self {
+        Self::with_defaults(TrackType::Subtitle, MediaConfig::TtxtConfig(txtt_conf))
+    }
+}
+
+impl From<StppConfig> for TrackConfig {
+    fn from(stpp_conf: StppConfig) -> Self {
+        Self::with_defaults(TrackType::Subtitle, MediaConfig::StppConfig(stpp_conf))
+    }
+}
+
+impl From<Vp9Config> for TrackConfig {
+    fn from(vp9_conf: Vp9Config) -> Self {
+        Self::with_defaults(TrackType::Video, MediaConfig::Vp9Config(vp9_conf))
+    }
+}
+
 #[derive(Debug)]
 pub struct Mp4Track {
     pub trak: TrakBox,
@@ -96,24 +164,103 @@ pub struct Mp4Track {
     pub moof_offsets: Vec<u64>,
 
     // Fragmented Tracks Defaults.
-    pub default_sample_duration: u32,
+    trex: Option<TrexBox>,
+
+    // The entries of the stsd in file order. Tracks are only handed out by
+    // shared reference, so these cannot drift from `trak`.
+    sample_entries: Vec<SampleEntry>,
 }
 
 impl Mp4Track {
     pub(crate) fn from(trak: &TrakBox) -> Self {
-        let trak = trak.clone();
         Self {
-            trak,
+            trak: trak.clone(),
             trafs: Vec::new(),
             moof_offsets: Vec::new(),
-            default_sample_duration: 0,
+            trex: None,
+            sample_entries: trak.mdia.minf.stbl.stsd.entries(),
         }
     }
 
+    // Use the defaults of the track's `mvex/trex` for fragment samples whose
+    // `tfhd` and `trun` leave values out.
+    pub(crate) fn set_trex(&mut self, trex: Option<&TrexBox>) {
+        self.trex = trex.cloned();
+    }
+
+    /// The fragment sample defaults of the track, from the `trex` of the
+    /// `moov/mvex`.
+    pub fn trex(&self) -> Option<&TrexBox> {
+        self.trex.as_ref()
+    }
+
     pub fn track_id(&self) -> u32 {
         self.trak.tkhd.track_id
     }
 
+    /// Whether the track is enabled for playback; see
+    /// [repack::update_track_header](crate::repack::update_track_header) to
+    /// change it.
+    pub fn is_enabled(&self) -> bool {
+        self.trak.tkhd.is_enabled()
+    }
+
+    /// The group of tracks this one is an alternative to, such as other
+    /// languages of the same audio; 0 if there are none.
+    pub fn alternate_group(&self) -> u16 {
+        self.trak.tkhd.alternate_group
+    }
+
+    /// The z-order of the track among overlapping video tracks.
+    pub fn layer(&self) -> i16 {
+        self.trak.tkhd.layer as i16
+    }
+
+    /// The relative volume of an audio track, where 1.0 is full volume.
+    pub fn volume(&self) -> FixedPointU8 {
+        self.trak.tkhd.volume
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.trak.tkhd.matrix
+    }
+
+    /// The 1-based index of the sample description of a sample, from the
+    /// `stsc` of its chunk or, for a fragment sample, the `tfhd` of its
+    /// `traf` or else the `trex` of the track.
+    pub fn sample_description_index(&self, sample_id: u32) -> Result<u32> {
+        if self.is_fragment_sample(sample_id) {
+            let (traf_idx, _) = self
+                .find_traf_idx_and_sample_idx(sample_id)
+                .ok_or(Error::BoxInTrafNotFound(self.track_id(), BoxType::TrafBox))?;
+            self.trafs[traf_idx]
+                .tfhd
+                .sample_description_index
+                .or(self
+                    .trex
+                    .as_ref()
+                    .map(|trex| trex.default_sample_description_index))
+                .ok_or(Error::BoxNotFound(BoxType::TrexBox))
+        } else {
+            let stsc_index = self.stsc_index(sample_id)?;
+            Ok(self.trak.mdia.minf.stbl.stsc.entries[stsc_index].sample_description_index)
+        }
+    }
+
+    /// Sample description by its 1-based `sample_description_index`.
+    pub fn sample_description(&self, index: u16) -> Option<&SampleEntry> {
+        let index = index.checked_sub(1)?;
+        self.sample_entries.get(index as usize)
+    }
+
+    pub fn sample_descriptions(&self) -> &[SampleEntry] {
+        &self.sample_entries
+    }
+
+    pub fn handler_type(&self) -> HandlerType {
+        HandlerType::from_fourcc(self.trak.mdia.hdlr.handler_type)
+    }
+
     pub fn track_type(&self) -> Result<TrackType> {
         TrackType::try_from(&self.trak.mdia.hdlr.handler_type)
     }
@@ -127,8 +274,14 @@ impl Mp4Track {
             Ok(MediaType::VP9)
         } else if self.trak.mdia.minf.stbl.stsd.mp4a.is_some() {
             Ok(MediaType::AAC)
+        } else if self.trak.mdia.minf.stbl.stsd.pcm.is_some() {
+            Ok(MediaType::PCM)
+        } else if self.trak.mdia.minf.stbl.stsd.ac4.is_some() {
+            Ok(MediaType::AC4)
         } else if self.trak.mdia.minf.stbl.stsd.tx3g.is_some() {
             Ok(MediaType::TTXT)
+        } else if self.trak.mdia.minf.stbl.stsd.stpp.is_some() {
+            Ok(MediaType::STPP)
         } else {
             Err(Error::InvalidData("unsupported media type"))
         }
@@ -137,19 +290,52 @@ impl Mp4Track {
     pub fn box_type(&self) -> Result<FourCC> {
         if self.trak.mdia.minf.stbl.stsd.avc1.is_some() {
             Ok(FourCC::from(BoxType::Avc1Box))
-        } else if self.trak.mdia.minf.stbl.stsd.hev1.is_some() {
-            Ok(FourCC::from(BoxType::Hev1Box))
+        } else if let Some(ref hev1) = self.trak.mdia.minf.stbl.stsd.hev1 {
+            Ok(FourCC::from(hev1.box_type()))
         } else if self.trak.mdia.minf.stbl.stsd.vp09.is_some() {
             Ok(FourCC::from(BoxType::Vp09Box))
         } else if self.trak.mdia.minf.stbl.stsd.mp4a.is_some() {
             Ok(FourCC::from(BoxType::Mp4aBox))
+        } else if let Some(ref pcm) = self.trak.mdia.minf.stbl.stsd.pcm {
+            Ok(FourCC::from(pcm.box_type()))
+        } else if self.trak.mdia.minf.stbl.stsd.ac4.is_some() {
+            Ok(FourCC::from(BoxType::Ac4Box))
         } else if self.trak.mdia.minf.stbl.stsd.tx3g.is_some() {
             Ok(FourCC::from(BoxType::Tx3gBox))
+        } else if self.trak.mdia.minf.stbl.stsd.mett.is_some() {
+            Ok(FourCC::from(BoxType::MettBox))
+        } else if self.trak.mdia.minf.stbl.stsd.metx.is_some() {
+            Ok(FourCC::from(BoxType::MetxBox))
+        } else if self.trak.mdia.minf.stbl.stsd.urim.is_some() {
+            Ok(FourCC::from(BoxType::UrimBox))
+        } else if self.trak.mdia.minf.stbl.stsd.stpp.is_some() {
+            Ok(FourCC::from(BoxType::StppBox))
+        } else if self.trak.mdia.minf.stbl.stsd.stxt.is_some() {
+            Ok(FourCC::from(BoxType::StxtBox))
         } else {
             Err(Error::InvalidData("unsupported sample entry box"))
         }
     }
 
+    /// The format of the samples of a timed metadata or text track: the MIME
+    /// type of a `mett` or `stxt` sample entry, the XML namespaces of a
+    /// `metx` or `stpp` one or the URI of a `urim` one. `None` for other
+    /// sample entries.
+    pub fn metadata_content_type(&self) -> Option<&str> {
+        let stsd = &self.trak.mdia.minf.stbl.stsd;
+        if let Some(ref mett) = stsd.mett {
+            Some(&mett.mime_format)
+        } else if let Some(ref metx) = stsd.metx {
+            Some(&metx.namespace)
+        } else if let Some(ref stpp) = stsd.stpp {
+            Some(&stpp.namespace)
+        } else if let Some(ref stxt) = stsd.stxt {
+            Some(&stxt.mime_format)
+        } else {
+            stsd.urim.as_ref().map(|urim| urim.uri.uri.as_str())
+        }
+    }
+
     pub fn width(&self) -> u16 {
         if let Some(ref avc1) = self.trak.mdia.minf.stbl.stsd.avc1 {
             avc1.width
@@ -166,12 +352,65 @@ impl Mp4Track {
         }
     }
 
-    pub fn frame_rate(&self) -> f64 {
-        let dur = self.duration();
-        if dur.is_zero() {
-            0.0
+    /// Frames per second of a video track: the exact rate for constant frame
+    /// rate tracks and the average rate otherwise. `None` for other tracks.
+    pub fn frame_rate(&self) -> Option<f64> {
+        if !matches!(self.track_type(), Ok(TrackType::Video)) {
+            return None;
+        }
+        let timescale = self.timescale();
+        if timescale == 0 {
+            return None;
+        }
+
+        let (sample_count, duration) = match self.constant_sample_deltas() {
+            Some(entries) => entries.iter().fold((0u64, 0u64), |(count, dur), entry| {
+                (
+                    count + entry.sample_count as u64,
+                    dur + entry.sample_count as u64 * entry.sample_delta as u64,
+                )
+            }),
+            None => {
+                let stts = &self.trak.mdia.minf.stbl.stts;
+                let duration = stts
+                    .entries
+                    .iter()
+                    .map(|entry| entry.sample_count as u64 * entry.sample_delta as u64)
+                    .sum::<u64>();
+                (self.sample_count() as u64, duration)
+            }
+        };
+        if duration == 0 {
+            return None;
+        }
+        Some(sample_count as f64 * timescale as f64 / duration as f64)
+    }
+
+    /// Whether all samples have the same duration, allowing for one tick of
+    /// rounding jitter and a shorter final sample.
+    pub fn is_constant_frame_rate(&self) -> bool {
+        self.constant_sample_deltas().is_some()
+    }
+
+    // The stts entries that determine a constant frame rate, i.e. without a
+    // trailing single short sample, or `None` if the rate is variable.
+    fn constant_sample_deltas(&self) -> Option<&[SttsEntry]> {
+        let mut entries = &self.trak.mdia.minf.stbl.stts.entries[..];
+        if let [rest @ .., last] = entries {
+            if !rest.is_empty() && last.sample_count == 1 {
+                let min_delta = rest.iter().map(|entry| entry.sample_delta).min().unwrap();
+                if last.sample_delta < min_delta {
+                    entries = rest;
+                }
+            }
+        }
+
+        let min_delta = entries.iter().map(|entry| entry.sample_delta).min()?;
+        let max_delta = entries.iter().map(|entry| entry.sample_delta).max()?;
+        if max_delta - min_delta <= 1 {
+            Some(entries)
         } else {
-            self.sample_count() as f64 / dur.as_secs_f64()
+            None
         }
     }
 
@@ -199,10 +438,82 @@ impl Mp4Track {
         }
     }
 
+    /// Sample rate in Hz of an audio track.
+    pub fn sample_rate(&self) -> Result<u32> {
+        self.audio_properties().map(|props| props.sample_rate)
+    }
+
+    /// Channel count of an audio track.
+    pub fn channel_count(&self) -> Result<u16> {
+        self.audio_properties().map(|props| props.channel_count)
+    }
+
+    /// Sample size in bits of an audio track, as declared by its sample entry.
+    pub fn bits_per_sample(&self) -> Result<u16> {
+        self.audio_properties().map(|props| props.bits_per_sample)
+    }
+
+    // Resolve audio properties from the first sample entry, preferring the
+    // codec configuration over the generic AudioSampleEntry fields, which
+    // encoders often leave at their defaults.
+    fn audio_properties(&self) -> Result<AudioProperties> {
+        match self.sample_description(1) {
+            Some(SampleEntry::Mp4a(mp4a)) => {
+                let mut props = AudioProperties {
+                    sample_rate: mp4a.samplerate.value() as u32,
+                    channel_count: mp4a.channelcount,
+                    bits_per_sample: mp4a.samplesize,
+                };
+                if let Some(ref esds) = mp4a.esds {
+                    let dec_specific = &esds.es_desc.dec_config.dec_specific;
+                    if let Some(sample_rate) = dec_specific.output_sampling_frequency() {
+                        props.sample_rate = sample_rate;
+                    }
+                    if let Some(channel_count) = dec_specific.channel_count() {
+                        props.channel_count = channel_count;
+                    }
+                }
+                Ok(props)
+            }
+            Some(SampleEntry::Pcm(pcm)) => Ok(AudioProperties {
+                sample_rate: pcm.samplerate,
+                channel_count: pcm.channelcount,
+                bits_per_sample: pcm.bits_per_sample(),
+            }),
+            Some(SampleEntry::Ac4(ac4)) => Ok(AudioProperties {
+                sample_rate: ac4
+                    .dac4
+                    .dsi()
+                    .map(|dsi| dsi.sampling_frequency())
+                    .unwrap_or(ac4.samplerate),
+                channel_count: ac4.channelcount,
+                bits_per_sample: ac4.samplesize,
+            }),
+            Some(SampleEntry::Unknown(_, data))
+                if matches!(self.track_type(), Ok(TrackType::Audio)) =>
+            {
+                AudioProperties::parse(data)
+                    .ok_or(Error::InvalidData("audio sample entry too small"))
+            }
+            Some(_) => Err(Error::InvalidData("not an audio sample entry")),
+            None => Err(Error::BoxInTrakNotFound(self.track_id(), BoxType::StsdBox)),
+        }
+    }
+
     pub fn language(&self) -> &str {
         &self.trak.mdia.mdhd.language
     }
 
+    /// The BCP 47 language tag of the `elng` box, if any, which takes
+    /// precedence over the [language](Mp4Track::language) of the `mdhd`.
+    pub fn extended_language(&self) -> Option<&str> {
+        self.trak
+            .mdia
+            .elng
+            .as_ref()
+            .map(|elng| elng.extended_language.as_str())
+    }
+
     pub fn timescale(&self) -> u32 {
         self.trak.mdia.mdhd.timescale
     }
@@ -221,6 +532,18 @@ impl Mp4Track {
                 0
             }
             // mp4a.esds.es_desc.dec_config.avg_bitrate
+        } else if let Some(bit_rate) = self
+            .trak
+            .mdia
+            .minf
+            .stbl
+            .stsd
+            .ac4
+            .as_ref()
+            .and_then(|ac4| ac4.dac4.dsi().ok()?.bit_rate)
+            .filter(|bit_rate| *bit_rate > 0)
+        {
+            bit_rate
         } else {
             let dur = self.duration();
             if dur.is_zero() {
@@ -232,20 +555,201 @@ impl Mp4Track {
         }
     }
 
+    /// The number of samples in the `moov` sample table followed by those in
+    /// the fragments.
     pub fn sample_count(&self) -> u32 {
-        if !self.trafs.is_empty() {
-            let mut sample_count = 0u32;
-            for traf in self.trafs.iter() {
-                if let Some(ref trun) = traf.trun {
-                    sample_count = sample_count
-                        .checked_add(trun.sample_count)
-                        .expect("attempt to sum trun sample_count with overflow");
+        let mut sample_count = self.trak.mdia.minf.stbl.stsz.sample_count;
+        for traf in self.trafs.iter() {
+            if let Some(ref trun) = traf.trun {
+                sample_count = sample_count
+                    .checked_add(trun.sample_count)
+                    .expect("attempt to sum trun sample_count with overflow");
+            }
+        }
+        sample_count
+    }
+
+    /// The mean size of the samples in bytes, rounded down; 0 if there are
+    /// none. Fragment samples whose size is not known are left out.
+    pub fn average_sample_size(&self) -> u64 {
+        let (count, total, _) = self.sample_size_stats();
+        total.checked_div(count).unwrap_or(0)
+    }
+
+    /// The size of the largest sample in bytes; 0 if there are none.
+    pub fn max_sample_size(&self) -> u32 {
+        let (_, _, max) = self.sample_size_stats();
+        max
+    }
+
+    // The number, total size and largest size of the samples of the sample
+    // table and of the fragments.
+    fn sample_size_stats(&self) -> (u64, u64, u32) {
+        let stsz = &self.trak.mdia.minf.stbl.stsz;
+        let mut count = stsz.sample_count as u64;
+        let mut total = stsz.total_size();
+        let mut max = if stsz.sample_size > 0 || stsz.sample_count == 0 {
+            stsz.sample_size
+        } else {
+            stsz.sample_sizes.iter().copied().max().unwrap_or(0)
+        };
+
+        for traf in self.trafs.iter() {
+            let Some(ref trun) = traf.trun else {
+                continue;
+            };
+            if TrunBox::FLAG_SAMPLE_SIZE & trun.flags != 0 {
+                for &size in trun.sample_sizes.iter() {
+                    count += 1;
+                    total += size as u64;
+                    max = max.max(size);
                 }
+            } else if let Some(size) = self
+                .traf_default_sample_size(traf)
+                .filter(|_| trun.sample_count > 0)
+            {
+                count += trun.sample_count as u64;
+                total += size as u64 * trun.sample_count as u64;
+                max = max.max(size);
+            }
+        }
+        (count, total, max)
+    }
+
+    /// The ids of the samples presented, at least in part, between `start`
+    /// and `end`, in decoding order. The first id is snapped down to the
+    /// closest preceding sync sample so that the samples can be decoded.
+    ///
+    /// Presentation times are the decode times plus the composition offsets;
+    /// edit lists are not applied.
+    pub fn samples_in_range(&self, start: Duration, end: Duration) -> Vec<u32> {
+        let timescale = self.timescale() as u128;
+        let to_ticks = |time: Duration| (time.as_nanos() * timescale / 1_000_000_000) as i64;
+        let (start, end) = (to_ticks(start), to_ticks(end));
+        if end <= start {
+            return Vec::new();
+        }
+
+        let mut first = None;
+        let mut last = 0;
+        for (i, (decode_time, duration, rendering_offset)) in
+            self.sample_times().into_iter().enumerate()
+        {
+            let presentation_time = decode_time as i64 + rendering_offset as i64;
+            if presentation_time < end && presentation_time + duration.max(1) as i64 > start {
+                let sample_id = i as u32 + 1;
+                first = first.or(Some(sample_id));
+                last = sample_id;
             }
-            sample_count
+        }
+
+        let mut first = match first {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        while first > 1 && !self.is_sync_sample(first) {
+            first -= 1;
+        }
+        (first..=last).collect()
+    }
+
+    /// The ids of the sync samples (keyframes), in decoding order: those
+    /// listed in `stss`, or every `moov` sample if it is absent, followed by
+    /// the fragment samples whose `trun` or `tfhd` flags mark them as sync.
+    pub fn sync_samples(&self) -> Vec<u32> {
+        let stbl = &self.trak.mdia.minf.stbl;
+        let moov_sample_count = stbl.stsz.sample_count;
+        let mut sync_samples: Vec<u32> = match stbl.stss {
+            Some(ref stss) => stss
+                .entries
+                .iter()
+                .copied()
+                .filter(|&id| id >= 1 && id <= moov_sample_count)
+                .collect(),
+            None => (1..=moov_sample_count).collect(),
+        };
+
+        let mut sample_id = moov_sample_count;
+        for (traf_idx, traf) in self.trafs.iter().enumerate() {
+            if let Some(ref trun) = traf.trun {
+                for sample_idx in 0..trun.sample_count as usize {
+                    sample_id += 1;
+                    if self
+                        .traf_sample_flags(traf_idx, sample_idx)
+                        .is_none_or(|flags| flags & SAMPLE_IS_NON_SYNC_SAMPLE == 0)
+                    {
+                        sync_samples.push(sample_id);
+                    }
+                }
+            }
+        }
+        sync_samples
+    }
+
+    /// The number of sync samples; see [Mp4Track::sync_samples].
+    pub fn keyframe_count(&self) -> u32 {
+        self.sync_samples().len() as u32
+    }
+
+    /// The decode time, composition time and duration of every sample,
+    /// computed in one pass over the sample tables and fragments.
+    pub fn timing_table(&self) -> TimingTable {
+        let times = self.sample_times();
+        let mut table = TimingTable {
+            dts: Vec::with_capacity(times.len()),
+            cts: Vec::with_capacity(times.len()),
+            duration: Vec::with_capacity(times.len()),
+        };
+        for (decode_time, duration, rendering_offset) in times {
+            table.dts.push(decode_time);
+            table.cts.push(decode_time as i64 + rendering_offset as i64);
+            table.duration.push(duration);
+        }
+        table
+    }
+
+    pub fn vp9_config(&self) -> Result<Vp9Config> {
+        if let Some(ref vp09) = self.trak.mdia.minf.stbl.stsd.vp09 {
+            Ok(Vp9Config {
+                width: vp09.width,
+                height: vp09.height,
+                profile: vp09.vpcc.profile,
+                level: vp09.vpcc.level,
+                bit_depth: vp09.vpcc.bit_depth,
+                chroma_subsampling: vp09.vpcc.chroma_subsampling,
+                video_full_range_flag: vp09.vpcc.video_full_range_flag,
+                color_primaries: vp09.vpcc.color_primaries,
+                transfer_characteristics: vp09.vpcc.transfer_characteristics,
+                matrix_coefficients: vp09.vpcc.matrix_coefficients,
+                codec_initialization_data: vp09.vpcc.codec_initialization_data.clone(),
+            })
+        } else {
+            Err(Error::BoxInStblNotFound(self.track_id(), BoxType::Vp09Box))
+        }
+    }
+
+    /// The mastering display, content light level and ambient viewing
+    /// environment boxes of an `avc1`, `hev1`/`hvc1` or `vp09` sample entry,
+    /// or `None` if the track carries none of them.
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        let stsd = &self.trak.mdia.minf.stbl.stsd;
+        let (mdcv, clli, amve) = if let Some(ref avc1) = stsd.avc1 {
+            (&avc1.mdcv, &avc1.clli, &avc1.amve)
+        } else if let Some(ref hev1) = stsd.hev1 {
+            (&hev1.mdcv, &hev1.clli, &hev1.amve)
+        } else if let Some(ref vp09) = stsd.vp09 {
+            (&vp09.mdcv, &vp09.clli, &vp09.amve)
         } else {
-            self.trak.mdia.minf.stbl.stsz.sample_count
+            return None;
+        };
+        if mdcv.is_none() && clli.is_none() && amve.is_none() {
+            return None;
         }
+        Some(HdrMetadata {
+            mdcv: mdcv.clone(),
+            clli: clli.clone(),
+            amve: amve.clone(),
+        })
     }
 
     pub fn video_profile(&self) -> Result<AvcProfile> {
@@ -261,7 +765,7 @@ impl Mp4Track {
 
     pub fn sequence_parameter_set(&self) -> Result<&[u8]> {
         if let Some(ref avc1) = self.trak.mdia.minf.stbl.stsd.avc1 {
-            match avc1.avcc.sequence_parameter_sets.get(0) {
+            match avc1.avcc.sequence_parameter_sets.first() {
                 Some(nal) => Ok(nal.bytes.as_ref()),
                 None => Err(Error::EntryInStblNotFound(
                     self.track_id(),
@@ -276,7 +780,7 @@ impl Mp4Track {
 
     pub fn picture_parameter_set(&self) -> Result<&[u8]> {
         if let Some(ref avc1) = self.trak.mdia.minf.stbl.stsd.avc1 {
-            match avc1.avcc.picture_parameter_sets.get(0) {
+            match avc1.avcc.picture_parameter_sets.first() {
                 Some(nal) => Ok(nal.bytes.as_ref()),
                 None => Err(Error::EntryInStblNotFound(
                     self.track_id(),
@@ -301,6 +805,71 @@ impl Mp4Track {
         }
     }
 
+    pub fn aac_config(&self) -> Result<AacConfig> {
+        if let Some(ref mp4a) = self.trak.mdia.minf.stbl.stsd.mp4a {
+            if let Some(ref esds) = mp4a.esds {
+                let dec_specific = &esds.es_desc.dec_config.dec_specific;
+                Ok(AacConfig {
+                    bitrate: esds.es_desc.dec_config.avg_bitrate,
+                    profile: AudioObjectType::try_from(dec_specific.profile)?,
+                    freq_index: SampleFreqIndex::try_from(dec_specific.freq_index)?,
+                    chan_conf: ChannelConfig::try_from(dec_specific.chan_conf)?,
+                    // An explicit frequency has no index.
+                    extension_freq_index: dec_specific
+                        .extension_freq_index
+                        .filter(|&index| index != 15)
+                        .map(SampleFreqIndex::try_from)
+                        .transpose()?,
+                    sbr_present: dec_specific.sbr_present,
+                    ps_present: dec_specific.ps_present,
+                })
+            } else {
+                Err(Error::BoxInStblNotFound(self.track_id(), BoxType::EsdsBox))
+            }
+        } else {
+            Err(Error::BoxInStblNotFound(self.track_id(), BoxType::Mp4aBox))
+        }
+    }
+
+    pub fn ac4_config(&self) -> Result<Ac4Config> {
+        if let Some(ref ac4) = self.trak.mdia.minf.stbl.stsd.ac4 {
+            Ok(Ac4Config {
+                sample_rate: self.sample_rate()?,
+                channel_count: ac4.channelcount,
+                dsi: ac4.dac4.data.clone(),
+            })
+        } else {
+            Err(Error::BoxInStblNotFound(self.track_id(), BoxType::Ac4Box))
+        }
+    }
+
+    pub fn stpp_config(&self) -> Result<StppConfig> {
+        if let Some(ref stpp) = self.trak.mdia.minf.stbl.stsd.stpp {
+            Ok(StppConfig {
+                namespace: stpp.namespace.clone(),
+                schema_location: stpp.schema_location.clone(),
+                auxiliary_mime_types: stpp.auxiliary_mime_types.clone(),
+            })
+        } else {
+            Err(Error::BoxInStblNotFound(self.track_id(), BoxType::StppBox))
+        }
+    }
+
+    pub fn pcm_config(&self) -> Result<PcmConfig> {
+        if let Some(ref pcm) = self.trak.mdia.minf.stbl.stsd.pcm {
+            Ok(PcmConfig {
+                sample_rate: pcm.samplerate,
+                channel_count: pcm.channelcount,
+                bits_per_sample: pcm.bits_per_sample(),
+                float: pcm.is_float(),
+                little_endian: pcm.is_little_endian(),
+                signed: pcm.is_signed(),
+            })
+        } else {
+            Err(Error::BoxInStblNotFound(self.track_id(), BoxType::IpcmBox))
+        }
+    }
+
     fn stsc_index(&self, sample_id: u32) -> Result<usize> {
         if self.trak.mdia.minf.stbl.stsc.entries.is_empty() {
             return Err(Error::InvalidData("no stsc entries"));
@@ -318,31 +887,27 @@ impl Mp4Track {
     }
 
     fn chunk_offset(&self, chunk_id: u32) -> Result<u64> {
-        if self.trak.mdia.minf.stbl.stco.is_none() && self.trak.mdia.minf.stbl.co64.is_none() {
-            return Err(Error::InvalidData("must have either stco or co64 boxes"));
-        }
-        if let Some(ref stco) = self.trak.mdia.minf.stbl.stco {
-            if let Some(offset) = stco.entries.get(chunk_id as usize - 1) {
-                return Ok(*offset as u64);
-            } else {
-                return Err(Error::EntryInStblNotFound(
-                    self.track_id(),
-                    BoxType::StcoBox,
-                    chunk_id,
-                ));
-            }
-        } else if let Some(ref co64) = self.trak.mdia.minf.stbl.co64 {
-            if let Some(offset) = co64.entries.get(chunk_id as usize - 1) {
-                return Ok(*offset);
-            } else {
-                return Err(Error::EntryInStblNotFound(
-                    self.track_id(),
-                    BoxType::Co64Box,
-                    chunk_id,
-                ));
-            }
-        }
-        Err(Error::Box2NotFound(BoxType::StcoBox, BoxType::Co64Box))
+        let stbl = &self.trak.mdia.minf.stbl;
+        let (box_type, offset) = if let Some(ref co64) = stbl.co64 {
+            (
+                BoxType::Co64Box,
+                co64.entries.get(chunk_id as usize - 1).copied(),
+            )
+        } else if let Some(ref stco) = stbl.stco {
+            (
+                BoxType::StcoBox,
+                stco.entries
+                    .get(chunk_id as usize - 1)
+                    .map(|offset| *offset as u64),
+            )
+        } else {
+            return Err(Error::Box2NotFound(BoxType::StcoBox, BoxType::Co64Box));
+        };
+        offset.ok_or(Error::EntryInStblNotFound(
+            self.track_id(),
+            box_type,
+            chunk_id,
+        ))
     }
 
     fn ctts_index(&self, sample_id: u32) -> Result<(usize, u32)> {
@@ -368,9 +933,16 @@ impl Mp4Track {
         ))
     }
 
+    // Whether a sample comes after the `moov` sample table, from a fragment.
+    fn is_fragment_sample(&self, sample_id: u32) -> bool {
+        !self.trafs.is_empty() && sample_id > self.trak.mdia.minf.stbl.stsz.sample_count
+    }
+
     /// return `(traf_idx, sample_idx_in_trun)`
     fn find_traf_idx_and_sample_idx(&self, sample_id: u32) -> Option<(usize, usize)> {
-        let global_idx = sample_id - 1;
+        let global_idx = sample_id
+            .checked_sub(1)?
+            .checked_sub(self.trak.mdia.minf.stbl.stsz.sample_count)?;
         let mut offset = 0;
         for traf_idx in 0..self.trafs.len() {
             if let Some(trun) = &self.trafs[traf_idx].trun {
@@ -386,17 +958,131 @@ impl Mp4Track {
         None
     }
 
+    fn traf_sample_duration(&self, traf_idx: usize, sample_idx: usize) -> u32 {
+        let traf = &self.trafs[traf_idx];
+        traf.trun
+            .as_ref()
+            .filter(|trun| TrunBox::FLAG_SAMPLE_DURATION & trun.flags != 0)
+            .and_then(|trun| trun.sample_durations.get(sample_idx).copied())
+            .or(traf.tfhd.default_sample_duration)
+            .or(self.trex.as_ref().map(|trex| trex.default_sample_duration))
+            .unwrap_or(0)
+    }
+
+    fn traf_sample_flags(&self, traf_idx: usize, sample_idx: usize) -> Option<u32> {
+        let traf = &self.trafs[traf_idx];
+        let trun = traf.trun.as_ref()?;
+        if sample_idx == 0 && trun.first_sample_flags.is_some() {
+            return trun.first_sample_flags;
+        }
+        if TrunBox::FLAG_SAMPLE_FLAGS & trun.flags != 0 {
+            return trun.sample_flags.get(sample_idx).copied();
+        }
+        traf.tfhd
+            .default_sample_flags
+            .or(self.trex.as_ref().map(|trex| trex.default_sample_flags))
+    }
+
+    fn traf_default_sample_size(&self, traf: &TrafBox) -> Option<u32> {
+        traf.tfhd
+            .default_sample_size
+            .or(self.trex.as_ref().map(|trex| trex.default_sample_size))
+    }
+
+    // Decode time of the first sample of a traf: its tfdt, or the end of the
+    // samples before it.
+    fn traf_start_time(&self, traf_idx: usize) -> Result<u64> {
+        let mut first = traf_idx;
+        let mut start_time = loop {
+            if let Some(ref tfdt) = self.trafs[first].tfdt {
+                break tfdt.base_media_decode_time;
+            }
+            if first == 0 {
+                break self
+                    .trak
+                    .mdia
+                    .minf
+                    .stbl
+                    .stts
+                    .entries
+                    .iter()
+                    .map(|entry| entry.sample_count as u64 * entry.sample_delta as u64)
+                    .sum();
+            }
+            first -= 1;
+        };
+        for idx in first..traf_idx {
+            let sample_count = self.trafs[idx]
+                .trun
+                .as_ref()
+                .map_or(0, |trun| trun.sample_count);
+            for sample_idx in 0..sample_count as usize {
+                start_time = start_time
+                    .checked_add(self.traf_sample_duration(idx, sample_idx) as u64)
+                    .ok_or(Error::InvalidData(
+                        "attempt to sum sample durations with overflow",
+                    ))?;
+            }
+        }
+        Ok(start_time)
+    }
+
+    // Decode time, duration and rendering offset of every sample, in one pass
+    // over the sample tables.
+    fn sample_times(&self) -> Vec<(u64, u32, i32)> {
+        let stbl = &self.trak.mdia.minf.stbl;
+        let mut times = Vec::with_capacity(self.sample_count() as usize);
+
+        let mut time = 0u64;
+        'stts: for entry in stbl.stts.entries.iter() {
+            for _ in 0..entry.sample_count {
+                if times.len() == stbl.stsz.sample_count as usize {
+                    break 'stts;
+                }
+                times.push((time, entry.sample_delta, 0));
+                time = time.saturating_add(entry.sample_delta as u64);
+            }
+        }
+        // Keep the ids of fragment samples aligned if stts is short.
+        times.resize(stbl.stsz.sample_count as usize, (time, 0, 0));
+        if let Some(ref ctts) = stbl.ctts {
+            let offsets = ctts
+                .entries
+                .iter()
+                .flat_map(|entry| (0..entry.sample_count).map(move |_| entry.sample_offset));
+            for (sample, offset) in times.iter_mut().zip(offsets) {
+                sample.2 = offset;
+            }
+        }
+
+        for (traf_idx, traf) in self.trafs.iter().enumerate() {
+            if let Some(ref tfdt) = traf.tfdt {
+                time = tfdt.base_media_decode_time;
+            }
+            if let Some(ref trun) = traf.trun {
+                for sample_idx in 0..trun.sample_count as usize {
+                    let duration = self.traf_sample_duration(traf_idx, sample_idx);
+                    let offset = trun.sample_cts.get(sample_idx).copied().unwrap_or(0);
+                    times.push((time, duration, offset as i32));
+                    time = time.saturating_add(duration as u64);
+                }
+            }
+        }
+        times
+    }
+
     fn sample_size(&self, sample_id: u32) -> Result<u32> {
-        if !self.trafs.is_empty() {
+        if self.is_fragment_sample(sample_id) {
             if let Some((traf_idx, sample_idx)) = self.find_traf_idx_and_sample_idx(sample_id) {
-                if let Some(size) = self.trafs[traf_idx]
-                    .trun
-                    .as_ref()
-                    .unwrap()
-                    .sample_sizes
-                    .get(sample_idx)
-                {
-                    Ok(*size)
+                let traf = &self.trafs[traf_idx];
+                let trun = traf.trun.as_ref().unwrap();
+                let size = if TrunBox::FLAG_SAMPLE_SIZE & trun.flags != 0 {
+                    trun.sample_sizes.get(sample_idx).copied()
+                } else {
+                    self.traf_default_sample_size(traf)
+                };
+                if let Some(size) = size {
+                    Ok(size)
                 } else {
                     Err(Error::EntryInTrunNotFound(
                         self.track_id(),
@@ -408,12 +1094,8 @@ impl Mp4Track {
                 Err(Error::BoxInTrafNotFound(self.track_id(), BoxType::TrafBox))
             }
         } else {
-            let stsz = &self.trak.mdia.minf.stbl.stsz;
-            if stsz.sample_size > 0 {
-                return Ok(stsz.sample_size);
-            }
-            if let Some(size) = stsz.sample_sizes.get(sample_id as usize - 1) {
-                Ok(*size)
+            if let Some(size) = self.trak.mdia.minf.stbl.stsz.entry_size(sample_id) {
+                Ok(size)
             } else {
                 Err(Error::EntryInStblNotFound(
                     self.track_id(),
@@ -425,20 +1107,17 @@ impl Mp4Track {
     }
 
     fn total_sample_size(&self) -> u64 {
-        let stsz = &self.trak.mdia.minf.stbl.stsz;
-        if stsz.sample_size > 0 {
-            stsz.sample_size as u64 * self.sample_count() as u64
-        } else {
-            let mut total_size = 0;
-            for size in stsz.sample_sizes.iter() {
-                total_size += *size as u64;
-            }
-            total_size
-        }
+        self.trak.mdia.minf.stbl.stsz.total_size()
     }
 
+    /// The file offset of a sample's data.
+    ///
+    /// Samples of a chunk, or of a `trun`, are contiguous: the offset is that
+    /// of the chunk plus the `stsz` sizes of the samples before it in the
+    /// chunk. Chunks themselves may be anywhere in the file, with padding or
+    /// other data between them, as their offsets are listed one by one.
     pub fn sample_offset(&self, sample_id: u32) -> Result<u64> {
-        if !self.trafs.is_empty() {
+        if self.is_fragment_sample(sample_id) {
             if let Some((traf_idx, sample_idx)) = self.find_traf_idx_and_sample_idx(sample_id) {
                 let mut sample_offset = self.trafs[traf_idx]
                     .tfhd
@@ -469,63 +1148,128 @@ impl Mp4Track {
                 Err(Error::BoxInTrafNotFound(self.track_id(), BoxType::TrafBox))
             }
         } else {
-            let stsc_index = self.stsc_index(sample_id)?;
-
-            let stsc = &self.trak.mdia.minf.stbl.stsc;
-            let stsc_entry = stsc.entries.get(stsc_index).unwrap();
-
-            let first_chunk = stsc_entry.first_chunk;
-            let first_sample = stsc_entry.first_sample;
-            let samples_per_chunk = stsc_entry.samples_per_chunk;
-
-            let chunk_id = sample_id
-                .checked_sub(first_sample)
-                .map(|n| n / samples_per_chunk)
-                .and_then(|n| n.checked_add(first_chunk))
-                .ok_or(Error::InvalidData(
-                    "attempt to calculate stsc chunk_id with overflow",
-                ))?;
+            let (chunk_id, first_sample_in_chunk) = self.sample_chunk(sample_id)?;
 
             let chunk_offset = self.chunk_offset(chunk_id)?;
 
-            let first_sample_in_chunk = sample_id - (sample_id - first_sample) % samples_per_chunk;
-
-            let mut sample_offset = 0;
+            let mut sample_offset = chunk_offset;
             for i in first_sample_in_chunk..sample_id {
-                sample_offset += self.sample_size(i)?;
+                sample_offset = sample_offset
+                    .checked_add(self.sample_size(i)? as u64)
+                    .ok_or(Error::InvalidData(
+                        "attempt to calculate chunk sample offset with overflow",
+                    ))?;
             }
 
-            Ok(chunk_offset + sample_offset as u64)
+            Ok(sample_offset)
         }
     }
 
-    fn sample_time(&self, sample_id: u32) -> Result<(u64, u32)> {
-        if !self.trafs.is_empty() {
-            let mut base_start_time = 0;
-            let mut default_sample_duration = self.default_sample_duration;
-            if let Some((traf_idx, sample_idx)) = self.find_traf_idx_and_sample_idx(sample_id) {
+    /// return `(chunk_id, first_sample_in_chunk)`
+    fn sample_chunk(&self, sample_id: u32) -> Result<(u32, u32)> {
+        let stsc_index = self.stsc_index(sample_id)?;
+
+        let stsc = &self.trak.mdia.minf.stbl.stsc;
+        let stsc_entry = stsc.entries.get(stsc_index).unwrap();
+
+        let first_chunk = stsc_entry.first_chunk;
+        let first_sample = stsc_entry.first_sample;
+        let samples_per_chunk = stsc_entry.samples_per_chunk;
+
+        let chunk_id = sample_id
+            .checked_sub(first_sample)
+            .map(|n| n / samples_per_chunk)
+            .and_then(|n| n.checked_add(first_chunk))
+            .ok_or(Error::InvalidData(
+                "attempt to calculate stsc chunk_id with overflow",
+            ))?;
+
+        let first_sample_in_chunk = sample_id - (sample_id - first_sample) % samples_per_chunk;
+
+        Ok((chunk_id, first_sample_in_chunk))
+    }
+
+    /// The offset and size of the Common Encryption auxiliary information of
+    /// a sample, as given by the `saiz` and `saio` boxes of its `traf`, or of
+    /// the `stbl` for samples of the `moov`.
+    pub(crate) fn aux_info_location(&self, sample_id: u32) -> Result<Option<(u64, u32)>> {
+        // Offsets of fragment aux info are relative to the sample data base.
+        let (saiz, base, index, first_index) =
+            if self.is_fragment_sample(sample_id) {
+                let (traf_idx, sample_idx) = match self.find_traf_idx_and_sample_idx(sample_id) {
+                    Some(idx) => idx,
+                    None => {
+                        return Err(Error::BoxInTrafNotFound(self.track_id(), BoxType::TrafBox))
+                    }
+                };
                 let traf = &self.trafs[traf_idx];
-                if let Some(tfdt) = &traf.tfdt {
-                    base_start_time = tfdt.base_media_decode_time;
+                let (saiz, saio) = match (&traf.saiz, &traf.saio) {
+                    (Some(saiz), Some(saio)) => (saiz, saio),
+                    _ => return Ok(None),
+                };
+                if saio.offsets.len() != 1 {
+                    return Err(Error::InvalidData("traf saio must have a single offset"));
                 }
-                if let Some(duration) = traf.tfhd.default_sample_duration {
-                    default_sample_duration = duration;
+                let base = traf
+                    .tfhd
+                    .base_data_offset
+                    .unwrap_or(self.moof_offsets[traf_idx]);
+                let base = base.checked_add(saio.offsets[0]).ok_or(Error::InvalidData(
+                    "attempt to calculate saio offset with overflow",
+                ))?;
+                (saiz, base, sample_idx, 0)
+            } else {
+                let stbl = &self.trak.mdia.minf.stbl;
+                let (saiz, saio) = match (&stbl.saiz, &stbl.saio) {
+                    (Some(saiz), Some(saio)) => (saiz, saio),
+                    _ => return Ok(None),
+                };
+                let index = sample_id as usize - 1;
+                // A single offset covers all samples, otherwise there is one per
+                // chunk.
+                if saio.offsets.len() == 1 {
+                    (saiz, saio.offsets[0], index, 0)
+                } else {
+                    let (chunk_id, first_sample_in_chunk) = self.sample_chunk(sample_id)?;
+                    let offset = saio.offsets.get(chunk_id as usize - 1).ok_or(
+                        Error::EntryInStblNotFound(self.track_id(), BoxType::SaioBox, chunk_id),
+                    )?;
+                    (saiz, *offset, index, first_sample_in_chunk as usize - 1)
                 }
-                if let Some(trun) = &traf.trun {
-                    if TrunBox::FLAG_SAMPLE_DURATION & trun.flags != 0 {
-                        let mut start_offset = 0u64;
-                        for duration in &trun.sample_durations[..sample_idx] {
-                            start_offset = start_offset.checked_add(*duration as u64).ok_or(
-                                Error::InvalidData("attempt to sum sample durations with overflow"),
-                            )?;
-                        }
-                        let duration = trun.sample_durations[sample_idx];
-                        return Ok((base_start_time + start_offset, duration));
-                    }
+            };
+
+        if let Some(aux_info_type) = saiz.aux_info_type {
+            if !matches!(&aux_info_type.value, b"cenc" | b"cens" | b"cbc1" | b"cbcs") {
+                return Ok(None);
+            }
+        }
+        let size = match saiz.sample_info_size(index) {
+            Some(size) if size > 0 => size as u32,
+            _ => return Ok(None),
+        };
+        let mut offset = base;
+        for i in first_index..index {
+            offset += saiz.sample_info_size(i).unwrap_or(0) as u64;
+        }
+
+        Ok(Some((offset, size)))
+    }
+
+    fn sample_time(&self, sample_id: u32) -> Result<(u64, u32)> {
+        if self.is_fragment_sample(sample_id) {
+            if let Some((traf_idx, sample_idx)) = self.find_traf_idx_and_sample_idx(sample_id) {
+                let mut start_time = self.traf_start_time(traf_idx)?;
+                for i in 0..sample_idx {
+                    start_time = start_time
+                        .checked_add(self.traf_sample_duration(traf_idx, i) as u64)
+                        .ok_or(Error::InvalidData(
+                            "attempt to sum sample durations with overflow",
+                        ))?;
                 }
+                Ok((start_time, self.traf_sample_duration(traf_idx, sample_idx)))
+            } else {
+                Err(Error::BoxInTrafNotFound(self.track_id(), BoxType::TrafBox))
             }
-            let start_offset = ((sample_id - 1) * default_sample_duration) as u64;
-            Ok((base_start_time + start_offset, default_sample_duration))
         } else {
             let stts = &self.trak.mdia.minf.stbl.stts;
 
@@ -558,7 +1302,7 @@ impl Mp4Track {
     }
 
     fn sample_rendering_offset(&self, sample_id: u32) -> i32 {
-        if !self.trafs.is_empty() {
+        if self.is_fragment_sample(sample_id) {
             if let Some((traf_idx, sample_idx)) = self.find_traf_idx_and_sample_idx(sample_id) {
                 if let Some(cts) = self.trafs[traf_idx]
                     .trun
@@ -578,9 +1322,11 @@ impl Mp4Track {
     }
 
     fn is_sync_sample(&self, sample_id: u32) -> bool {
-        if !self.trafs.is_empty() {
-            let sample_sizes_count = self.sample_count() / self.trafs.len() as u32;
-            return sample_id == 1 || sample_id % sample_sizes_count == 0;
+        if self.is_fragment_sample(sample_id) {
+            return self
+                .find_traf_idx_and_sample_idx(sample_id)
+                .and_then(|(traf_idx, sample_idx)| self.traf_sample_flags(traf_idx, sample_idx))
+                .is_none_or(|flags| flags & SAMPLE_IS_NON_SYNC_SAMPLE == 0);
         }
 
         if let Some(ref stss) = self.trak.mdia.minf.stbl.stss {
@@ -590,11 +1336,19 @@ impl Mp4Track {
         }
     }
 
-    pub(crate) fn read_sample<R: Read + Seek>(
-        &self,
-        reader: &mut R,
-        sample_id: u32,
-    ) -> Result<Option<Mp4Sample>> {
+    /// Whether the data of a sample lies entirely before `data_end`.
+    pub(crate) fn is_sample_present(&self, sample_id: u32, data_end: u64) -> bool {
+        match (self.sample_offset(sample_id), self.sample_size(sample_id)) {
+            (Ok(offset), Ok(size)) => offset
+                .checked_add(size as u64)
+                .is_some_and(|end| end <= data_end),
+            _ => false,
+        }
+    }
+
+    // Offset and size of the data of a sample, or `None` past the end of the
+    // sample table.
+    pub(crate) fn sample_location(&self, sample_id: u32) -> Result<Option<(u64, u32)>> {
         let sample_offset = match self.sample_offset(sample_id) {
             Ok(offset) => offset,
             Err(Error::EntryInStblNotFound(_, _, _)) => return Ok(None),
@@ -605,25 +1359,163 @@ impl Mp4Track {
             Err(Error::EntryInStblNotFound(_, _, _)) => return Ok(None),
             Err(err) => return Err(err),
         };
+        Ok(Some((sample_offset, sample_size)))
+    }
+
+    pub(crate) fn read_sample<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        sample_id: u32,
+    ) -> Result<Option<Mp4Sample>> {
+        let (sample_offset, sample_size) = match self.sample_location(sample_id)? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
 
         let mut buffer = vec![0x0u8; sample_size as usize];
         reader.seek(SeekFrom::Start(sample_offset))?;
         reader.read_exact(&mut buffer)?;
 
-        let (start_time, duration) = self.sample_time(sample_id).unwrap(); // XXX
+        self.sample_with_bytes(sample_id, Bytes::from(buffer))
+            .map(Some)
+    }
+
+    // A sample with its timing taken from the sample tables and its data
+    // already read.
+    pub(crate) fn sample_with_bytes(&self, sample_id: u32, bytes: Bytes) -> Result<Mp4Sample> {
+        let (start_time, duration) = self.sample_time(sample_id)?;
+        let rendering_offset = self.sample_rendering_offset(sample_id);
+        let is_sync = self.is_sync_sample(sample_id);
+
+        Ok(Mp4Sample {
+            start_time,
+            duration,
+            rendering_offset,
+            is_sync,
+            bytes,
+        })
+    }
+
+    pub(crate) fn read_sample_ref<'a>(
+        &self,
+        data: &'a [u8],
+        sample_id: u32,
+    ) -> Result<Option<Mp4SampleRef<'a>>> {
+        let (sample_offset, sample_size) = match self.sample_location(sample_id)? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        let bytes = usize::try_from(sample_offset)
+            .ok()
+            .and_then(|start| data.get(start..start.checked_add(sample_size as usize)?))
+            .ok_or(Error::InvalidData(
+                "sample data extends past the end of the buffer",
+            ))?;
+
+        let (start_time, duration) = self.sample_time(sample_id)?;
         let rendering_offset = self.sample_rendering_offset(sample_id);
         let is_sync = self.is_sync_sample(sample_id);
 
-        Ok(Some(Mp4Sample {
+        Ok(Some(Mp4SampleRef {
             start_time,
             duration,
             rendering_offset,
             is_sync,
-            bytes: Bytes::from(buffer),
+            bytes,
         }))
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct AudioProperties {
+    sample_rate: u32,
+    channel_count: u16,
+    bits_per_sample: u16,
+}
+
+impl AudioProperties {
+    // Fields of an AudioSampleEntry (ISO/IEC 14496-12 12.2.3), which every
+    // audio codec's sample entry begins with, overridden by the `srat` box
+    // or the codec configuration box that follow them, if known.
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 28 {
+            return None;
+        }
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        let mut props = AudioProperties {
+            channel_count: u16_at(16),
+            bits_per_sample: u16_at(18),
+            sample_rate: u16_at(24) as u32,
+        };
+
+        let mut children = &data[28..];
+        while children.len() >= 8 {
+            let size = u32::from_be_bytes([children[0], children[1], children[2], children[3]]);
+            let size = size as usize;
+            if size < 8 || size > children.len() {
+                break;
+            }
+            let body = &children[8..size];
+            match &children[4..8] {
+                b"srat" => props.parse_srat(body),
+                b"dOps" => props.parse_dops(body),
+                b"dfLa" => props.parse_dfla(body),
+                b"dac3" => props.parse_dac3(body),
+                _ => {}
+            }
+            children = &children[size..];
+        }
+        Some(props)
+    }
+
+    // The sampling rate of an AudioSampleEntryV1, for rates that do not fit
+    // the 16-bit field.
+    fn parse_srat(&mut self, body: &[u8]) {
+        if let Some(rate) = body.get(4..8) {
+            self.sample_rate = u32::from_be_bytes([rate[0], rate[1], rate[2], rate[3]]);
+        }
+    }
+
+    // Opus is always decoded at 48 kHz, whatever the rate of the input was.
+    fn parse_dops(&mut self, body: &[u8]) {
+        if let Some(&channel_count) = body.get(1) {
+            self.channel_count = channel_count as u16;
+            self.sample_rate = 48000;
+        }
+    }
+
+    // The STREAMINFO metadata block, which comes first in a FLAC stream.
+    fn parse_dfla(&mut self, body: &[u8]) {
+        let block = match body.get(4..) {
+            Some(block) if block.len() >= 4 + 18 && block[0] & 0x7f == 0 => &block[4..],
+            _ => return,
+        };
+        let mut bits = [0; 8];
+        bits.copy_from_slice(&block[10..18]);
+        let bits = u64::from_be_bytes(bits);
+        self.sample_rate = (bits >> 44) as u32;
+        self.channel_count = ((bits >> 41) & 0x7) as u16 + 1;
+        self.bits_per_sample = ((bits >> 36) & 0x1f) as u16 + 1;
+    }
+
+    // The fscod, acmod and lfeon fields of an AC3SpecificBox.
+    fn parse_dac3(&mut self, body: &[u8]) {
+        if body.len() < 3 {
+            return;
+        }
+        match body[0] >> 6 {
+            0 => self.sample_rate = 48000,
+            1 => self.sample_rate = 44100,
+            2 => self.sample_rate = 32000,
+            _ => {}
+        }
+        let acmod = (body[1] >> 3) & 0x7;
+        let lfeon = (body[1] >> 2) & 0x1;
+        self.channel_count = [2, 1, 2, 3, 3, 4, 4, 5][acmod as usize] + lfeon as u16;
+    }
+}
+
 // TODO creation_time, modification_time
 #[derive(Debug, Default)]
 pub(crate) struct Mp4TrackWriter {
@@ -638,14 +1530,32 @@ pub(crate) struct Mp4TrackWriter {
 
     samples_per_chunk: u32,
     duration_per_chunk: u32,
+
+    // Sample written with timestamps, waiting for the next DTS to know its
+    // duration.
+    pending_sample: Option<Mp4Sample>,
+
+    encoder_delay: u32,
 }
 
 impl Mp4TrackWriter {
     pub(crate) fn new(track_id: u32, config: &TrackConfig) -> Result<Self> {
         let mut trak = TrakBox::default();
         trak.tkhd.track_id = track_id;
+        trak.tkhd.alternate_group = config.alternate_group;
+        trak.tkhd.set_enabled(config.is_default);
+        trak.tkhd.layer = config.layer as u16;
+        trak.tkhd.volume = config.volume;
+        trak.tkhd.matrix = config.matrix.clone();
         trak.mdia.mdhd.timescale = config.timescale;
         trak.mdia.mdhd.language = config.language.to_owned();
+        trak.mdia.elng = config
+            .extended_language
+            .as_ref()
+            .map(|extended_language| ElngBox {
+                extended_language: extended_language.to_owned(),
+                ..Default::default()
+            });
         trak.mdia.hdlr.handler_type = config.track_type.into();
         trak.mdia.minf.stbl.co64 = Some(Co64Box::default());
         match config.media_conf {
@@ -653,7 +1563,7 @@ impl Mp4TrackWriter {
                 trak.tkhd.set_width(avc_config.width);
                 trak.tkhd.set_height(avc_config.height);
 
-                let vmhd = VmhdBox::default();
+                let vmhd = VmhdBox::new(config);
                 trak.mdia.minf.vmhd = Some(vmhd);
 
                 let avc1 = Avc1Box::new(avc_config);
@@ -663,39 +1573,72 @@ impl Mp4TrackWriter {
                 trak.tkhd.set_width(hevc_config.width);
                 trak.tkhd.set_height(hevc_config.height);
 
-                let vmhd = VmhdBox::default();
+                let vmhd = VmhdBox::new(config);
                 trak.mdia.minf.vmhd = Some(vmhd);
 
                 let hev1 = Hev1Box::new(hevc_config);
                 trak.mdia.minf.stbl.stsd.hev1 = Some(hev1);
             }
-            MediaConfig::Vp9Config(ref config) => {
-                trak.tkhd.set_width(config.width);
-                trak.tkhd.set_height(config.height);
+            MediaConfig::Vp9Config(ref vp9_config) => {
+                trak.tkhd.set_width(vp9_config.width);
+                trak.tkhd.set_height(vp9_config.height);
 
-                trak.mdia.minf.stbl.stsd.vp09 = Some(Vp09Box::new(config));
+                let vmhd = VmhdBox::new(config);
+                trak.mdia.minf.vmhd = Some(vmhd);
+
+                trak.mdia.minf.stbl.stsd.vp09 = Some(Vp09Box::new(vp9_config));
             }
             MediaConfig::AacConfig(ref aac_config) => {
-                let smhd = SmhdBox::default();
+                let smhd = SmhdBox::new(config);
                 trak.mdia.minf.smhd = Some(smhd);
 
                 let mp4a = Mp4aBox::new(aac_config);
                 trak.mdia.minf.stbl.stsd.mp4a = Some(mp4a);
             }
+            MediaConfig::PcmConfig(ref pcm_config) => {
+                let smhd = SmhdBox::new(config);
+                trak.mdia.minf.smhd = Some(smhd);
+
+                let pcm = PcmBox::new(pcm_config);
+                trak.mdia.minf.stbl.stsd.pcm = Some(pcm);
+            }
+            MediaConfig::Ac4Config(ref ac4_config) => {
+                let smhd = SmhdBox::new(config);
+                trak.mdia.minf.smhd = Some(smhd);
+
+                trak.mdia.minf.stbl.stsd.ac4 = Some(Ac4Box::new(ac4_config));
+            }
             MediaConfig::TtxtConfig(ref _ttxt_config) => {
                 let tx3g = Tx3gBox::default();
                 trak.mdia.minf.stbl.stsd.tx3g = Some(tx3g);
             }
+            MediaConfig::StppConfig(ref stpp_config) => {
+                // ISO subtitles rather than the QuickTime ones of tx3g.
+                trak.mdia.hdlr.handler_type = HandlerType::Subtitle.to_fourcc();
+                trak.mdia.minf.sthd = Some(SthdBox::default());
+
+                trak.mdia.minf.stbl.stsd.stpp = Some(StppBox {
+                    data_reference_index: 1,
+                    namespace: stpp_config.namespace.clone(),
+                    schema_location: stpp_config.schema_location.clone(),
+                    auxiliary_mime_types: stpp_config.auxiliary_mime_types.clone(),
+                });
+            }
         }
         Ok(Mp4TrackWriter {
             trak,
             chunk_buffer: BytesMut::new(),
             sample_id: 1,
             duration_per_chunk: config.timescale, // 1 second
+            encoder_delay: config.encoder_delay,
             ..Self::default()
         })
     }
 
+    pub(crate) fn track_id(&self) -> u32 {
+        self.trak.tkhd.track_id
+    }
+
     fn update_sample_sizes(&mut self, size: u32) {
         if self.trak.mdia.minf.stbl.stsz.sample_count == 0 {
             if size == 0 {
@@ -764,6 +1707,10 @@ impl Mp4TrackWriter {
             self.trak.mdia.minf.stbl.ctts.as_mut().unwrap()
         };
 
+        if offset < 0 {
+            ctts.version = 1;
+        }
+
         if let Some(ref mut entry) = ctts.entries.last_mut() {
             if entry.sample_offset == offset {
                 entry.sample_count += 1;
@@ -786,13 +1733,14 @@ impl Mp4TrackWriter {
 
             stss.entries.push(self.sample_id);
         } else {
-            if !is_sync {
+            if is_sync {
                 return;
             }
 
-            // Create the stts box if not found and push the entry.
+            // An absent stss means every sample is a sync sample, so create it
+            // on the first non-sync sample with the sync samples written so far.
             let mut stss = StssBox::default();
-            stss.entries.push(self.sample_id);
+            stss.entries.extend(1..self.sample_id);
             self.trak.mdia.minf.stbl.stss = Some(stss);
         };
     }
@@ -840,6 +1788,104 @@ impl Mp4TrackWriter {
         Ok(self.trak.tkhd.duration)
     }
 
+    pub(crate) fn write_sample_with_timestamps<W: Write + Seek>(
+        &mut self,
+        writer: &mut W,
+        dts: u64,
+        pts: u64,
+        bytes: Bytes,
+        is_sync: bool,
+        movie_timescale: u32,
+    ) -> Result<u64> {
+        let rendering_offset = i32::try_from(pts as i64 - dts as i64)
+            .map_err(|_| Error::InvalidData("composition offset out of range"))?;
+
+        let mut track_dur = self.trak.tkhd.duration;
+        if let Some(ref pending) = self.pending_sample {
+            if dts < pending.start_time {
+                return Err(Error::InvalidData("decode timestamps must not decrease"));
+            }
+            let duration = u32::try_from(dts - pending.start_time)
+                .map_err(|_| Error::InvalidData("sample duration out of range"))?;
+            let mut sample = self.pending_sample.take().unwrap();
+            sample.duration = duration;
+            track_dur = self.write_sample(writer, &sample, movie_timescale)?;
+        }
+
+        self.pending_sample = Some(Mp4Sample {
+            start_time: dts,
+            duration: 0,
+            rendering_offset,
+            is_sync,
+            bytes,
+        });
+        Ok(track_dur)
+    }
+
+    pub(crate) fn has_pending_sample(&self) -> bool {
+        self.pending_sample.is_some()
+    }
+
+    /// Skip the priming samples with an edit over the rest of the media,
+    /// whose duration is in the movie timescale. In an fMP4 init segment the
+    /// media is still empty, so the edit has a zero duration, which spans all
+    /// of the fragments.
+    fn update_edit_list(&mut self, movie_timescale: u32) {
+        if self.encoder_delay == 0 {
+            return;
+        }
+        let media_timescale = self.trak.mdia.mdhd.timescale as u64;
+        let media_duration = self
+            .trak
+            .mdia
+            .mdhd
+            .duration
+            .saturating_sub(self.encoder_delay as u64);
+        let segment_duration = media_duration * movie_timescale as u64 / media_timescale;
+
+        let mut elst = ElstBox::default();
+        if segment_duration > u32::MAX as u64 {
+            elst.version = 1;
+        }
+        elst.entries.push(ElstEntry {
+            segment_duration,
+            media_time: self.encoder_delay as u64,
+            media_rate: 1,
+            media_rate_fraction: 0,
+        });
+        self.trak.edts = Some(EdtsBox { elst: Some(elst) });
+
+        self.trak.tkhd.duration = segment_duration;
+        self.trak.tkhd.version = if segment_duration > u32::MAX as u64 {
+            1
+        } else {
+            0
+        };
+    }
+
+    /// Write the last sample added with timestamps. Having no next DTS, it
+    /// takes the duration of the sample before it.
+    pub(crate) fn flush_pending_sample<W: Write + Seek>(
+        &mut self,
+        writer: &mut W,
+        movie_timescale: u32,
+    ) -> Result<u64> {
+        if let Some(mut sample) = self.pending_sample.take() {
+            sample.duration = self
+                .trak
+                .mdia
+                .minf
+                .stbl
+                .stts
+                .entries
+                .last()
+                .map(|entry| entry.sample_delta)
+                .unwrap_or(0);
+            self.write_sample(writer, &sample, movie_timescale)?;
+        }
+        Ok(self.trak.tkhd.duration)
+    }
+
     fn chunk_count(&self) -> u32 {
         let co64 = self.trak.mdia.minf.stbl.co64.as_ref().unwrap();
         co64.entries.len() as u32
@@ -896,8 +1942,13 @@ impl Mp4TrackWriter {
         }
     }
 
-    pub(crate) fn write_end<W: Write + Seek>(&mut self, writer: &mut W) -> Result<TrakBox> {
+    pub(crate) fn write_end<W: Write + Seek>(
+        &mut self,
+        writer: &mut W,
+        movie_timescale: u32,
+    ) -> Result<TrakBox> {
         self.write_chunk(writer)?;
+        self.update_edit_list(movie_timescale);
 
         let max_sample_size = self.max_sample_size();
         if let Some(ref mut mp4a) = self.trak.mdia.minf.stbl.stsd.mp4a {
@@ -165,6 +165,9 @@ const HANDLER_TYPE_AUDIO_FOURCC: [u8; 4] = [b's', b'o', b'u', b'n'];
 const HANDLER_TYPE_SUBTITLE: &str = "sbtl";
 const HANDLER_TYPE_SUBTITLE_FOURCC: [u8; 4] = [b's', b'b', b't', b'l'];
 
+const HANDLER_TYPE_ISO_SUBTITLE: &str = "subt";
+const HANDLER_TYPE_ISO_SUBTITLE_FOURCC: [u8; 4] = [b's', b'u', b'b', b't'];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrackType {
     Video,
@@ -189,7 +192,7 @@ impl TryFrom<&str> for TrackType {
         match handler {
             HANDLER_TYPE_VIDEO => Ok(TrackType::Video),
             HANDLER_TYPE_AUDIO => Ok(TrackType::Audio),
-            HANDLER_TYPE_SUBTITLE => Ok(TrackType::Subtitle),
+            HANDLER_TYPE_SUBTITLE | HANDLER_TYPE_ISO_SUBTITLE => Ok(TrackType::Subtitle),
             _ => Err(Error::InvalidData("unsupported handler type")),
         }
     }
@@ -201,7 +204,9 @@ impl TryFrom<&FourCC> for TrackType {
         match fourcc.value {
             HANDLER_TYPE_VIDEO_FOURCC => Ok(TrackType::Video),
             HANDLER_TYPE_AUDIO_FOURCC => Ok(TrackType::Audio),
-            HANDLER_TYPE_SUBTITLE_FOURCC => Ok(TrackType::Subtitle),
+            HANDLER_TYPE_SUBTITLE_FOURCC | HANDLER_TYPE_ISO_SUBTITLE_FOURCC => {
+                Ok(TrackType::Subtitle)
+            }
             _ => Err(Error::InvalidData("unsupported handler type")),
         }
     }
@@ -217,11 +222,118 @@ impl From<TrackType> for FourCC {
     }
 }
 
+/// The kind of media of a track, from the `handler_type` of its `hdlr` box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandlerType {
+    /// `vide`
+    Video,
+    /// `soun`
+    Audio,
+    /// `text`, QuickTime and 3GPP timed text.
+    Text,
+    /// `subt`, ISO subtitles such as TTML or WebVTT.
+    Subtitle,
+    /// `sbtl`, QuickTime subtitles.
+    QuickTimeSubtitle,
+    /// `clcp`, QuickTime closed captions.
+    ClosedCaption,
+    /// `meta`, timed metadata.
+    Metadata,
+    /// `hint`
+    Hint,
+    Other(FourCC),
+}
+
+impl HandlerType {
+    pub fn from_fourcc(fourcc: FourCC) -> Self {
+        match &fourcc.value {
+            b"vide" => HandlerType::Video,
+            b"soun" => HandlerType::Audio,
+            b"text" => HandlerType::Text,
+            b"subt" => HandlerType::Subtitle,
+            b"sbtl" => HandlerType::QuickTimeSubtitle,
+            b"clcp" => HandlerType::ClosedCaption,
+            b"meta" => HandlerType::Metadata,
+            b"hint" => HandlerType::Hint,
+            _ => HandlerType::Other(fourcc),
+        }
+    }
+
+    pub fn to_fourcc(&self) -> FourCC {
+        match self {
+            HandlerType::Video => FourCC::from(*b"vide"),
+            HandlerType::Audio => FourCC::from(*b"soun"),
+            HandlerType::Text => FourCC::from(*b"text"),
+            HandlerType::Subtitle => FourCC::from(*b"subt"),
+            HandlerType::QuickTimeSubtitle => FourCC::from(*b"sbtl"),
+            HandlerType::ClosedCaption => FourCC::from(*b"clcp"),
+            HandlerType::Metadata => FourCC::from(*b"meta"),
+            HandlerType::Hint => FourCC::from(*b"hint"),
+            HandlerType::Other(fourcc) => *fourcc,
+        }
+    }
+}
+
+impl fmt::Display for HandlerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_fourcc())
+    }
+}
+
+const AVIF_BRANDS: [[u8; 4]; 2] = [*b"avif", *b"avis"];
+const HEIF_BRANDS: [[u8; 4]; 9] = [
+    *b"mif1", *b"mif2", *b"msf1", *b"heic", *b"heix", *b"heim", *b"heis", *b"hevc", *b"hevx",
+];
+const FRAGMENTED_BRANDS: [[u8; 4]; 5] = [*b"dash", *b"msdh", *b"msix", *b"cmfc", *b"cmf2"];
+
+/// Kind of file carried by an ISO BMFF container, as classified from its brands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ContainerKind {
+    Mp4,
+    FragmentedMp4,
+    Heif,
+    Avif,
+}
+
+impl ContainerKind {
+    pub fn from_brands(major_brand: &FourCC, compatible_brands: &[FourCC]) -> ContainerKind {
+        let brands = || std::iter::once(major_brand).chain(compatible_brands.iter());
+        if brands().any(|b| AVIF_BRANDS.contains(&b.value)) {
+            ContainerKind::Avif
+        } else if brands().any(|b| HEIF_BRANDS.contains(&b.value)) {
+            ContainerKind::Heif
+        } else if brands().any(|b| FRAGMENTED_BRANDS.contains(&b.value)) {
+            ContainerKind::FragmentedMp4
+        } else {
+            ContainerKind::Mp4
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        matches!(self, ContainerKind::Heif | ContainerKind::Avif)
+    }
+}
+
+impl fmt::Display for ContainerKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ContainerKind::Mp4 => "MP4",
+            ContainerKind::FragmentedMp4 => "Fragmented MP4",
+            ContainerKind::Heif => "HEIF",
+            ContainerKind::Avif => "AVIF",
+        };
+        write!(f, "{s}")
+    }
+}
+
 const MEDIA_TYPE_H264: &str = "h264";
 const MEDIA_TYPE_H265: &str = "h265";
 const MEDIA_TYPE_VP9: &str = "vp9";
 const MEDIA_TYPE_AAC: &str = "aac";
 const MEDIA_TYPE_TTXT: &str = "ttxt";
+const MEDIA_TYPE_PCM: &str = "pcm";
+const MEDIA_TYPE_AC4: &str = "ac4";
+const MEDIA_TYPE_STPP: &str = "stpp";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MediaType {
@@ -230,6 +342,9 @@ pub enum MediaType {
     VP9,
     AAC,
     TTXT,
+    PCM,
+    AC4,
+    STPP,
 }
 
 impl fmt::Display for MediaType {
@@ -248,6 +363,9 @@ impl TryFrom<&str> for MediaType {
             MEDIA_TYPE_VP9 => Ok(MediaType::VP9),
             MEDIA_TYPE_AAC => Ok(MediaType::AAC),
             MEDIA_TYPE_TTXT => Ok(MediaType::TTXT),
+            MEDIA_TYPE_PCM => Ok(MediaType::PCM),
+            MEDIA_TYPE_AC4 => Ok(MediaType::AC4),
+            MEDIA_TYPE_STPP => Ok(MediaType::STPP),
             _ => Err(Error::InvalidData("unsupported media type")),
         }
     }
@@ -261,6 +379,9 @@ impl From<MediaType> for &str {
             MediaType::VP9 => MEDIA_TYPE_VP9,
             MediaType::AAC => MEDIA_TYPE_AAC,
             MediaType::TTXT => MEDIA_TYPE_TTXT,
+            MediaType::PCM => MEDIA_TYPE_PCM,
+            MediaType::AC4 => MEDIA_TYPE_AC4,
+            MediaType::STPP => MEDIA_TYPE_STPP,
         }
     }
 }
@@ -273,6 +394,9 @@ impl From<&MediaType> for &str {
             MediaType::VP9 => MEDIA_TYPE_VP9,
             MediaType::AAC => MEDIA_TYPE_AAC,
             MediaType::TTXT => MEDIA_TYPE_TTXT,
+            MediaType::PCM => MEDIA_TYPE_PCM,
+            MediaType::AC4 => MEDIA_TYPE_AC4,
+            MediaType::STPP => MEDIA_TYPE_STPP,
         }
     }
 }
@@ -576,12 +700,40 @@ pub struct AvcConfig {
 pub struct HevcConfig {
     pub width: u16,
     pub height: u16,
+    pub format: HevcFormat,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Vp9Config {
     pub width: u16,
     pub height: u16,
+    pub profile: u8,
+    pub level: u8,
+    pub bit_depth: u8,
+    pub chroma_subsampling: u8,
+    pub video_full_range_flag: bool,
+    pub color_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub codec_initialization_data: Vec<u8>,
+}
+
+impl Default for Vp9Config {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            profile: 0,
+            level: 0x1F,
+            bit_depth: 8,
+            chroma_subsampling: 0,
+            video_full_range_flag: false,
+            color_primaries: 0,
+            transfer_characteristics: 0,
+            matrix_coefficients: 0,
+            codec_initialization_data: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -590,6 +742,12 @@ pub struct AacConfig {
     pub profile: AudioObjectType,
     pub freq_index: SampleFreqIndex,
     pub chan_conf: ChannelConfig,
+
+    /// Output sampling frequency of the SBR extension; twice `freq_index`
+    /// if `None`.
+    pub extension_freq_index: Option<SampleFreqIndex>,
+    pub sbr_present: bool,
+    pub ps_present: bool,
 }
 
 impl Default for AacConfig {
@@ -599,13 +757,101 @@ impl Default for AacConfig {
             profile: AudioObjectType::AacLowComplexity,
             freq_index: SampleFreqIndex::Freq48000,
             chan_conf: ChannelConfig::Stereo,
+            extension_freq_index: None,
+            sbr_present: false,
+            ps_present: false,
         }
     }
 }
 
+impl AacConfig {
+    /// Sampling frequency after decoding, accounting for SBR upsampling.
+    pub fn output_sample_freq(&self) -> u32 {
+        match self.extension_freq_index {
+            Some(index) if self.sbr_present || self.ps_present => index.freq(),
+            None if self.sbr_present || self.ps_present => default_sbr_freq(self.freq_index.freq()),
+            _ => self.freq_index.freq(),
+        }
+    }
+}
+
+// The output rate of SBR over a core rate when no extension rate is given:
+// twice the core rate, or the core rate itself (downsampled SBR) above 48kHz,
+// as SBR output cannot exceed 96kHz.
+pub(crate) fn default_sbr_freq(core: u32) -> u32 {
+    if core <= 48000 {
+        core * 2
+    } else {
+        core
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct TtxtConfig {}
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PcmConfig {
+    pub sample_rate: u32,
+    pub channel_count: u16,
+    pub bits_per_sample: u16,
+    pub float: bool,
+    pub little_endian: bool,
+
+    /// Whether integer samples are signed; ignored for floating point.
+    pub signed: bool,
+}
+
+impl Default for PcmConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            channel_count: 2,
+            bits_per_sample: 16,
+            float: false,
+            little_endian: true,
+            signed: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ac4Config {
+    pub sample_rate: u32,
+    pub channel_count: u16,
+
+    /// The `ac4_dsi_v1` carried by the `dac4` box.
+    pub dsi: Vec<u8>,
+}
+
+impl Default for Ac4Config {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            channel_count: 2,
+            dsi: Vec::new(),
+        }
+    }
+}
+
+/// TTML subtitles, such as IMSC1 text profile documents.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StppConfig {
+    /// Space-separated XML namespaces the documents conform to.
+    pub namespace: String,
+    pub schema_location: String,
+    pub auxiliary_mime_types: String,
+}
+
+impl Default for StppConfig {
+    fn default() -> Self {
+        Self {
+            namespace: String::from("http://www.w3.org/ns/ttml"),
+            schema_location: String::new(),
+            auxiliary_mime_types: String::new(),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MediaConfig {
     AvcConfig(AvcConfig),
@@ -613,6 +859,9 @@ pub enum MediaConfig {
     Vp9Config(Vp9Config),
     AacConfig(AacConfig),
     TtxtConfig(TtxtConfig),
+    PcmConfig(PcmConfig),
+    Ac4Config(Ac4Config),
+    StppConfig(StppConfig),
 }
 
 #[derive(Debug)]
@@ -634,6 +883,26 @@ impl PartialEq for Mp4Sample {
     }
 }
 
+impl Mp4Sample {
+    /// The sample's timing in seconds of a track with `timescale` units per
+    /// second, with its sync flag and size, for logging; the raw units of
+    /// the [Display](fmt::Display) form if `timescale` is 0.
+    pub fn summary(&self, timescale: u32) -> String {
+        if timescale == 0 {
+            return self.to_string();
+        }
+        let seconds = |ticks: f64| ticks / timescale as f64;
+        format!(
+            "start_time {:.6}s, duration {:.6}s, rendering_offset {:.6}s, {}, length {}",
+            seconds(self.start_time as f64),
+            seconds(self.duration as f64),
+            seconds(self.rendering_offset as f64),
+            if self.is_sync { "sync" } else { "non-sync" },
+            self.bytes.len()
+        )
+    }
+}
+
 impl fmt::Display for Mp4Sample {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -648,6 +917,112 @@ impl fmt::Display for Mp4Sample {
     }
 }
 
+/// A sample whose data is borrowed from the buffer of an in-memory reader;
+/// see [Mp4Reader::read_sample_ref](crate::Mp4Reader::read_sample_ref).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mp4SampleRef<'a> {
+    pub start_time: u64,
+    pub duration: u32,
+    pub rendering_offset: i32,
+    pub is_sync: bool,
+    pub bytes: &'a [u8],
+}
+
+impl<'a> From<Mp4SampleRef<'a>> for Mp4Sample {
+    fn from(sample: Mp4SampleRef<'a>) -> Self {
+        Mp4Sample {
+            start_time: sample.start_time,
+            duration: sample.duration,
+            rendering_offset: sample.rendering_offset,
+            is_sync: sample.is_sync,
+            bytes: Bytes::copy_from_slice(sample.bytes),
+        }
+    }
+}
+
+/// The timing of every sample of a track as parallel arrays, indexed by
+/// sample id minus one; see [Mp4Track::timing_table](crate::Mp4Track::timing_table).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TimingTable {
+    /// Decode times, in track timescale units.
+    pub dts: Vec<u64>,
+    /// Composition times: the decode times plus the signed composition
+    /// offsets.
+    pub cts: Vec<i64>,
+    pub duration: Vec<u32>,
+}
+
+/// The HDR boxes of a video sample entry; see
+/// [Mp4Track::hdr_metadata](crate::Mp4Track::hdr_metadata).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HdrMetadata {
+    pub mdcv: Option<MdcvBox>,
+    pub clli: Option<ClliBox>,
+    pub amve: Option<AmveBox>,
+}
+
+/// The Common Encryption parameters of one sample (ISO/IEC 23001-7 7.2),
+/// as linked to it by the `saiz` and `saio` boxes; see
+/// [Mp4Reader::sample_encryption](crate::Mp4Reader::sample_encryption).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SampleEncryption {
+    /// The initialization vector, empty when the track uses a constant IV.
+    pub iv: Vec<u8>,
+    /// The clear and protected ranges of the sample, in order. Empty if the
+    /// whole sample is protected.
+    pub subsamples: Vec<SubsampleEncryption>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubsampleEncryption {
+    pub bytes_of_clear_data: u16,
+    pub bytes_of_protected_data: u32,
+}
+
+impl SampleEncryption {
+    /// Parse the auxiliary information of a sample whose IVs are `iv_size`
+    /// bytes long, as given by the track's `tenc` box. The subsamples are
+    /// present if the information is longer than the IV.
+    pub fn parse(data: &[u8], iv_size: u8) -> Result<Self> {
+        let iv_size = iv_size as usize;
+        if data.len() < iv_size {
+            return Err(Error::InvalidData(
+                "sample auxiliary information shorter than its IV",
+            ));
+        }
+        let (iv, rest) = data.split_at(iv_size);
+
+        let mut subsamples = Vec::new();
+        if !rest.is_empty() {
+            if rest.len() < 2 {
+                return Err(Error::InvalidData(
+                    "sample auxiliary information is truncated",
+                ));
+            }
+            let subsample_count = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let entries = &rest[2..];
+            if entries.len() < subsample_count * 6 {
+                return Err(Error::InvalidData(
+                    "sample auxiliary information is truncated",
+                ));
+            }
+            for entry in entries.chunks_exact(6).take(subsample_count) {
+                subsamples.push(SubsampleEncryption {
+                    bytes_of_clear_data: u16::from_be_bytes([entry[0], entry[1]]),
+                    bytes_of_protected_data: u32::from_be_bytes([
+                        entry[2], entry[3], entry[4], entry[5],
+                    ]),
+                });
+            }
+        }
+
+        Ok(SampleEncryption {
+            iv: iv.to_vec(),
+            subsamples,
+        })
+    }
+}
+
 pub fn creation_time(creation_time: u64) -> u64 {
     // convert from MP4 epoch (1904-01-01) to Unix epoch (1970-01-01)
     if creation_time >= 2082844800 {
@@ -662,7 +1037,11 @@ pub enum DataType {
     Binary = 0x000000,
     Text = 0x000001,
     Image = 0x00000D,
+    Png = 0x00000E,
     TempoCpil = 0x000015,
+    BeUnsignedInt = 0x000016,
+    Float32 = 0x000017,
+    Float64 = 0x000018,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -679,7 +1058,11 @@ impl TryFrom<u32> for DataType {
             0x000000 => Ok(DataType::Binary),
             0x000001 => Ok(DataType::Text),
             0x00000D => Ok(DataType::Image),
+            0x00000E => Ok(DataType::Png),
             0x000015 => Ok(DataType::TempoCpil),
+            0x000016 => Ok(DataType::BeUnsignedInt),
+            0x000017 => Ok(DataType::Float32),
+            0x000018 => Ok(DataType::Float64),
             _ => Err(Error::InvalidData("invalid data type")),
         }
     }
@@ -691,21 +1074,149 @@ pub enum MetadataKey {
     Year,
     Poster,
     Summary,
+    Location,
+}
+
+/// Image format of a cover art, from the type code of its `data` box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverArtFormat {
+    Jpeg,
+    Png,
+    Unknown(u32),
+}
+
+impl From<u32> for CoverArtFormat {
+    fn from(type_code: u32) -> Self {
+        match type_code {
+            0x00000D => CoverArtFormat::Jpeg,
+            0x00000E => CoverArtFormat::Png,
+            _ => CoverArtFormat::Unknown(type_code),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoverArt<'a> {
+    pub format: CoverArtFormat,
+    pub data: &'a [u8],
+}
+
+/// A point on Earth, as stored in ISO 6709 strings such as
+/// `+37.7749-122.4194+012.345/`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoLocation {
+    /// Degrees north of the equator; negative in the south.
+    pub lat: f64,
+    /// Degrees east of the prime meridian; negative in the west.
+    pub lon: f64,
+    /// Meters above the WGS 84 ellipsoid, if known.
+    pub altitude: Option<f64>,
+}
+
+impl GeoLocation {
+    /// Parse an ISO 6709 point, with angles in degrees, in degrees and
+    /// minutes or in degrees, minutes and seconds. A trailing CRS
+    /// specification is ignored.
+    pub fn from_iso6709(s: &str) -> Option<Self> {
+        let s = s.trim_end_matches('/');
+        let s = s.find("CRS").map_or(s, |crs| &s[..crs]);
+
+        let mut parts = Vec::new();
+        let mut part_start = 0;
+        for (i, c) in s.char_indices().skip(1) {
+            if c == '+' || c == '-' {
+                parts.push(&s[part_start..i]);
+                part_start = i;
+            }
+        }
+        parts.push(&s[part_start..]);
+
+        let (lat, lon, altitude) = match parts[..] {
+            [lat, lon] => (lat, lon, None),
+            [lat, lon, altitude] => (lat, lon, Some(altitude)),
+            _ => return None,
+        };
+        let lat = iso6709_angle(lat, 2).filter(|lat| lat.abs() <= 90.0)?;
+        let lon = iso6709_angle(lon, 3).filter(|lon| lon.abs() <= 180.0)?;
+        let altitude = match altitude {
+            Some(altitude) => Some(altitude.parse().ok()?),
+            None => None,
+        };
+        Some(GeoLocation { lat, lon, altitude })
+    }
+
+    /// The point as an ISO 6709 string in degrees, the form iOS writes.
+    pub fn to_iso6709(&self) -> String {
+        let mut s = format!("{:+08.4}{:+09.4}", self.lat, self.lon);
+        if let Some(altitude) = self.altitude {
+            s += &format!("{:+.3}", altitude);
+        }
+        s.push('/');
+        s
+    }
+}
+
+// A signed ISO 6709 angle whose degrees take `degree_digits` digits,
+// followed by as many as two pairs of digits of minutes and seconds.
+fn iso6709_angle(s: &str, degree_digits: usize) -> Option<f64> {
+    let (sign, digits) = match s.as_bytes().first()? {
+        b'+' => (1.0, &s[1..]),
+        b'-' => (-1.0, &s[1..]),
+        _ => return None,
+    };
+    let int_len = digits.find('.').unwrap_or(digits.len());
+    if !digits.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return None;
+    }
+    let value: f64 = digits.parse().ok()?;
+    let degrees = match int_len.checked_sub(degree_digits)? {
+        0 => value,
+        2 => {
+            let degrees = (value / 100.0).trunc();
+            degrees + (value - degrees * 100.0) / 60.0
+        }
+        4 => {
+            let degrees = (value / 10000.0).trunc();
+            let minutes = ((value - degrees * 10000.0) / 100.0).trunc();
+            degrees + minutes / 60.0 + (value - degrees * 10000.0 - minutes * 100.0) / 3600.0
+        }
+        _ => return None,
+    };
+    Some(sign * degrees)
+}
+
+/// How the items of a `meta` box are laid out, as named by its `hdlr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataLayout {
+    /// iTunes-style items, typed by four-character codes such as `©nam`.
+    Mdir,
+    /// QuickTime key-value items, typed by their index in a `keys` table.
+    Mdta,
+    /// A handler the crate does not interpret.
+    Unknown(FourCC),
 }
 
 pub trait Metadata<'a> {
     /// The video's title
-    fn title(&self) -> Option<Cow<str>>;
+    fn title(&self) -> Option<Cow<'_, str>>;
     /// The video's release year
     fn year(&self) -> Option<u32>;
     /// The video's poster (cover art)
     fn poster(&self) -> Option<&[u8]>;
     /// The video's summary
-    fn summary(&self) -> Option<Cow<str>>;
+    fn summary(&self) -> Option<Cow<'_, str>>;
+    /// All cover images, in file order
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        Vec::new()
+    }
+    /// Where the video was recorded
+    fn location(&self) -> Option<GeoLocation> {
+        None
+    }
 }
 
 impl<'a, T: Metadata<'a>> Metadata<'a> for &'a T {
-    fn title(&self) -> Option<Cow<str>> {
+    fn title(&self) -> Option<Cow<'_, str>> {
         (**self).title()
     }
 
@@ -717,13 +1228,21 @@ impl<'a, T: Metadata<'a>> Metadata<'a> for &'a T {
         (**self).poster()
     }
 
-    fn summary(&self) -> Option<Cow<str>> {
+    fn summary(&self) -> Option<Cow<'_, str>> {
         (**self).summary()
     }
+
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        (**self).cover_art()
+    }
+
+    fn location(&self) -> Option<GeoLocation> {
+        (**self).location()
+    }
 }
 
 impl<'a, T: Metadata<'a>> Metadata<'a> for Option<T> {
-    fn title(&self) -> Option<Cow<str>> {
+    fn title(&self) -> Option<Cow<'_, str>> {
         self.as_ref().and_then(|t| t.title())
     }
 
@@ -735,7 +1254,15 @@ impl<'a, T: Metadata<'a>> Metadata<'a> for Option<T> {
         self.as_ref().and_then(|t| t.poster())
     }
 
-    fn summary(&self) -> Option<Cow<str>> {
+    fn summary(&self) -> Option<Cow<'_, str>> {
         self.as_ref().and_then(|t| t.summary())
     }
+
+    fn cover_art(&self) -> Vec<CoverArt<'_>> {
+        self.as_ref().map(|t| t.cover_art()).unwrap_or_default()
+    }
+
+    fn location(&self) -> Option<GeoLocation> {
+        self.as_ref().and_then(|t| t.location())
+    }
 }
@@ -1,6 +1,7 @@
 use byteorder::{BigEndian, WriteBytesExt};
-use std::io::{Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
+use crate::mp4box::ilst::IlstItemBox;
 use crate::mp4box::*;
 use crate::track::Mp4TrackWriter;
 use crate::*;
@@ -13,6 +14,19 @@ pub struct Mp4Config {
     pub timescale: u32,
 }
 
+/// Options of how [Mp4Writer] lays out the file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Mp4WriterConfig {
+    /// The bytes, header included, of a `free` box written ahead of the
+    /// `mdat` to hold the `moov`, so that it comes first in the file and
+    /// [repack::update_moov](crate::repack::update_moov) can later grow it
+    /// into the rest of the box without moving anything else. If the `moov`
+    /// does not fit, it is written after the `mdat` as usual and the box is
+    /// left as padding. Sizes below the 8-byte header are rounded up to it;
+    /// 0, the default, reserves nothing.
+    pub reserved_free_space: u64,
+}
+
 #[derive(Debug)]
 pub struct Mp4Writer<W> {
     writer: W,
@@ -20,6 +34,11 @@ pub struct Mp4Writer<W> {
     mdat_pos: u64,
     timescale: u32,
     duration: u64,
+    copyright: Option<CprtBox>,
+    reserved_pos: u64,
+    reserved_size: u64,
+    meta: Option<MetaBox>,
+    location: Option<GeoLocation>,
 }
 
 impl<W> Mp4Writer<W> {
@@ -60,13 +79,36 @@ impl<W> Mp4Writer<W> {
 }
 
 impl<W: Write + Seek> Mp4Writer<W> {
-    pub fn write_start(mut writer: W, config: &Mp4Config) -> Result<Self> {
+    pub fn write_start(writer: W, config: &Mp4Config) -> Result<Self> {
+        Self::write_start_with_config(writer, config, &Mp4WriterConfig::default())
+    }
+
+    /// Like [Mp4Writer::write_start], laying out the file as `writer_config`
+    /// asks.
+    pub fn write_start_with_config(
+        mut writer: W,
+        config: &Mp4Config,
+        writer_config: &Mp4WriterConfig,
+    ) -> Result<Self> {
         let ftyp = FtypBox {
             major_brand: config.major_brand,
             minor_version: config.minor_version,
             compatible_brands: config.compatible_brands.clone(),
         };
-        ftyp.write_box(&mut writer)?;
+        write_box_checked(&ftyp, &mut writer)?;
+
+        let reserved_pos = writer.stream_position()?;
+        let reserved_size = match writer_config.reserved_free_space {
+            0 => 0,
+            bytes => bytes.max(HEADER_SIZE),
+        };
+        if reserved_size > 0 {
+            BoxHeader::new(BoxType::FreeBox, reserved_size).write(&mut writer)?;
+            io::copy(
+                &mut io::repeat(0).take(reserved_size - HEADER_SIZE),
+                &mut writer,
+            )?;
+        }
 
         // TODO largesize
         let mdat_pos = writer.stream_position()?;
@@ -82,16 +124,60 @@ impl<W: Write + Seek> Mp4Writer<W> {
             mdat_pos,
             timescale,
             duration,
+            copyright: None,
+            reserved_pos,
+            reserved_size,
+            meta: None,
+            location: None,
         })
     }
 
+    /// Set the copyright notice written to `moov/udta/cprt`, in the language
+    /// with ISO 639-2/T code `language` such as `eng`.
+    pub fn set_copyright(&mut self, language: &str, notice: &str) {
+        self.copyright = Some(CprtBox::new(language, notice));
+    }
+
+    /// Set the location written as the `©xyz` item of `moov/udta/meta`.
+    pub fn set_location(&mut self, location: GeoLocation) {
+        self.location = Some(location);
+    }
+
+    /// Set the file-level `meta` box, written after the `moov`, such as one
+    /// read from [Mp4Reader::meta](crate::Mp4Reader::meta) to copy it.
+    pub fn set_meta(&mut self, meta: MetaBox) {
+        self.meta = Some(meta);
+    }
+
+    /// Add a track with the next free track ID, one past the largest in use.
     pub fn add_track(&mut self, config: &TrackConfig) -> Result<()> {
-        let track_id = self.tracks.len() as u32 + 1;
+        let track_id = self.next_track_id();
+        self.add_track_with_id(track_id, config)
+    }
+
+    /// Add a track with an explicit track ID, which must be non-zero and not
+    /// already in use.
+    pub fn add_track_with_id(&mut self, track_id: u32, config: &TrackConfig) -> Result<()> {
+        if track_id == 0 {
+            return Err(Error::InvalidData("illegal track id 0"));
+        }
+        if self.tracks.iter().any(|track| track.track_id() == track_id) {
+            return Err(Error::InvalidData("duplicate track id"));
+        }
         let track = Mp4TrackWriter::new(track_id, config)?;
         self.tracks.push(track);
         Ok(())
     }
 
+    fn next_track_id(&self) -> u32 {
+        self.tracks
+            .iter()
+            .map(|track| track.track_id())
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
     fn update_durations(&mut self, track_dur: u64) {
         if track_dur > self.duration {
             self.duration = track_dur;
@@ -99,12 +185,54 @@ impl<W: Write + Seek> Mp4Writer<W> {
     }
 
     pub fn write_sample(&mut self, track_id: u32, sample: &Mp4Sample) -> Result<()> {
-        if track_id == 0 {
+        let track_dur = if let Some(track) = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id() == track_id)
+        {
+            if track.has_pending_sample() {
+                return Err(Error::InvalidData(
+                    "cannot mix write_sample and add_sample_with_timestamps in a track",
+                ));
+            }
+            track.write_sample(&mut self.writer, sample, self.timescale)?
+        } else {
             return Err(Error::TrakNotFound(track_id));
-        }
+        };
 
-        let track_dur = if let Some(ref mut track) = self.tracks.get_mut(track_id as usize - 1) {
-            track.write_sample(&mut self.writer, sample, self.timescale)?
+        self.update_durations(track_dur);
+
+        Ok(())
+    }
+
+    /// Add a sample by its decode and presentation timestamps in the track's
+    /// timescale, instead of by duration and rendering offset.
+    ///
+    /// Durations are the differences between consecutive DTS values, so each
+    /// sample is written once the next one arrives; the last sample takes the
+    /// duration of the one before it. Timestamps are relative to the DTS of
+    /// the first sample, which is expected to be 0.
+    pub fn add_sample_with_timestamps(
+        &mut self,
+        track_id: u32,
+        dts: u64,
+        pts: u64,
+        bytes: Bytes,
+        is_sync: bool,
+    ) -> Result<()> {
+        let track_dur = if let Some(track) = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id() == track_id)
+        {
+            track.write_sample_with_timestamps(
+                &mut self.writer,
+                dts,
+                pts,
+                bytes,
+                is_sync,
+                self.timescale,
+            )?
         } else {
             return Err(Error::TrakNotFound(track_id));
         };
@@ -117,7 +245,7 @@ impl<W: Write + Seek> Mp4Writer<W> {
     fn update_mdat_size(&mut self) -> Result<()> {
         let mdat_end = self.writer.stream_position()?;
         let mdat_size = mdat_end - self.mdat_pos;
-        if mdat_size > std::u32::MAX as u64 {
+        if mdat_size > u32::MAX as u64 {
             self.writer.seek(SeekFrom::Start(self.mdat_pos))?;
             self.writer.write_u32::<BigEndian>(1)?;
             self.writer.seek(SeekFrom::Start(self.mdat_pos + 8))?;
@@ -133,17 +261,442 @@ impl<W: Write + Seek> Mp4Writer<W> {
     pub fn write_end(&mut self) -> Result<()> {
         let mut moov = MoovBox::default();
 
+        let mut duration = self.duration;
         for track in self.tracks.iter_mut() {
-            moov.traks.push(track.write_end(&mut self.writer)?);
+            duration = duration.max(track.flush_pending_sample(&mut self.writer, self.timescale)?);
+        }
+        self.update_durations(duration);
+
+        for track in self.tracks.iter_mut() {
+            moov.traks
+                .push(track.write_end(&mut self.writer, self.timescale)?);
         }
         self.update_mdat_size()?;
 
+        // Edits for encoder delays shorten the tracks.
+        self.duration = moov
+            .traks
+            .iter()
+            .map(|trak| trak.tkhd.duration)
+            .max()
+            .unwrap_or(0);
+
         moov.mvhd.timescale = self.timescale;
+        moov.mvhd.next_track_id = self.next_track_id();
         moov.mvhd.duration = self.duration;
         if moov.mvhd.duration > (u32::MAX as u64) {
             moov.mvhd.version = 1
         }
-        moov.write_box(&mut self.writer)?;
+        if self.copyright.is_some() || self.location.is_some() {
+            let meta = self.location.map(|location| {
+                let data = DataBox {
+                    data: location.to_iso6709().into_bytes(),
+                    data_type: DataType::Text,
+                };
+                let mut ilst = IlstBox::default();
+                ilst.items
+                    .insert(MetadataKey::Location, IlstItemBox { data });
+                MetaBox::Mdir { ilst: Some(ilst) }
+            });
+            moov.udta = Some(UdtaBox {
+                meta,
+                cprt: self.copyright.clone(),
+                ..Default::default()
+            });
+        }
+
+        let moov_size = moov.box_size();
+        let room = self.reserved_size;
+        if room > 0 && (moov_size == room || moov_size + HEADER_SIZE <= room) {
+            let end = self.writer.stream_position()?;
+            self.writer.seek(SeekFrom::Start(self.reserved_pos))?;
+            write_box_checked(&moov, &mut self.writer)?;
+            if moov_size < room {
+                BoxHeader::new(BoxType::FreeBox, room - moov_size).write(&mut self.writer)?;
+            }
+            self.writer.seek(SeekFrom::Start(end))?;
+        } else {
+            write_box_checked(&moov, &mut self.writer)?;
+        }
+        if let Some(meta) = &self.meta {
+            write_box_checked(meta, &mut self.writer)?;
+        }
+        Ok(())
+    }
+}
+
+const SYNC_SAMPLE_FLAGS: u32 = 0x02000000;
+const NON_SYNC_SAMPLE_FLAGS: u32 = 0x01010000;
+
+#[derive(Debug, Default)]
+pub(crate) struct FragmentTrack {
+    track_id: u32,
+    base_media_decode_time: u64,
+    trun: TrunBox,
+    data: Vec<u8>,
+
+    // Written to the tfhd unless it is 1, the default of the trex.
+    sample_description_index: u32,
+}
+
+impl FragmentTrack {
+    pub(crate) fn new(track_id: u32, base_media_decode_time: u64) -> Self {
+        FragmentTrack {
+            track_id,
+            base_media_decode_time,
+            sample_description_index: 1,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn set_sample_description_index(&mut self, index: u32) {
+        self.sample_description_index = index;
+    }
+
+    pub(crate) fn add_sample(&mut self, sample: &Mp4Sample) {
+        let trun = &mut self.trun;
+        trun.sample_count += 1;
+        trun.sample_durations.push(sample.duration);
+        trun.sample_sizes.push(sample.bytes.len() as u32);
+        trun.sample_flags.push(if sample.is_sync {
+            SYNC_SAMPLE_FLAGS
+        } else {
+            NON_SYNC_SAMPLE_FLAGS
+        });
+        trun.sample_cts.push(sample.rendering_offset as u32);
+        if sample.rendering_offset < 0 {
+            trun.version = 1;
+        }
+        self.data.extend_from_slice(&sample.bytes);
+    }
+
+    fn take_traf(&mut self) -> TrafBox {
+        let mut trun = std::mem::take(&mut self.trun);
+        trun.flags = TrunBox::FLAG_DATA_OFFSET
+            | TrunBox::FLAG_SAMPLE_DURATION
+            | TrunBox::FLAG_SAMPLE_SIZE
+            | TrunBox::FLAG_SAMPLE_FLAGS;
+        if trun.sample_cts.iter().any(|cts| *cts != 0) {
+            trun.flags |= TrunBox::FLAG_SAMPLE_CTS;
+        }
+        trun.data_offset = Some(0);
+
+        let mut tfhd = TfhdBox {
+            flags: TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+            track_id: self.track_id,
+            ..Default::default()
+        };
+        if self.sample_description_index != 1 {
+            tfhd.flags |= TfhdBox::FLAG_SAMPLE_DESCRIPTION_INDEX;
+            tfhd.sample_description_index = Some(self.sample_description_index);
+        }
+
+        let traf = TrafBox {
+            tfhd,
+            tfdt: Some(TfdtBox {
+                version: 1,
+                flags: 0,
+                base_media_decode_time: self.base_media_decode_time,
+            }),
+            trun: Some(trun),
+            subs: None,
+            saiz: None,
+            saio: None,
+        };
+        for duration in traf.trun.as_ref().unwrap().sample_durations.iter() {
+            self.base_media_decode_time += *duration as u64;
+        }
+        traf
+    }
+}
+
+// Write a `moof` and its `mdat` with the pending samples of `tracks`,
+// returning whether there were any.
+pub(crate) fn write_fragment<W: Write>(
+    writer: &mut W,
+    sequence_number: u32,
+    tracks: &mut [FragmentTrack],
+) -> Result<bool> {
+    let mut moof = MoofBox {
+        mfhd: MfhdBox {
+            sequence_number,
+            ..Default::default()
+        },
+        trafs: Vec::new(),
+    };
+    let mut data = Vec::new();
+    for track in tracks.iter_mut() {
+        if track.trun.sample_count == 0 {
+            continue;
+        }
+        moof.trafs.push(track.take_traf());
+        data.push(std::mem::take(&mut track.data));
+    }
+    if moof.trafs.is_empty() {
+        return Ok(false);
+    }
+
+    let data_size = data.iter().map(|d| d.len() as u64).sum::<u64>();
+    let mut mdat_size = HEADER_SIZE + data_size;
+    if mdat_size > u32::MAX as u64 {
+        // largesize
+        mdat_size += 8;
+    }
+    let mut data_offset = moof.box_size() + mdat_size - data_size;
+    for (traf, track_data) in moof.trafs.iter_mut().zip(data.iter()) {
+        if data_offset > i32::MAX as u64 {
+            return Err(Error::InvalidData("trun data offset overflow"));
+        }
+        traf.trun.as_mut().unwrap().data_offset = Some(data_offset as i32);
+        data_offset += track_data.len() as u64;
+    }
+
+    write_box_checked(&moof, writer)?;
+    BoxHeader::new(BoxType::MdatBox, mdat_size).write(writer)?;
+    for track_data in data.iter() {
+        writer.write_all(track_data)?;
+    }
+    Ok(true)
+}
+
+/// Writer for fragmented MP4 files: an init segment (`ftyp` and `moov` with
+/// `mvex`) followed by `moof`/`mdat` pairs.
+#[derive(Debug)]
+pub struct FMp4Writer<W> {
+    writer: W,
+    timescale: u32,
+    pending_tracks: Vec<Mp4TrackWriter>,
+    tracks: Vec<FragmentTrack>,
+    init_written: bool,
+    fragment_open: bool,
+    sequence_number: u32,
+}
+
+impl<W> FMp4Writer<W> {
+    /// Consume self, returning the inner writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Seek> FMp4Writer<W> {
+    pub fn write_start(mut writer: W, config: &Mp4Config) -> Result<Self> {
+        let ftyp = FtypBox {
+            major_brand: config.major_brand,
+            minor_version: config.minor_version,
+            compatible_brands: config.compatible_brands.clone(),
+        };
+        write_box_checked(&ftyp, &mut writer)?;
+
+        Ok(Self {
+            writer,
+            timescale: config.timescale,
+            pending_tracks: Vec::new(),
+            tracks: Vec::new(),
+            init_written: false,
+            fragment_open: false,
+            sequence_number: 1,
+        })
+    }
+
+    /// Add a track. Tracks must be added before the first fragment is started,
+    /// since the init segment is written at that point.
+    pub fn add_track(&mut self, config: &TrackConfig) -> Result<()> {
+        if self.init_written {
+            return Err(Error::InvalidData(
+                "cannot add a track after the init segment is written",
+            ));
+        }
+        let track_id = self.pending_tracks.len() as u32 + 1;
+        self.pending_tracks
+            .push(Mp4TrackWriter::new(track_id, config)?);
+        self.tracks.push(FragmentTrack::new(track_id, 0));
+        Ok(())
+    }
+
+    fn write_init(&mut self) -> Result<()> {
+        if self.init_written {
+            return Ok(());
+        }
+
+        let mut moov = MoovBox::default();
+        moov.mvhd.timescale = self.timescale;
+        let mut mvex = MvexBox::default();
+        for track in self.pending_tracks.iter_mut() {
+            let trak = track.write_end(&mut self.writer, self.timescale)?;
+            mvex.trexs.push(TrexBox {
+                track_id: trak.tkhd.track_id,
+                default_sample_description_index: 1,
+                ..Default::default()
+            });
+            moov.traks.push(trak);
+        }
+        moov.mvhd.next_track_id = moov.traks.len() as u32 + 1;
+        moov.mvex = Some(mvex);
+        write_box_checked(&moov, &mut self.writer)?;
+
+        self.pending_tracks.clear();
+        self.init_written = true;
+        Ok(())
+    }
+
+    fn write_fragment(&mut self) -> Result<()> {
+        if !self.fragment_open {
+            return Ok(());
+        }
+        self.fragment_open = false;
+
+        if write_fragment(&mut self.writer, self.sequence_number, &mut self.tracks)? {
+            self.sequence_number += 1;
+        }
+        Ok(())
+    }
+
+    /// Finish the current fragment, if any, and start a new one. The init
+    /// segment is written before the first fragment.
+    pub fn start_fragment(&mut self) -> Result<()> {
+        self.write_init()?;
+        self.write_fragment()?;
+        self.fragment_open = true;
         Ok(())
     }
+
+    /// Add a sample to the current fragment, starting one if needed.
+    pub fn add_sample(&mut self, track_id: u32, sample: &Mp4Sample) -> Result<()> {
+        if !self.fragment_open {
+            self.start_fragment()?;
+        }
+
+        if let Some(track) = self.tracks.iter_mut().find(|t| t.track_id == track_id) {
+            track.add_sample(sample);
+            Ok(())
+        } else {
+            Err(Error::TrakNotFound(track_id))
+        }
+    }
+
+    pub fn write_end(&mut self) -> Result<()> {
+        self.write_init()?;
+        self.write_fragment()
+    }
+}
+
+impl<W: Read + Write + Seek> FMp4Writer<W> {
+    /// Open an existing fragmented file for appending further fragments.
+    ///
+    /// The init segment and the existing fragments are read to recover the
+    /// tracks, the next `mfhd` sequence number and the base media decode time
+    /// each track continues from. The writer is left positioned at the end of
+    /// the file.
+    ///
+    /// A last fragment cut short, as by a crash while recording, is dropped:
+    /// it is covered with a `free` box, and new fragments follow the
+    /// complete ones after it.
+    pub fn open_append(mut writer: W) -> Result<Self> {
+        let size = writer.seek(SeekFrom::End(0))?;
+        writer.seek(SeekFrom::Start(0))?;
+
+        let (tracks, sequence_number, timescale, end) = {
+            let (end, moof_count) = complete_fragments_end(&mut writer, size)?;
+            writer.seek(SeekFrom::Start(0))?;
+            let mp4 = Mp4Reader::read_header_recover(&mut writer, size)?;
+            let mvex = match mp4.moov.mvex {
+                Some(ref mvex) => mvex,
+                None => return Err(Error::BoxNotFound(BoxType::MvexBox)),
+            };
+
+            let mut tracks: Vec<FragmentTrack> = mp4
+                .moov
+                .traks
+                .iter()
+                .map(|trak| FragmentTrack::new(trak.tkhd.track_id, 0))
+                .collect();
+            let moofs = &mp4.moofs[..moof_count.min(mp4.moofs.len())];
+            for traf in moofs.iter().flat_map(|moof| moof.trafs.iter()) {
+                let track = match tracks.iter_mut().find(|t| t.track_id == traf.tfhd.track_id) {
+                    Some(track) => track,
+                    None => return Err(Error::TrakNotFound(traf.tfhd.track_id)),
+                };
+                if let Some(ref tfdt) = traf.tfdt {
+                    track.base_media_decode_time = tfdt.base_media_decode_time;
+                }
+                if let Some(ref trun) = traf.trun {
+                    if TrunBox::FLAG_SAMPLE_DURATION & trun.flags != 0 {
+                        for duration in trun.sample_durations.iter() {
+                            track.base_media_decode_time += *duration as u64;
+                        }
+                    } else {
+                        let default_sample_duration = traf
+                            .tfhd
+                            .default_sample_duration
+                            .or_else(|| {
+                                mvex.trex(track.track_id)
+                                    .map(|trex| trex.default_sample_duration)
+                            })
+                            .unwrap_or(0);
+                        track.base_media_decode_time +=
+                            trun.sample_count as u64 * default_sample_duration as u64;
+                    }
+                }
+            }
+
+            let sequence_number = moofs
+                .last()
+                .map(|moof| moof.mfhd.sequence_number + 1)
+                .unwrap_or(1);
+            (tracks, sequence_number, mp4.moov.mvhd.timescale, end)
+        };
+
+        if end < size {
+            let free_size = (size - end).max(HEADER_SIZE);
+            writer.seek(SeekFrom::Start(end))?;
+            BoxHeader::new(BoxType::FreeBox, free_size).write(&mut writer)?;
+            writer.seek(SeekFrom::Start(end + free_size))?;
+        } else {
+            writer.seek(SeekFrom::End(0))?;
+        }
+
+        Ok(Self {
+            writer,
+            timescale,
+            pending_tracks: Vec::new(),
+            tracks,
+            init_written: true,
+            fragment_open: false,
+            sequence_number,
+        })
+    }
+}
+
+// The end of the last fragment in the first `size` bytes of `reader` whose
+// `moof` and `mdat` are both complete, or of the boxes before the first
+// fragment if there is none, and the number of `moof`s up to there.
+fn complete_fragments_end<R: Read + Seek>(reader: &mut R, size: u64) -> Result<(u64, usize)> {
+    let mut end = 0;
+    let mut moofs = 0;
+    let mut pending_moofs = 0;
+    let mut offset = 0;
+    while size.saturating_sub(offset) >= HEADER_SIZE {
+        reader.seek(SeekFrom::Start(offset))?;
+        let header = match BoxHeader::read_at(reader) {
+            Ok(header) => header,
+            Err(_) => break,
+        };
+        let body = match header.body_range()? {
+            Some(body) if body.end <= size => body,
+            _ => break,
+        };
+        match header.name {
+            BoxType::MoofBox => pending_moofs += 1,
+            BoxType::MdatBox => {
+                moofs += pending_moofs;
+                pending_moofs = 0;
+                end = body.end;
+            }
+            _ if pending_moofs == 0 => end = body.end,
+            _ => {}
+        }
+        offset = body.end;
+    }
+    Ok((end, moofs))
 }
@@ -1,9 +1,10 @@
 use mp4::{
-    AudioObjectType, AvcProfile, ChannelConfig, MediaType, Metadata, Mp4Reader, SampleFreqIndex,
-    TrackType,
+    AudioObjectType, AvcProfile, ChannelConfig, CoverArtFormat, MediaType, Metadata, Mp4Reader,
+    SampleFreqIndex, TrackType, WriteBox,
 };
+use std::convert::TryInto;
 use std::fs::{self, File};
-use std::io::BufReader;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
 use std::time::Duration;
 
 #[test]
@@ -100,7 +101,8 @@ fn test_read_mp4() {
     assert_eq!(track1.width(), 320);
     assert_eq!(track1.height(), 240);
     assert_eq!(track1.bitrate(), 150200);
-    assert_eq!(track1.frame_rate(), 25.00);
+    assert_eq!(track1.frame_rate(), Some(25.00));
+    assert!(track1.is_constant_frame_rate());
 
     // track #2
     let track2 = mp4.tracks().get(&2).unwrap();
@@ -116,6 +118,11 @@ fn test_read_mp4() {
     );
     assert_eq!(track2.channel_config().unwrap(), ChannelConfig::Mono);
     assert_eq!(track2.bitrate(), 67695);
+    assert_eq!(track2.sample_rate().unwrap(), 48000);
+    assert_eq!(track2.channel_count().unwrap(), 1);
+    assert_eq!(track2.bits_per_sample().unwrap(), 16);
+    assert!(track1.sample_rate().is_err());
+    assert_eq!(track2.frame_rate(), None);
 }
 
 #[test]
@@ -149,8 +156,31 @@ fn test_read_extended_audio_object_type() {
             .freq_index,
         15
     );
-    assert_eq!(track.channel_config().unwrap(), ChannelConfig::Stereo);
+    // The channel count comes from the ALSSpecificConfig instead.
+    assert!(track.channel_config().is_err());
     assert_eq!(track.bitrate(), 839250);
+    assert_eq!(track.channel_count().unwrap(), 1);
+
+    // The AudioSpecificConfig, with its ALSSpecificConfig, is written back
+    // unchanged.
+    let data = fs::read("tests/samples/extended_audio_object_type.mp4").unwrap();
+    let esds = track
+        .trak
+        .mdia
+        .minf
+        .stbl
+        .stsd
+        .mp4a
+        .as_ref()
+        .unwrap()
+        .esds
+        .as_ref()
+        .unwrap();
+    let mut buf = Vec::new();
+    esds.write_box(&mut buf).unwrap();
+    assert_eq!(buf.len(), 194);
+    let offset = data.windows(4).position(|w| w == b"esds").unwrap() - 4;
+    assert_eq!(&buf[..], &data[offset..offset + buf.len()]);
 }
 
 fn get_reader(path: &str) -> Mp4Reader<BufReader<File>> {
@@ -175,6 +205,40 @@ fn test_read_metadata() {
     let poster = metadata.poster().unwrap();
     assert_eq!(poster.len(), want_poster.len());
     assert_eq!(poster, want_poster.as_slice());
+
+    let cover_art = metadata.cover_art();
+    assert_eq!(cover_art.len(), 1);
+    assert_eq!(cover_art[0].format, CoverArtFormat::Jpeg);
+    assert_eq!(cover_art[0].data, want_poster.as_slice());
+}
+
+// A Metadata implementation providing only the methods the trait started
+// with.
+struct TitleOnly;
+
+impl<'a> Metadata<'a> for TitleOnly {
+    fn title(&self) -> Option<std::borrow::Cow<'_, str>> {
+        Some("title".into())
+    }
+
+    fn year(&self) -> Option<u32> {
+        None
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn summary(&self) -> Option<std::borrow::Cow<'_, str>> {
+        None
+    }
+}
+
+#[test]
+fn test_metadata_default_methods() {
+    assert_eq!(TitleOnly.title(), Some("title".into()));
+    assert!(TitleOnly.cover_art().is_empty());
+    assert_eq!(TitleOnly.location(), None);
 }
 
 #[test]
@@ -209,3 +273,2993 @@ fn test_read_fragments() {
     let eos = mp4_fragment.read_sample(1, 2);
     assert!(eos.is_err());
 }
+
+#[test]
+fn test_probe_container() {
+    let avif = b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00avifmif1miaf\x00\x00\x00\x08meta";
+    assert_eq!(
+        mp4::probe_container(avif).unwrap(),
+        mp4::ContainerKind::Avif
+    );
+
+    let buf = fs::read("tests/samples/minimal.mp4").unwrap();
+    assert_eq!(mp4::probe_container(&buf).unwrap(), mp4::ContainerKind::Mp4);
+
+    let buf = fs::read("tests/samples/minimal_init.mp4").unwrap();
+    assert_eq!(
+        mp4::probe_container(&buf).unwrap(),
+        mp4::ContainerKind::FragmentedMp4
+    );
+
+    let mp4 = get_reader("tests/samples/minimal_init.mp4");
+    assert_eq!(mp4.container_kind(), mp4::ContainerKind::FragmentedMp4);
+    assert!(mp4.major_brand_is(*mp4.major_brand()));
+
+    // A box after the ftyp whose largesize runs past the largest offset.
+    let mut buf = b"\x00\x00\x00\x18ftypisom\x00\x00\x02\x00isommp41".to_vec();
+    buf.extend_from_slice(b"\x00\x00\x00\x01free");
+    buf.extend_from_slice(&0xFFFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+    assert!(matches!(
+        mp4::probe_container(&buf),
+        Err(mp4::Error::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_sample_description() {
+    let mp4 = get_reader("tests/samples/minimal.mp4");
+    let track = mp4.tracks().get(&1).unwrap();
+
+    match track.sample_description(1) {
+        Some(mp4::SampleEntry::Avc1(avc1)) => {
+            assert_eq!(avc1.width, 320);
+            assert_eq!(avc1.height, 240);
+        }
+        entry => panic!("expected avc1 sample entry, got {:?}", entry),
+    }
+    assert_eq!(
+        track.sample_description(1).unwrap().data_reference_index(),
+        Some(1)
+    );
+    assert!(track.sample_description(0).is_none());
+    assert!(track.sample_description(2).is_none());
+
+    let track = mp4.tracks().get(&2).unwrap();
+    assert!(matches!(
+        track.sample_description(1),
+        Some(mp4::SampleEntry::Mp4a(_))
+    ));
+}
+
+#[test]
+fn test_write_vp9() {
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("isom").unwrap(),
+        minor_version: 512,
+        compatible_brands: vec![str::parse("isom").unwrap(), str::parse("vp09").unwrap()],
+        timescale: 1000,
+    };
+    let vp9_config = mp4::Vp9Config {
+        width: 1280,
+        height: 720,
+        profile: 2,
+        level: 31,
+        bit_depth: 10,
+        chroma_subsampling: 1,
+        video_full_range_flag: true,
+        color_primaries: 9,
+        transfer_characteristics: 16,
+        matrix_coefficients: 9,
+        codec_initialization_data: vec![],
+    };
+
+    let mut writer = mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(vp9_config.clone()))
+        .unwrap();
+    for i in 0..6 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: i % 3 == 0,
+            bytes: mp4::Bytes::from(vec![i as u8; 16]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.media_type().unwrap(), MediaType::VP9);
+    assert_eq!(track.vp9_config().unwrap(), vp9_config);
+    assert_eq!(
+        track.trak.mdia.minf.stbl.stss.as_ref().unwrap().entries,
+        vec![1, 4]
+    );
+
+    for i in 1..=6 {
+        let sample = mp4.read_sample(1, i).unwrap().unwrap();
+        assert_eq!(sample.is_sync, i % 3 == 1);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8 - 1; 16]));
+    }
+}
+
+#[test]
+fn test_fmp4_open_append() {
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("iso6").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![str::parse("iso6").unwrap(), str::parse("dash").unwrap()],
+        timescale: 1000,
+    };
+    let sample = |i: u64| mp4::Mp4Sample {
+        start_time: i * 40,
+        duration: 40,
+        rendering_offset: 0,
+        is_sync: i.is_multiple_of(2),
+        bytes: mp4::Bytes::from(vec![i as u8; 8]),
+    };
+
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for i in 0..4 {
+        if i % 2 == 0 {
+            writer.start_fragment().unwrap();
+        }
+        writer.add_sample(1, &sample(i)).unwrap();
+    }
+    writer.write_end().unwrap();
+    assert!(writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .is_err());
+
+    let mut writer = mp4::FMp4Writer::open_append(writer.into_writer()).unwrap();
+    for i in 4..6 {
+        writer.add_sample(1, &sample(i)).unwrap();
+    }
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.container_kind(), mp4::ContainerKind::FragmentedMp4);
+    assert_eq!(mp4.moofs.len(), 3);
+
+    let sequence_numbers: Vec<u32> = mp4.moofs.iter().map(|m| m.mfhd.sequence_number).collect();
+    assert_eq!(sequence_numbers, vec![1, 2, 3]);
+    let decode_times: Vec<u64> = mp4
+        .moofs
+        .iter()
+        .map(|m| m.trafs[0].tfdt.as_ref().unwrap().base_media_decode_time)
+        .collect();
+    assert_eq!(decode_times, vec![0, 80, 160]);
+
+    for i in 1..=6 {
+        let sample = mp4.read_sample(1, i).unwrap().unwrap();
+        assert_eq!(sample.start_time, (i as u64 - 1) * 40);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8 - 1; 8]));
+    }
+}
+
+#[test]
+fn test_fmp4_open_append_cut_fragment() {
+    let sample = |i: u64| mp4::Mp4Sample {
+        start_time: i * 40,
+        duration: 40,
+        rendering_offset: 0,
+        is_sync: true,
+        bytes: mp4::Bytes::from(vec![i as u8; 8]),
+    };
+
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &iso6_config()).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for i in 0..4 {
+        if i % 2 == 0 {
+            writer.start_fragment().unwrap();
+        }
+        writer.add_sample(1, &sample(i)).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let last_moof = data.windows(4).rposition(|w| w == b"moof").unwrap() - 4;
+
+    // Cut in the middle of the last mdat, of the last moof and of its header.
+    for cut in [data.len() - 4, last_moof + 12, last_moof + 4] {
+        let mut writer = mp4::FMp4Writer::open_append(Cursor::new(data[..cut].to_vec())).unwrap();
+        for i in 2..4 {
+            writer.add_sample(1, &sample(i + 10)).unwrap();
+        }
+        writer.write_end().unwrap();
+
+        let data = writer.into_writer().into_inner();
+        let size = data.len() as u64;
+        let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+        let sequence_numbers: Vec<u32> = mp4.moofs.iter().map(|m| m.mfhd.sequence_number).collect();
+        assert_eq!(sequence_numbers, vec![1, 2]);
+        let decode_times: Vec<u64> = mp4
+            .moofs
+            .iter()
+            .map(|m| m.trafs[0].tfdt.as_ref().unwrap().base_media_decode_time)
+            .collect();
+        assert_eq!(decode_times, vec![0, 80]);
+
+        for (i, byte) in [(1, 0), (2, 1), (3, 12), (4, 13)] {
+            let sample = mp4.read_sample(1, i).unwrap().unwrap();
+            assert_eq!(sample.start_time, (i as u64 - 1) * 40);
+            assert_eq!(sample.bytes, mp4::Bytes::from(vec![byte; 8]));
+        }
+        assert_eq!(mp4.sample_count(1).unwrap(), 4);
+    }
+}
+
+/// A stream that serves `header` followed by a synthetic payload, so that
+/// sample offsets past 4 GiB can be exercised without a real file.
+struct SparseFile {
+    header: Vec<u8>,
+    len: u64,
+    pos: u64,
+}
+
+fn sparse_byte(pos: u64) -> u8 {
+    (pos % 251) as u8
+}
+
+impl Read for SparseFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = std::cmp::min(buf.len() as u64, self.len.saturating_sub(self.pos)) as usize;
+        for (i, b) in buf[..n].iter_mut().enumerate() {
+            let pos = self.pos + i as u64;
+            *b = match self.header.get(pos as usize) {
+                Some(b) => *b,
+                None => sparse_byte(pos),
+            };
+        }
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for SparseFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(n) => n,
+            SeekFrom::End(n) => (self.len as i64 + n) as u64,
+            SeekFrom::Current(n) => (self.pos as i64 + n) as u64,
+        };
+        Ok(self.pos)
+    }
+}
+
+#[test]
+fn test_read_co64_past_4gib() {
+    let mut writer = start_vp9_writer();
+    for i in 0..3 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; 16]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    // Move the single chunk past the 4 GiB mark using a co64 box.
+    let chunk_offset = 5 << 30;
+    let mut moov = mp4.moov.clone();
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    assert_eq!(stbl.stco.as_ref().unwrap().entries.len(), 1);
+    stbl.stco = None;
+    stbl.co64 = Some(mp4::Co64Box {
+        entries: vec![chunk_offset],
+        ..Default::default()
+    });
+
+    let mut header = Vec::new();
+    mp4.ftyp.write_box(&mut header).unwrap();
+    moov.write_box(&mut header).unwrap();
+    let len = chunk_offset + 48;
+    let mdat_size = len - header.len() as u64;
+    header.extend_from_slice(&1u32.to_be_bytes());
+    header.extend_from_slice(b"mdat");
+    header.extend_from_slice(&mdat_size.to_be_bytes());
+
+    let file = SparseFile {
+        header,
+        len,
+        pos: 0,
+    };
+    let mut mp4 = Mp4Reader::read_header(file, len).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_offset(2).unwrap(), chunk_offset + 16);
+
+    let sample = mp4.read_sample(1, 2).unwrap().unwrap();
+    let expected: Vec<u8> = (chunk_offset + 16..chunk_offset + 32)
+        .map(sparse_byte)
+        .collect();
+    assert_eq!(sample.bytes, mp4::Bytes::from(expected));
+}
+
+// The config of the progressive files most tests write.
+fn isom_config() -> mp4::Mp4Config {
+    mp4::Mp4Config {
+        major_brand: str::parse("isom").unwrap(),
+        minor_version: 512,
+        compatible_brands: vec![str::parse("isom").unwrap()],
+        timescale: 1000,
+    }
+}
+
+fn start_writer() -> mp4::Mp4Writer<Cursor<Vec<u8>>> {
+    mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &isom_config()).unwrap()
+}
+
+// A writer with a VP9 track of id 1.
+fn start_vp9_writer() -> mp4::Mp4Writer<Cursor<Vec<u8>>> {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    writer
+}
+
+// The config of the fragmented files most tests write.
+fn iso6_config() -> mp4::Mp4Config {
+    mp4::Mp4Config {
+        major_brand: str::parse("iso6").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![str::parse("iso6").unwrap()],
+        timescale: 1000,
+    }
+}
+
+fn write_fragmented(fragments: u64, samples_per_fragment: u64) -> Vec<u8> {
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &iso6_config()).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for i in 0..fragments * samples_per_fragment {
+        if i.is_multiple_of(samples_per_fragment) {
+            writer.start_fragment().unwrap();
+        }
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i as u8; 16]),
+        };
+        writer.add_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    writer.into_writer().into_inner()
+}
+
+#[test]
+fn test_read_truncated_mdat() {
+    let mut data = write_fragmented(2, 3);
+    data.truncate(data.len() - 20);
+    let size = data.len() as u64;
+
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    assert!(mp4.read_sample(1, 5).is_err());
+    assert_eq!(mp4.first_truncated_sample(1).unwrap(), None);
+
+    let mut mp4 = Mp4Reader::read_header_recover(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.first_truncated_sample(1).unwrap(), Some(5));
+    for i in 1..=4 {
+        let sample = mp4.read_sample(1, i).unwrap().unwrap();
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8 - 1; 16]));
+    }
+    assert_eq!(mp4.read_sample(1, 5).unwrap(), None);
+    assert_eq!(mp4.read_sample(1, 6).unwrap(), None);
+}
+
+#[test]
+fn test_read_largesize_moof() {
+    let data = write_fragmented(2, 3);
+
+    // Rewrite the second moof with a 16-byte largesize header, moving its
+    // samples 8 bytes further from its start.
+    let mut offset = 0;
+    let mut moofs = Vec::new();
+    while offset < data.len() {
+        let box_size = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        if &data[offset + 4..offset + 8] == b"moof" {
+            moofs.push((offset, box_size));
+        }
+        offset += box_size;
+    }
+    let (moof_offset, moof_size) = moofs[1];
+    let mp4 = mp4::parse(&data).unwrap();
+    let mut moof = mp4.moofs[1].clone();
+    let trun = moof.trafs[0].trun.as_mut().unwrap();
+    trun.data_offset = Some(trun.data_offset.unwrap() + 8);
+    let moof_bytes = mp4::Mp4Box::to_bytes(&moof).unwrap();
+    let mut largesize_moof = vec![0, 0, 0, 1];
+    largesize_moof.extend_from_slice(b"moof");
+    largesize_moof.extend_from_slice(&(moof_size as u64 + 8).to_be_bytes());
+    largesize_moof.extend_from_slice(&moof_bytes[8..]);
+    let data: Vec<u8> = [
+        &data[..moof_offset],
+        &largesize_moof,
+        &data[moof_offset + moof_size..],
+    ]
+    .concat();
+
+    let mut mp4 = mp4::parse(&data).unwrap();
+    for i in 1..=6 {
+        let sample = mp4.read_sample(1, i).unwrap().unwrap();
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8 - 1; 16]));
+    }
+
+    // Cut within the last 8 bytes of that moof: recovery stops before it.
+    let cut = moof_offset + largesize_moof.len() - 4;
+    let mp4 = Mp4Reader::read_header_recover(Cursor::new(&data[..cut]), cut as u64).unwrap();
+    assert_eq!(mp4.moofs.len(), 1);
+    assert_eq!(mp4.sample_count(1).unwrap(), 3);
+}
+
+#[test]
+fn test_recover_missing_moov() {
+    let data = write_fragmented(2, 3);
+
+    // Drop the moov that follows the ftyp.
+    let ftyp_size = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+    let moov_size = u32::from_be_bytes(data[ftyp_size..ftyp_size + 4].try_into().unwrap()) as usize;
+    assert_eq!(&data[ftyp_size + 4..ftyp_size + 8], b"moov");
+    let mut data: Vec<u8> = [&data[..ftyp_size], &data[ftyp_size + moov_size..]].concat();
+    let size = data.len() as u64;
+
+    assert!(Mp4Reader::read_header(Cursor::new(data.clone()), size).is_err());
+
+    let moov = mp4::recover::rebuild_moov(&mut Cursor::new(&mut data), size).unwrap();
+    assert_eq!(moov.traks.len(), 1);
+    assert_eq!(moov.traks[0].tkhd.track_id, 1);
+    assert_eq!(moov.mvex.unwrap().trexs.len(), 1);
+
+    let mut mp4 = Mp4Reader::read_header_recover(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.moofs.len(), 2);
+    assert_eq!(mp4.sample_count(1).unwrap(), 6);
+    assert_eq!(mp4.first_truncated_sample(1).unwrap(), None);
+    for i in 1..=6 {
+        let sample = mp4.read_sample(1, i).unwrap().unwrap();
+        assert_eq!(sample.start_time, (i as u64 - 1) * 40);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8 - 1; 16]));
+    }
+}
+
+// The reader of a file with one audio track whose only sample entry is the
+// generic one of type `name`, with the AudioSampleEntry fields given and
+// followed by `children`.
+fn generic_audio_entry(
+    name: &[u8; 4],
+    channel_count: u16,
+    sample_size: u16,
+    sample_rate: u16,
+    children: &[u8],
+) -> Mp4Reader<Cursor<Vec<u8>>> {
+    let mut entry = vec![0u8; 28];
+    entry[6..8].copy_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    entry[16..18].copy_from_slice(&channel_count.to_be_bytes());
+    entry[18..20].copy_from_slice(&sample_size.to_be_bytes());
+    entry[24..26].copy_from_slice(&sample_rate.to_be_bytes());
+    entry.extend_from_slice(children);
+
+    let mut trak = mp4::TrakBox::default();
+    trak.tkhd.track_id = 1;
+    trak.mdia.hdlr.handler_type = str::parse("soun").unwrap();
+    trak.mdia.minf.stbl.stco = Some(mp4::StcoBox::default());
+    trak.mdia.minf.stbl.stsd.extra_entries = vec![mp4::SampleEntry::Unknown(
+        mp4::BoxType::from(u32::from_be_bytes(*name)),
+        entry,
+    )];
+    let moov = mp4::MoovBox {
+        traks: vec![trak],
+        ..Default::default()
+    };
+    let ftyp = mp4::FtypBox {
+        major_brand: str::parse("isom").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![],
+    };
+
+    let mut data = Vec::new();
+    ftyp.write_box(&mut data).unwrap();
+    moov.write_box(&mut data).unwrap();
+    let size = data.len() as u64;
+    Mp4Reader::read_header(Cursor::new(data), size).unwrap()
+}
+
+#[test]
+fn test_audio_properties_generic_sample_entry() {
+    // An Opus sample entry, which has no dedicated box type: AudioSampleEntry
+    // fields followed by a dOps box.
+    let mut dops = vec![0, 0, 0, 19];
+    dops.extend_from_slice(b"dOps");
+    dops.extend_from_slice(&[0, 6, 0x01, 0x38, 0, 0, 0xbb, 0x80, 0, 0, 0]);
+    let mp4 = generic_audio_entry(b"Opus", 6, 16, 48000, &dops);
+
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.track_type().unwrap(), TrackType::Audio);
+    assert_eq!(track.sample_rate().unwrap(), 48000);
+    assert_eq!(track.channel_count().unwrap(), 6);
+    assert_eq!(track.bits_per_sample().unwrap(), 16);
+}
+
+#[test]
+fn test_audio_properties_codec_config() {
+    // Opus decodes at 48kHz even when the entry says otherwise.
+    let mut dops = vec![0, 0, 0, 19];
+    dops.extend_from_slice(b"dOps");
+    dops.extend_from_slice(&[0, 2, 0x01, 0x38, 0, 0, 0x3e, 0x80, 0, 0, 0]);
+    let mp4 = generic_audio_entry(b"Opus", 1, 16, 16000, &dops);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_rate().unwrap(), 48000);
+    assert_eq!(track.channel_count().unwrap(), 2);
+
+    // FLAC at 192kHz, which the 16-bit samplerate field cannot hold: a
+    // STREAMINFO block with 6 channels of 24 bits.
+    let mut dfla = vec![0, 0, 0, 50];
+    dfla.extend_from_slice(b"dfLa");
+    dfla.extend_from_slice(&[0, 0, 0, 0]);
+    dfla.extend_from_slice(&[0x80, 0, 0, 34]);
+    dfla.extend_from_slice(&[0x10, 0, 0x10, 0, 0, 0, 0, 0, 0, 0]);
+    let streaminfo: u64 = (192000 << 44) | (5 << 41) | (23 << 36);
+    dfla.extend_from_slice(&streaminfo.to_be_bytes());
+    dfla.extend_from_slice(&[0; 16]); // MD5
+    let mp4 = generic_audio_entry(b"fLaC", 2, 16, 0, &dfla);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_rate().unwrap(), 192000);
+    assert_eq!(track.channel_count().unwrap(), 6);
+    assert_eq!(track.bits_per_sample().unwrap(), 24);
+
+    // AC-3 at 44.1kHz, 3/2 channels with LFE.
+    let mut dac3 = vec![0, 0, 0, 11];
+    dac3.extend_from_slice(b"dac3");
+    dac3.extend_from_slice(&[0x50, 0x3c, 0x40]);
+    let mp4 = generic_audio_entry(b"ac-3", 2, 16, 48000, &dac3);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_rate().unwrap(), 44100);
+    assert_eq!(track.channel_count().unwrap(), 6);
+
+    // An AudioSampleEntryV1 carrying its rate in srat.
+    let mut srat = vec![0, 0, 0, 16];
+    srat.extend_from_slice(b"srat");
+    srat.extend_from_slice(&[0, 0, 0, 0]);
+    srat.extend_from_slice(&96000u32.to_be_bytes());
+    let mp4 = generic_audio_entry(b"dtsc", 2, 24, 0, &srat);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_rate().unwrap(), 96000);
+}
+
+fn video_track_with_durations(timescale: u32, durations: &[u32]) -> Mp4Reader<Cursor<Vec<u8>>> {
+    let mut writer = start_writer();
+    let mut track_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+    track_config.timescale = timescale;
+    writer.add_track(&track_config).unwrap();
+    let mut start_time = 0;
+    for duration in durations {
+        let sample = mp4::Mp4Sample {
+            start_time,
+            duration: *duration,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+        start_time += *duration as u64;
+    }
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    Mp4Reader::read_header(Cursor::new(data), size).unwrap()
+}
+
+#[test]
+fn test_read_sample_short_stts() {
+    // Drop the stts entry of the last five samples: reading them fails
+    // instead of panicking.
+    let mut writer = start_vp9_writer();
+    let mut start_time = 0;
+    for duration in [40, 40, 40, 40, 40, 20, 20, 20, 20, 20] {
+        let sample = mp4::Mp4Sample {
+            start_time,
+            duration,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+        start_time += duration as u64;
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut moov = Mp4Reader::read_header(Cursor::new(&data), size)
+        .unwrap()
+        .moov;
+
+    // The writer puts the moov last.
+    let moov_start = data.len() - moov.get_size() as usize;
+    let stts = &mut moov.traks[0].mdia.minf.stbl.stts;
+    assert_eq!(stts.entries.len(), 2);
+    stts.entries.pop();
+    let mut data = data[..moov_start].to_vec();
+    moov.write_box(&mut data).unwrap();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    assert_eq!(mp4.read_sample(1, 5).unwrap().unwrap().start_time, 160);
+    assert!(mp4.read_sample(1, 6).is_err());
+    assert!(mp4.read_sample_ref(1, 6).is_err());
+}
+
+#[test]
+fn test_frame_rate() {
+    // 29.97 fps with a short final sample.
+    let mut durations = vec![1001; 30];
+    durations.push(500);
+    let mp4 = video_track_with_durations(30000, &durations);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert!(track.is_constant_frame_rate());
+    assert!((track.frame_rate().unwrap() - 29.97).abs() < 0.001);
+
+    // 29.97 fps rounded to a millisecond timescale.
+    let durations: Vec<u32> = (0..30)
+        .map(|i| (i + 1) * 1001 / 30 - i * 1001 / 30)
+        .collect();
+    let mp4 = video_track_with_durations(1000, &durations);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert!(track.is_constant_frame_rate());
+    assert!((track.frame_rate().unwrap() - 29.97).abs() < 0.01);
+
+    // Variable frame rate reports the average.
+    let durations = [40, 40, 20, 20, 40, 40];
+    let mp4 = video_track_with_durations(1000, &durations);
+    let track = mp4.tracks().get(&1).unwrap();
+    assert!(!track.is_constant_frame_rate());
+    assert_eq!(track.frame_rate(), Some(30.0));
+}
+
+#[test]
+fn test_write_sample_with_timestamps() {
+    let mut writer = start_writer();
+    let mut track_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+    track_config.timescale = 30000;
+    writer.add_track(&track_config).unwrap();
+
+    let dts = [0, 1001, 2002, 3003, 4004, 5005];
+    let pts = [0, 3003, 1001, 2002, 5005, 4004];
+    for i in 0..dts.len() {
+        writer
+            .add_sample_with_timestamps(1, dts[i], pts[i], mp4::Bytes::from(vec![i as u8]), i == 0)
+            .unwrap();
+    }
+    assert!(writer
+        .add_sample_with_timestamps(1, 0, 0, mp4::Bytes::new(), true)
+        .is_err());
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sample_count(), 6);
+    assert_eq!(track.duration(), Duration::from_micros(200_200));
+    assert_eq!(track.trak.mdia.minf.stbl.ctts.as_ref().unwrap().version, 1);
+
+    for i in 0..dts.len() {
+        let sample = mp4.read_sample(1, i as u32 + 1).unwrap().unwrap();
+        assert_eq!(sample.start_time, dts[i]);
+        assert_eq!(
+            sample.start_time as i64 + sample.rendering_offset as i64,
+            pts[i] as i64
+        );
+        assert_eq!(sample.duration, 1001);
+    }
+}
+
+#[test]
+fn test_writer_track_ids() {
+    let track_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+
+    let mut writer = start_writer();
+    for _ in 0..3 {
+        writer.add_track(&track_config).unwrap();
+    }
+    assert!(writer.add_track_with_id(2, &track_config).is_err());
+    assert!(writer.add_track_with_id(0, &track_config).is_err());
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track_ids: Vec<u32> = mp4.moov.traks.iter().map(|t| t.tkhd.track_id).collect();
+    assert_eq!(track_ids, vec![1, 2, 3]);
+    assert_eq!(mp4.moov.mvhd.next_track_id, 4);
+
+    let mut writer = start_writer();
+    writer.add_track_with_id(10, &track_config).unwrap();
+    writer.add_track(&track_config).unwrap();
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track_ids: Vec<u32> = mp4.moov.traks.iter().map(|t| t.tkhd.track_id).collect();
+    assert_eq!(track_ids, vec![10, 11]);
+    assert_eq!(mp4.moov.mvhd.next_track_id, 12);
+}
+
+#[test]
+fn test_dump_tree() {
+    let f = File::open("tests/samples/minimal.mp4").unwrap();
+    let size = f.metadata().unwrap().len();
+    let mut reader = BufReader::new(f);
+
+    let mut out = Vec::new();
+    mp4::dump::tree(&mut reader, size, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write("tests/samples/minimal_tree.txt", &out).unwrap();
+    }
+    assert_eq!(
+        out,
+        fs::read_to_string("tests/samples/minimal_tree.txt").unwrap()
+    );
+}
+
+#[test]
+fn test_read_fragmented_samples() {
+    let data = write_fragmented(3, 4);
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    assert!(mp4.is_fragmented());
+    assert_eq!(mp4.sample_count(1).unwrap(), 12);
+
+    let mut moof_offsets = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        if &data[offset + 4..offset + 8] == b"moof" {
+            moof_offsets.push(offset as u64);
+        }
+        offset += u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+    }
+    assert_eq!(moof_offsets.len(), 3);
+
+    // The 7th sample is the 3rd sample of the 2nd fragment.
+    let traf = &mp4.moofs[1].trafs[0];
+    let trun = traf.trun.as_ref().unwrap();
+    let start = moof_offsets[1] as usize
+        + trun.data_offset.unwrap() as usize
+        + trun.sample_sizes[..2].iter().sum::<u32>() as usize;
+    let expected = mp4::Mp4Sample {
+        start_time: traf.tfdt.as_ref().unwrap().base_media_decode_time
+            + trun.sample_durations[..2].iter().sum::<u32>() as u64,
+        duration: trun.sample_durations[2],
+        rendering_offset: 0,
+        is_sync: trun.sample_flags[2] & 0x00010000 == 0,
+        bytes: mp4::Bytes::copy_from_slice(&data[start..start + trun.sample_sizes[2] as usize]),
+    };
+    assert_eq!(mp4.read_sample(1, 7).unwrap().unwrap(), expected);
+    assert_eq!(expected.bytes, mp4::Bytes::from(vec![6; 16]));
+}
+
+#[test]
+fn test_read_moov_samples_and_fragments() {
+    let mut writer = start_vp9_writer();
+    for i in 0..2 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i as u8; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+
+    // Append a fragment without tfdt, continuing from the moov samples.
+    let mut moof = mp4::MoofBox::default();
+    moof.mfhd.sequence_number = 1;
+    moof.trafs.push(mp4::TrafBox {
+        tfhd: mp4::TfhdBox {
+            flags: mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_DURATION
+                | mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_FLAGS
+                | mp4::TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+            track_id: 1,
+            default_sample_duration: Some(50),
+            default_sample_flags: Some(0x01010000),
+            ..Default::default()
+        },
+        tfdt: None,
+        trun: Some(mp4::TrunBox {
+            flags: mp4::TrunBox::FLAG_DATA_OFFSET
+                | mp4::TrunBox::FLAG_FIRST_SAMPLE_FLAGS
+                | mp4::TrunBox::FLAG_SAMPLE_SIZE,
+            sample_count: 3,
+            data_offset: Some(0),
+            first_sample_flags: Some(0x02000000),
+            sample_sizes: vec![2, 3, 4],
+            ..Default::default()
+        }),
+        subs: None,
+        saiz: None,
+        saio: None,
+    });
+    let data_offset = moof.get_size() + 8;
+    moof.trafs[0].trun.as_mut().unwrap().data_offset = Some(data_offset as i32);
+    moof.write_box(&mut data).unwrap();
+    data.extend_from_slice(&(8u32 + 9).to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&[2, 2, 3, 3, 3, 4, 4, 4, 4]);
+
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert!(mp4.is_fragmented());
+    assert_eq!(mp4.sample_count(1).unwrap(), 5);
+
+    let samples: Vec<mp4::Mp4Sample> = (1..=5)
+        .map(|i| mp4.read_sample(1, i).unwrap().unwrap())
+        .collect();
+    let start_times: Vec<u64> = samples.iter().map(|s| s.start_time).collect();
+    assert_eq!(start_times, vec![0, 40, 80, 130, 180]);
+    let durations: Vec<u32> = samples.iter().map(|s| s.duration).collect();
+    assert_eq!(durations, vec![40, 40, 50, 50, 50]);
+    let syncs: Vec<bool> = samples.iter().map(|s| s.is_sync).collect();
+    assert_eq!(syncs, vec![true, true, true, false, false]);
+    for (i, sample) in samples.iter().enumerate() {
+        let len = if i < 2 { 4 } else { i };
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8; len]));
+    }
+    assert!(mp4.read_sample(1, 6).is_err());
+}
+
+#[test]
+fn test_write_pcm() {
+    let configs = [
+        mp4::PcmConfig {
+            sample_rate: 44100,
+            channel_count: 2,
+            bits_per_sample: 24,
+            float: false,
+            little_endian: false,
+            signed: true,
+        },
+        mp4::PcmConfig {
+            bits_per_sample: 32,
+            float: true,
+            ..Default::default()
+        },
+        mp4::PcmConfig {
+            bits_per_sample: 8,
+            signed: false,
+            ..Default::default()
+        },
+        mp4::PcmConfig {
+            sample_rate: 96000,
+            ..Default::default()
+        },
+    ];
+    for pcm_config in configs.iter() {
+        let mut writer = start_writer();
+        let mut track_config = mp4::TrackConfig::from(pcm_config.clone());
+        track_config.timescale = pcm_config.sample_rate;
+        writer.add_track(&track_config).unwrap();
+        let frame_size =
+            pcm_config.channel_count as usize * pcm_config.bits_per_sample as usize / 8;
+        let bytes: Vec<u8> = (0..frame_size * 1024).map(|i| i as u8).collect();
+        let sample = mp4::Mp4Sample {
+            start_time: 0,
+            duration: 1024,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(bytes),
+        };
+        writer.write_sample(1, &sample).unwrap();
+        writer.write_end().unwrap();
+
+        let data = writer.into_writer().into_inner();
+        let size = data.len() as u64;
+        let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+        let track = mp4.tracks().get(&1).unwrap();
+        assert_eq!(track.track_type().unwrap(), TrackType::Audio);
+        assert_eq!(track.media_type().unwrap(), MediaType::PCM);
+        assert_eq!(&track.pcm_config().unwrap(), pcm_config);
+        assert_eq!(track.sample_rate().unwrap(), pcm_config.sample_rate);
+        assert_eq!(track.bits_per_sample().unwrap(), pcm_config.bits_per_sample);
+        assert_eq!(mp4.read_sample(1, 1).unwrap().unwrap(), sample);
+    }
+}
+
+#[test]
+fn test_samples_in_range() {
+    let mut writer = start_vp9_writer();
+    // 100ms samples in GOPs of four: sync samples 1, 5 and 9.
+    for i in 0..10 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 100,
+            duration: 100,
+            rendering_offset: 0,
+            is_sync: i.is_multiple_of(4),
+            bytes: mp4::Bytes::from(vec![0; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    let range = |start: u64, end: u64| {
+        track.samples_in_range(Duration::from_millis(start), Duration::from_millis(end))
+    };
+
+    assert_eq!(range(0, 1000), (1..=10).collect::<Vec<u32>>());
+    assert_eq!(range(400, 800), vec![5, 6, 7, 8]);
+    // Within a single GOP, starting from its sync sample.
+    assert_eq!(range(650, 750), vec![5, 6, 7, 8]);
+    assert_eq!(range(250, 310), vec![1, 2, 3, 4]);
+    assert_eq!(range(900, 950), vec![9, 10]);
+    assert!(range(1000, 2000).is_empty());
+    assert!(range(500, 500).is_empty());
+}
+
+#[test]
+fn test_read_sample_ref() {
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data.as_slice()), size).unwrap();
+
+    for track_id in [1, 2] {
+        let sample_count = mp4.sample_count(track_id).unwrap();
+        for sample_id in 1..=sample_count {
+            let sample = mp4.read_sample(track_id, sample_id).unwrap().unwrap();
+            let offset = mp4.sample_offset(track_id, sample_id).unwrap() as usize;
+            let sample_ref = mp4.read_sample_ref(track_id, sample_id).unwrap().unwrap();
+            assert_eq!(sample_ref.bytes, &sample.bytes[..]);
+            assert_eq!(mp4::Mp4Sample::from(sample_ref), sample);
+
+            // The data is a view into the backing buffer.
+            assert_eq!(sample_ref.bytes.as_ptr(), data[offset..].as_ptr());
+        }
+        assert_eq!(
+            mp4.read_sample_ref(track_id, sample_count + 1).unwrap(),
+            None
+        );
+    }
+    assert!(mp4.read_sample_ref(3, 1).is_err());
+}
+
+// A reader that cannot seek, like a pipe.
+struct Pipe<R>(R);
+
+impl<R: Read> Read for Pipe<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+#[test]
+fn test_stream_reader() {
+    let mut mp4 = get_reader("tests/samples/minimal.mp4");
+    let f = File::open("tests/samples/minimal.mp4").unwrap();
+    let mut stream = mp4::Mp4StreamReader::read_header(Pipe(BufReader::new(f))).unwrap();
+    assert_eq!(stream.tracks().len(), mp4.tracks().len());
+
+    let mut counts = std::collections::HashMap::new();
+    let mut last_offset = 0;
+    while let Some((track_id, sample)) = stream.next_sample().unwrap() {
+        let sample_id = counts.entry(track_id).or_insert(0);
+        *sample_id += 1;
+        let offset = mp4.sample_offset(track_id, *sample_id).unwrap();
+        assert!(offset >= last_offset);
+        last_offset = offset;
+        assert_eq!(Some(sample), mp4.read_sample(track_id, *sample_id).unwrap());
+    }
+    for (track_id, track) in mp4.tracks() {
+        assert_eq!(counts[track_id], track.sample_count());
+    }
+
+    // The moov comes after the mdat.
+    let f = File::open("tests/samples/extended_audio_object_type.mp4").unwrap();
+    assert!(matches!(
+        mp4::Mp4StreamReader::read_header(Pipe(BufReader::new(f))),
+        Err(mp4::Error::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_stream_reader_oversized_moov() {
+    // A moov claiming far more bytes than the stream holds fails instead of
+    // allocating them.
+    let mut data = vec![0, 0, 0, 24];
+    data.extend_from_slice(b"ftypisom\0\0\x02\0isommp41");
+    data.extend_from_slice(&[0, 0, 0, 1]);
+    data.extend_from_slice(b"moov");
+    data.extend_from_slice(&0x7FFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+    assert!(matches!(
+        mp4::Mp4StreamReader::read_header(Pipe(&data[..])),
+        Err(mp4::Error::IoError(_))
+    ));
+}
+
+#[test]
+fn test_repack_normalize() {
+    let mut writer = start_vp9_writer();
+    for i in 0..10u8 {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 1000,
+            duration: 1000,
+            rendering_offset: 0,
+            is_sync: i == 0,
+            bytes: mp4::Bytes::from(vec![i; 16 + i as usize]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), data.len() as u64).unwrap();
+
+    // Rearrange it as moov, ftyp, a vendor box, then the media data.
+    let ftyp_size = mp4.ftyp.get_size() as usize;
+    let mdat = &data[ftyp_size..data.len() - mp4.moov.get_size() as usize];
+    let vendor = b"\x00\x00\x00\x0cxtra\x01\x02\x03\x04";
+    let mut moov = mp4.moov.clone();
+    let shift = moov.get_size() as u32 + vendor.len() as u32;
+    for offset in moov.traks[0]
+        .mdia
+        .minf
+        .stbl
+        .stco
+        .as_mut()
+        .unwrap()
+        .entries
+        .iter_mut()
+    {
+        *offset += shift;
+    }
+    let mut input = Vec::new();
+    moov.write_box(&mut input).unwrap();
+    mp4.ftyp.write_box(&mut input).unwrap();
+    input.extend_from_slice(vendor);
+    input.extend_from_slice(mdat);
+
+    let mut output = Vec::new();
+    mp4::repack::normalize(&mut Cursor::new(&input), input.len() as u64, &mut output).unwrap();
+    assert_eq!(output.len(), input.len());
+
+    let mut names = Vec::new();
+    let mut offset = 0;
+    while offset < output.len() {
+        let size = u32::from_be_bytes(output[offset..offset + 4].try_into().unwrap()) as usize;
+        names.push(String::from_utf8_lossy(&output[offset + 4..offset + 8]).into_owned());
+        offset += size;
+    }
+    assert_eq!(names[..3], ["ftyp", "moov", "xtra"]);
+    assert_eq!(names.last().unwrap(), "mdat");
+    let vendor_start = (mp4.ftyp.get_size() + mp4.moov.get_size()) as usize;
+    assert_eq!(&output[vendor_start..vendor_start + vendor.len()], vendor);
+
+    let size = output.len() as u64;
+    let mut repacked = Mp4Reader::read_header(Cursor::new(output), size).unwrap();
+    let mut original = mp4;
+    assert_eq!(repacked.sample_count(1).unwrap(), 10);
+    for sample_id in 1..=10 {
+        assert_eq!(
+            repacked.read_sample(1, sample_id).unwrap(),
+            original.read_sample(1, sample_id).unwrap()
+        );
+    }
+}
+
+// The names and bodies of the boxes in `data`, all with 32-bit sizes.
+fn box_bodies(data: &[u8]) -> Vec<(String, &[u8])> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let size = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+        let name = String::from_utf8_lossy(&data[offset + 4..offset + 8]).into_owned();
+        boxes.push((name, &data[offset + 8..offset + size]));
+        offset += size;
+    }
+    boxes
+}
+
+#[test]
+fn test_repack_unparsed_children() {
+    let mut writer = start_vp9_writer();
+    for i in 0..3u8 {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 1000,
+            duration: 1000,
+            rendering_offset: 0,
+            is_sync: i == 0,
+            bytes: mp4::Bytes::from(vec![i; 16]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), data.len() as u64).unwrap();
+
+    // Replace the trailing moov with one holding a vendor box before its
+    // trak, whose tref of chapter track 2 comes before its tkhd.
+    let vendor = b"\x00\x00\x00\x0cxvnd\x01\x02\x03\x04";
+    let tref = b"\x00\x00\x00\x14tref\x00\x00\x00\x0cchap\x00\x00\x00\x02";
+    let mut mvhd = Vec::new();
+    mp4.moov.mvhd.write_box(&mut mvhd).unwrap();
+    let mut trak = Vec::new();
+    mp4.moov.traks[0].write_box(&mut trak).unwrap();
+    let trak_size = trak.len() + tref.len();
+    let mut moov_body = mvhd;
+    moov_body.extend_from_slice(vendor);
+    moov_body.extend_from_slice(&(trak_size as u32).to_be_bytes());
+    moov_body.extend_from_slice(b"trak");
+    moov_body.extend_from_slice(tref);
+    moov_body.extend_from_slice(&trak[8..]);
+    let mut input = data[..data.len() - mp4.moov.get_size() as usize].to_vec();
+    input.extend_from_slice(&(8 + moov_body.len() as u32).to_be_bytes());
+    input.extend_from_slice(b"moov");
+    input.extend_from_slice(&moov_body);
+
+    // Both are written after the children the crate writes.
+    let check = |file: &[u8]| {
+        let boxes = box_bodies(file);
+        let moov = boxes.iter().find(|(name, _)| name == "moov").unwrap().1;
+        let children = box_bodies(moov);
+        let names: Vec<&str> = children.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["mvhd", "trak", "xvnd"]);
+        assert_eq!(children[2].1, &vendor[8..]);
+        let trak_children = box_bodies(children[1].1);
+        let (name, body) = trak_children.last().unwrap();
+        assert_eq!(name, "tref");
+        assert_eq!(*body, &tref[8..]);
+    };
+
+    let mut output = Vec::new();
+    mp4::repack::normalize(&mut Cursor::new(&input), input.len() as u64, &mut output).unwrap();
+    assert_eq!(output.len(), input.len());
+    check(&output);
+    let size = output.len() as u64;
+    let mut repacked = Mp4Reader::read_header(Cursor::new(output), size).unwrap();
+    assert_eq!(
+        repacked.read_sample(1, 3).unwrap().unwrap().bytes,
+        mp4::Bytes::from(vec![2; 16])
+    );
+
+    let size = input.len() as u64;
+    let mut file = Cursor::new(input);
+    mp4::repack::update_moov(&mut file, size, |moov| moov.mvhd.timescale = 500).unwrap();
+    check(file.get_ref());
+}
+
+#[test]
+fn test_timing_table() {
+    let mut mp4 = get_reader("tests/samples/minimal.mp4");
+    check_timing_table(&mut mp4, 1);
+    check_timing_table(&mut mp4, 2);
+
+    let data = write_fragmented(3, 4);
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    check_timing_table(&mut mp4, 1);
+
+    // Signed composition offsets, as written for B-frames without an edit.
+    let mut writer = start_vp9_writer();
+    for (i, &rendering_offset) in [0, 80, -40, -40, 80, -40, -40].iter().enumerate() {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 40,
+            duration: 40,
+            rendering_offset,
+            is_sync: i == 0,
+            bytes: mp4::Bytes::from(vec![0; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    check_timing_table(&mut mp4, 1);
+    let table = mp4.tracks().get(&1).unwrap().timing_table();
+    assert_eq!(table.cts[..3], [0, 120, 40]);
+    assert_eq!(table.dts[..3], [0, 40, 80]);
+}
+
+fn check_timing_table<R: Read + Seek>(mp4: &mut Mp4Reader<R>, track_id: u32) {
+    let table = mp4.tracks().get(&track_id).unwrap().timing_table();
+    let sample_count = mp4.sample_count(track_id).unwrap();
+    assert_eq!(table.dts.len(), sample_count as usize);
+    assert_eq!(table.cts.len(), sample_count as usize);
+    assert_eq!(table.duration.len(), sample_count as usize);
+    for sample_id in 1..=sample_count {
+        let sample = mp4.read_sample(track_id, sample_id).unwrap().unwrap();
+        let i = sample_id as usize - 1;
+        assert_eq!(table.dts[i], sample.start_time);
+        assert_eq!(
+            table.cts[i],
+            sample.start_time as i64 + sample.rendering_offset as i64
+        );
+        assert_eq!(table.duration[i], sample.duration);
+    }
+}
+
+#[test]
+fn test_optimize_sample_tables() {
+    let mut writer = start_vp9_writer();
+    let mut start_time = 0;
+    for i in 0..12 {
+        let duration = if i < 8 { 40 } else { 20 };
+        let sample = mp4::Mp4Sample {
+            start_time,
+            duration,
+            rendering_offset: 40,
+            is_sync: i == 0,
+            bytes: mp4::Bytes::from(vec![i as u8; 4 + i]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+        start_time += duration as u64;
+    }
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let offsets: Vec<u64> = (1..=12)
+        .map(|sample_id| mp4.sample_offset(1, sample_id).unwrap())
+        .collect();
+
+    // Split every table into one entry per sample and put each sample in
+    // its own chunk, as an editor appending samples one by one might.
+    let mut moov = mp4.moov.clone();
+    data.truncate(data.len() - mp4.moov.get_size() as usize);
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    stbl.stts.entries = stbl
+        .stts
+        .entries
+        .iter()
+        .flat_map(|e| {
+            (0..e.sample_count).map(move |_| mp4::SttsEntry {
+                sample_count: 1,
+                sample_delta: e.sample_delta,
+            })
+        })
+        .collect();
+    let ctts = stbl.ctts.as_mut().unwrap();
+    ctts.entries = ctts
+        .entries
+        .iter()
+        .flat_map(|e| {
+            (0..e.sample_count).map(move |_| mp4::CttsEntry {
+                sample_count: 1,
+                sample_offset: e.sample_offset,
+            })
+        })
+        .collect();
+    stbl.stco.as_mut().unwrap().entries = (1..=12)
+        .map(|sample_id| offsets[sample_id - 1] as u32)
+        .collect();
+    stbl.stsc.entries = (1..=12)
+        .map(|first_chunk| mp4::StscEntry {
+            first_chunk,
+            samples_per_chunk: 1,
+            sample_description_index: 1,
+            first_sample: first_chunk,
+        })
+        .collect();
+    moov.write_box(&mut data).unwrap();
+    let size = data.len() as u64;
+    let mut split = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    check_timing_table(&mut split, 1);
+
+    moov.optimize_sample_tables();
+    let stbl = &moov.traks[0].mdia.minf.stbl;
+    assert_eq!(stbl.stts.entries.len(), 2);
+    assert_eq!(stbl.ctts.as_ref().unwrap().entries.len(), 1);
+    assert_eq!(stbl.stsc.entries.len(), 1);
+    data.truncate(data.len() - split.moov.get_size() as usize);
+    moov.write_box(&mut data).unwrap();
+    let size = data.len() as u64;
+    let mut optimized = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert!(optimized.moov.get_size() < split.moov.get_size());
+
+    let split_track = split.tracks().get(&1).unwrap();
+    let optimized_track = optimized.tracks().get(&1).unwrap();
+    assert_eq!(optimized_track.timing_table(), split_track.timing_table());
+    for sample_id in 1..=12 {
+        assert_eq!(
+            optimized.read_sample(1, sample_id).unwrap(),
+            split.read_sample(1, sample_id).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_write_ac4() {
+    // ac4_dsi_v1 at 48 kHz with a 64 kb/s bit rate, then presentation bytes.
+    let dsi = vec![
+        0x20, 0xA6, 0x01, 0x20, 0x00, 0x1F, 0x40, 0x1F, 0xFF, 0xFF, 0xFF, 0xE0, 0x01, 0x02, 0x03,
+    ];
+    let mut writer = start_writer();
+    let mut track_config = mp4::TrackConfig::from(mp4::Ac4Config {
+        sample_rate: 48000,
+        channel_count: 6,
+        dsi: dsi.clone(),
+    });
+    track_config.timescale = 48000;
+    writer.add_track(&track_config).unwrap();
+    for i in 0..4 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 2048,
+            duration: 2048,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0xAC; 32]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.track_type().unwrap(), TrackType::Audio);
+    assert_eq!(track.media_type().unwrap(), MediaType::AC4);
+    assert_eq!(track.box_type().unwrap(), str::parse("ac-4").unwrap());
+    assert_eq!(track.sample_rate().unwrap(), 48000);
+    assert_eq!(track.channel_count().unwrap(), 6);
+    assert_eq!(track.bitrate(), 64000);
+    assert_eq!(
+        track.ac4_config().unwrap(),
+        mp4::Ac4Config {
+            sample_rate: 48000,
+            channel_count: 6,
+            dsi,
+        }
+    );
+    assert_eq!(mp4.sample_count(1).unwrap(), 4);
+    let sample = mp4.read_sample(1, 4).unwrap().unwrap();
+    assert_eq!(sample.start_time, 3 * 2048);
+}
+
+#[test]
+fn test_handler_type() {
+    let mp4 = get_reader("tests/samples/minimal.mp4");
+    let mut data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let moov_start = mp4.ftyp.get_size() as usize;
+    let moov_end = moov_start + mp4.moov.get_size() as usize;
+
+    let cases = [
+        (*b"vide", mp4::HandlerType::Video),
+        (*b"soun", mp4::HandlerType::Audio),
+        (*b"text", mp4::HandlerType::Text),
+        (*b"subt", mp4::HandlerType::Subtitle),
+        (*b"sbtl", mp4::HandlerType::QuickTimeSubtitle),
+        (*b"clcp", mp4::HandlerType::ClosedCaption),
+        (*b"meta", mp4::HandlerType::Metadata),
+        (*b"hint", mp4::HandlerType::Hint),
+        (
+            *b"abcd",
+            mp4::HandlerType::Other(mp4::FourCC::from(*b"abcd")),
+        ),
+    ];
+    for (fourcc, handler_type) in cases.iter() {
+        // Rewriting the hdlr in place keeps the moov size and every offset.
+        let mut moov = mp4.moov.clone();
+        moov.traks[0].mdia.hdlr.handler_type = mp4::FourCC::from(*fourcc);
+        let mut buf = Vec::new();
+        moov.write_box(&mut buf).unwrap();
+        assert_eq!(buf.len(), moov_end - moov_start);
+        data.splice(moov_start..moov_end, buf);
+
+        let size = data.len() as u64;
+        let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+        let track_id = mp4.moov.traks[0].tkhd.track_id;
+        let read = mp4.tracks().get(&track_id).unwrap().handler_type();
+        assert_eq!(read, *handler_type);
+        assert_eq!(read.to_fourcc(), mp4::FourCC::from(*fourcc));
+    }
+}
+
+#[test]
+fn test_rebase_chunk_offsets() {
+    let mut writer = start_vp9_writer();
+    for i in 0..10u8 {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 500,
+            duration: 500,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i; 8 + i as usize]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), data.len() as u64).unwrap();
+    let samples: Vec<mp4::Mp4Sample> = (1..=10)
+        .map(|i| mp4.read_sample(1, i).unwrap().unwrap())
+        .collect();
+
+    let ftyp_size = mp4.ftyp.get_size() as usize;
+    let moov_start = data.len() - mp4.moov.get_size() as usize;
+    let mdat = mp4::BoxHeader::read(&mut &data[ftyp_size..]).unwrap();
+    assert_eq!(mdat.name, mp4::BoxType::MdatBox);
+    let payload = &data[ftyp_size + mdat.header_size as usize..moov_start];
+
+    // Media of another track, removed below.
+    let removed = [0xEE; 64];
+    let mut moov = mp4.moov.clone();
+    moov.rebase_chunk_offsets(removed.len() as i64).unwrap();
+    let mut spliced = data[..ftyp_size].to_vec();
+    mp4::BoxHeader::new(
+        mp4::BoxType::MdatBox,
+        8 + (removed.len() + payload.len()) as u64,
+    )
+    .write(&mut spliced)
+    .unwrap();
+    spliced.extend_from_slice(&removed);
+    spliced.extend_from_slice(payload);
+    moov.write_box(&mut spliced).unwrap();
+    let size = spliced.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(spliced), size).unwrap();
+    for (i, sample) in samples.iter().enumerate() {
+        let read = mp4.read_sample(1, i as u32 + 1).unwrap().unwrap();
+        assert_eq!(read.bytes, sample.bytes);
+    }
+
+    // Drop the other track's media and move the samples back.
+    let mut moov = mp4.moov.clone();
+    assert!(moov.rebase_chunk_offsets(-(size as i64)).is_err());
+    assert_eq!(moov, mp4.moov);
+    moov.rebase_chunk_offsets(-(removed.len() as i64)).unwrap();
+    let mut rebuilt = data[..ftyp_size].to_vec();
+    mp4::BoxHeader::new(mp4::BoxType::MdatBox, 8 + payload.len() as u64)
+        .write(&mut rebuilt)
+        .unwrap();
+    rebuilt.extend_from_slice(payload);
+    moov.write_box(&mut rebuilt).unwrap();
+    let size = rebuilt.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(rebuilt), size).unwrap();
+    for (i, sample) in samples.iter().enumerate() {
+        let read = mp4.read_sample(1, i as u32 + 1).unwrap().unwrap();
+        assert_eq!(read.bytes, sample.bytes);
+    }
+
+    // Offsets past 4 GiB no longer fit in stco.
+    let mut moov = mp4.moov.clone();
+    moov.rebase_chunk_offsets(u32::MAX as i64).unwrap();
+    let stbl = &moov.traks[0].mdia.minf.stbl;
+    assert!(stbl.stco.is_none());
+    let chunk_offset = mp4.moov.traks[0]
+        .mdia
+        .minf
+        .stbl
+        .stco
+        .as_ref()
+        .unwrap()
+        .entries[0];
+    assert_eq!(
+        stbl.co64.as_ref().unwrap().entries[0],
+        chunk_offset as u64 + u32::MAX as u64
+    );
+}
+
+#[test]
+fn test_write_hvc1_hev1() {
+    for format in [mp4::HevcFormat::Hvc1, mp4::HevcFormat::Hev1].iter() {
+        let mut writer = start_writer();
+        writer
+            .add_track(&mp4::TrackConfig::from(mp4::HevcConfig {
+                width: 1280,
+                height: 720,
+                format: *format,
+            }))
+            .unwrap();
+        writer.write_end().unwrap();
+        let data = writer.into_writer().into_inner();
+        let size = data.len() as u64;
+        let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+        let track = mp4.tracks().get(&1).unwrap();
+        assert_eq!(track.media_type().unwrap(), MediaType::H265);
+        assert_eq!(track.box_type().unwrap(), format.box_type().into());
+        let hev1 = track.trak.mdia.minf.stbl.stsd.hev1.as_ref().unwrap();
+        assert_eq!(hev1.format, *format);
+        let prefix = match format {
+            mp4::HevcFormat::Hvc1 => "hvc1.",
+            mp4::HevcFormat::Hev1 => "hev1.",
+        };
+        assert!(hev1.codec_string().starts_with(prefix));
+    }
+}
+
+#[test]
+fn test_hdr_metadata() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::HevcConfig {
+            width: 3840,
+            height: 2160,
+            format: mp4::HevcFormat::Hvc1,
+        }))
+        .unwrap();
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    assert_eq!(mp4.tracks().get(&1).unwrap().hdr_metadata(), None);
+
+    // HDR10: BT.2020 primaries, D65 white point, 1000 and 0.0050 cd/m².
+    let mdcv = mp4::MdcvBox {
+        display_primaries: [(8500, 39850), (6550, 2300), (35400, 14600)],
+        white_point: (15635, 16450),
+        max_display_mastering_luminance: 10_000_000,
+        min_display_mastering_luminance: 50,
+    };
+    let clli = mp4::ClliBox {
+        max_content_light_level: 1000,
+        max_pic_average_light_level: 400,
+    };
+
+    // The moov is last, so it can be replaced without moving any sample.
+    let mut moov = mp4.moov.clone();
+    data.truncate(data.len() - mp4.moov.get_size() as usize);
+    let hev1 = moov.traks[0].mdia.minf.stbl.stsd.hev1.as_mut().unwrap();
+    hev1.mdcv = Some(mdcv.clone());
+    hev1.clli = Some(clli.clone());
+    moov.write_box(&mut data).unwrap();
+
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.box_type().unwrap(), str::parse("hvc1").unwrap());
+    assert_eq!(
+        track.hdr_metadata(),
+        Some(mp4::HdrMetadata {
+            mdcv: Some(mdcv),
+            clli: Some(clli),
+            amve: None,
+        })
+    );
+}
+
+#[test]
+fn test_write_encoder_delay() {
+    let mut writer = start_writer();
+    let mut track_config = mp4::TrackConfig::from(mp4::AacConfig::default());
+    track_config.timescale = 48000;
+    track_config.encoder_delay = 2112;
+    writer.add_track(&track_config).unwrap();
+    for i in 0..10 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 1024,
+            duration: 1024,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; 8]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    // 10240 - 2112 media samples at 48 kHz, in milliseconds.
+    let trak = &mp4.tracks().get(&1).unwrap().trak;
+    let elst = trak.edts.as_ref().unwrap().elst.as_ref().unwrap();
+    assert_eq!(elst.entries.len(), 1);
+    assert_eq!(elst.entries[0].media_time, 2112);
+    assert_eq!(elst.entries[0].segment_duration, 169);
+    assert_eq!(elst.entries[0].media_rate, 1);
+    assert_eq!(trak.mdia.mdhd.duration, 10240);
+    assert_eq!(trak.tkhd.duration, 169);
+    assert_eq!(mp4.moov.mvhd.duration, 169);
+}
+
+#[test]
+fn test_sample_encryption_aux_info() {
+    let mut writer = start_vp9_writer();
+    for i in 0..2 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i as u8; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+
+    // Store the IVs of the moov samples in a free box before the moov.
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let mut moov = mp4.moov.clone();
+    data.truncate(data.len() - mp4.moov.get_size() as usize);
+    let free_start = data.len() as u64;
+    data.extend_from_slice(&(8u32 + 16).to_be_bytes());
+    data.extend_from_slice(b"free");
+    data.extend_from_slice(&[0xA0; 8]);
+    data.extend_from_slice(&[0xA1; 8]);
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    stbl.saiz = Some(mp4::SaizBox {
+        default_sample_info_size: 8,
+        sample_count: 2,
+        ..Default::default()
+    });
+    stbl.saio = Some(mp4::SaioBox {
+        offsets: vec![free_start + 8],
+        ..Default::default()
+    });
+    moov.write_box(&mut data).unwrap();
+
+    // A cbcs fragment whose aux info precedes the sample data in its mdat:
+    // an IV alone, then with one and with two subsamples.
+    let mut aux = Vec::new();
+    aux.extend_from_slice(&[1; 8]);
+    aux.extend_from_slice(&[2; 8]);
+    aux.extend_from_slice(&1u16.to_be_bytes());
+    aux.extend_from_slice(&[0, 16, 0, 0, 0, 32]);
+    aux.extend_from_slice(&[3; 8]);
+    aux.extend_from_slice(&2u16.to_be_bytes());
+    aux.extend_from_slice(&[0, 5, 0, 0, 1, 0, 0, 7, 0, 0, 0, 9]);
+    let mut moof = mp4::MoofBox::default();
+    moof.mfhd.sequence_number = 1;
+    moof.trafs.push(mp4::TrafBox {
+        tfhd: mp4::TfhdBox {
+            flags: mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_DURATION
+                | mp4::TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+            track_id: 1,
+            default_sample_duration: Some(40),
+            ..Default::default()
+        },
+        tfdt: None,
+        trun: Some(mp4::TrunBox {
+            flags: mp4::TrunBox::FLAG_DATA_OFFSET | mp4::TrunBox::FLAG_SAMPLE_SIZE,
+            sample_count: 3,
+            data_offset: Some(0),
+            sample_sizes: vec![1, 1, 1],
+            ..Default::default()
+        }),
+        subs: None,
+        saiz: Some(mp4::SaizBox {
+            version: 0,
+            flags: mp4::SaizBox::FLAG_AUX_INFO_TYPE,
+            aux_info_type: Some(str::parse("cbcs").unwrap()),
+            aux_info_type_parameter: Some(0),
+            default_sample_info_size: 0,
+            sample_count: 3,
+            sample_info_sizes: vec![8, 16, 22],
+        }),
+        saio: Some(mp4::SaioBox {
+            flags: mp4::SaioBox::FLAG_AUX_INFO_TYPE,
+            aux_info_type: Some(str::parse("cbcs").unwrap()),
+            aux_info_type_parameter: Some(0),
+            offsets: vec![0],
+            ..Default::default()
+        }),
+    });
+    let aux_offset = moof.get_size() + 8;
+    let traf = &mut moof.trafs[0];
+    traf.saio.as_mut().unwrap().offsets = vec![aux_offset];
+    traf.trun.as_mut().unwrap().data_offset = Some((aux_offset + aux.len() as u64) as i32);
+    moof.write_box(&mut data).unwrap();
+    data.extend_from_slice(&(8 + aux.len() as u32 + 3).to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&aux);
+    data.extend_from_slice(&[2, 3, 4]);
+
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.sample_count(1).unwrap(), 5);
+
+    let encryption: Vec<mp4::SampleEncryption> = (1..=5)
+        .map(|i| mp4.sample_encryption(1, i, 8).unwrap().unwrap())
+        .collect();
+    let ivs: Vec<&[u8]> = encryption.iter().map(|e| e.iv.as_slice()).collect();
+    assert_eq!(ivs, vec![&[0xA0; 8], &[0xA1; 8], &[1; 8], &[2; 8], &[3; 8]]);
+    assert!(encryption[2].subsamples.is_empty());
+    assert_eq!(
+        encryption[3].subsamples,
+        vec![mp4::SubsampleEncryption {
+            bytes_of_clear_data: 16,
+            bytes_of_protected_data: 32,
+        }]
+    );
+    assert_eq!(
+        encryption[4].subsamples,
+        vec![
+            mp4::SubsampleEncryption {
+                bytes_of_clear_data: 5,
+                bytes_of_protected_data: 256,
+            },
+            mp4::SubsampleEncryption {
+                bytes_of_clear_data: 7,
+                bytes_of_protected_data: 9,
+            },
+        ]
+    );
+    assert_eq!(mp4.read_sample(1, 5).unwrap().unwrap().bytes.as_ref(), &[4]);
+}
+
+#[test]
+fn test_sample_encryption_aux_info_overflow() {
+    let mut writer = start_vp9_writer();
+    let sample = mp4::Mp4Sample {
+        start_time: 0,
+        duration: 40,
+        rendering_offset: 0,
+        is_sync: true,
+        bytes: mp4::Bytes::from(vec![0; 4]),
+    };
+    writer.write_sample(1, &sample).unwrap();
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+
+    // A 64-bit saio offset whose aux info would end past the largest offset.
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let mut moov = mp4.moov.clone();
+    data.truncate(data.len() - mp4.moov.get_size() as usize);
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    stbl.saiz = Some(mp4::SaizBox {
+        default_sample_info_size: 8,
+        sample_count: 1,
+        ..Default::default()
+    });
+    stbl.saio = Some(mp4::SaioBox {
+        version: 1,
+        offsets: vec![u64::MAX - 4],
+        ..Default::default()
+    });
+    moov.write_box(&mut data).unwrap();
+
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert!(matches!(
+        mp4.sample_encryption(1, 1, 8),
+        Err(mp4::Error::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_sync_samples() {
+    let config = isom_config();
+    let sample = |i: u64| mp4::Mp4Sample {
+        start_time: i * 40,
+        duration: 40,
+        rendering_offset: 0,
+        is_sync: i.is_multiple_of(60),
+        bytes: mp4::Bytes::from(vec![0; 4]),
+    };
+    let track_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+
+    let mut writer = mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer.add_track(&track_config).unwrap();
+    for i in 0..180 {
+        writer.write_sample(1, &sample(i)).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sync_samples(), vec![1, 61, 121]);
+    assert_eq!(track.keyframe_count(), 3);
+
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer.add_track(&track_config).unwrap();
+    for i in 0..180u64 {
+        if i.is_multiple_of(45) {
+            writer.start_fragment().unwrap();
+        }
+        writer.add_sample(1, &sample(i)).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = mp4.tracks().get(&1).unwrap();
+    assert_eq!(track.sync_samples(), vec![1, 61, 121]);
+    assert_eq!(track.keyframe_count(), 3);
+}
+
+#[test]
+fn test_write_media_header_fields() {
+    let mut writer = start_writer();
+    let mut video_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+    video_config.graphics_mode = 0x0100; // dither copy
+    video_config.op_color = mp4::RgbColor {
+        red: 0x8000,
+        green: 0x4000,
+        blue: 0x2000,
+    };
+    writer.add_track(&video_config).unwrap();
+    let mut audio_config = mp4::TrackConfig::from(mp4::AacConfig::default());
+    audio_config.balance = mp4::FixedPointI8::new_raw(-0x80);
+    writer.add_track(&audio_config).unwrap();
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let vmhd = mp4.tracks()[&1].trak.mdia.minf.vmhd.as_ref().unwrap();
+    assert_eq!(vmhd.graphics_mode, video_config.graphics_mode);
+    assert_eq!(vmhd.op_color, video_config.op_color);
+    let smhd = mp4.tracks()[&2].trak.mdia.minf.smhd.as_ref().unwrap();
+    assert_eq!(smhd.balance.raw_value(), -0x80);
+}
+
+#[test]
+fn test_read_box_size_too_small() {
+    let mut data = std::fs::read("tests/samples/minimal.mp4").unwrap();
+    data.extend_from_slice(&[0, 0, 0, 3]);
+    data.extend_from_slice(b"free");
+    let size = data.len() as u64;
+    assert!(matches!(
+        Mp4Reader::read_header(Cursor::new(data), size),
+        Err(mp4::Error::InvalidData(_))
+    ));
+}
+
+#[test]
+fn test_tracks_by_type() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::AacConfig::default()))
+        .unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    // A thumbnail track after the main video track.
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config {
+            width: 160,
+            height: 90,
+            ..Default::default()
+        }))
+        .unwrap();
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let video_ids: Vec<u32> = mp4
+        .tracks_by_type(TrackType::Video)
+        .iter()
+        .map(|track| track.track_id())
+        .collect();
+    assert_eq!(video_ids, vec![2, 3]);
+    assert_eq!(mp4.video_track().unwrap().track_id(), 2);
+    assert_eq!(mp4.audio_track().unwrap().track_id(), 1);
+    assert!(mp4.tracks_by_type(TrackType::Subtitle).is_empty());
+}
+
+#[test]
+fn test_tapt_round_trip() {
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("qt  ").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![str::parse("qt  ").unwrap()],
+        timescale: 600,
+    };
+    let mut writer = mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config {
+            width: 1440,
+            height: 1080,
+            ..Default::default()
+        }))
+        .unwrap();
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let mut moov = mp4.moov.clone();
+    data.truncate(data.len() - mp4.moov.get_size() as usize);
+    let moov_start = data.len();
+    // 1888.5x1062 clean aperture, 1920x1080 production aperture.
+    moov.traks[0].tapt = Some(mp4::TaptBox {
+        clef: Some(mp4::ClefBox {
+            width: mp4::FixedPointU16::new_raw(0x0760_8000),
+            height: mp4::FixedPointU16::new(1062),
+            ..Default::default()
+        }),
+        prof: Some(mp4::ProfBox {
+            width: mp4::FixedPointU16::new(1920),
+            height: mp4::FixedPointU16::new(1080),
+            ..Default::default()
+        }),
+        enof: Some(mp4::EnofBox {
+            width: mp4::FixedPointU16::new(1440),
+            height: mp4::FixedPointU16::new(1080),
+            ..Default::default()
+        }),
+    });
+    moov.write_box(&mut data).unwrap();
+
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let tapt = mp4.moov.traks[0].tapt.as_ref().unwrap();
+    assert_eq!(tapt, moov.traks[0].tapt.as_ref().unwrap());
+    assert_eq!(tapt.clef.as_ref().unwrap().width.raw_value(), 0x0760_8000);
+
+    let mut buf = Vec::new();
+    mp4.moov.write_box(&mut buf).unwrap();
+    assert_eq!(buf, data[moov_start..]);
+}
+
+#[test]
+fn test_update_track_header() {
+    let mut writer = start_writer();
+    for language in ["eng", "fra"] {
+        let mut track_config = mp4::TrackConfig::from(mp4::AacConfig::default());
+        track_config.language = language.to_string();
+        writer.add_track(&track_config).unwrap();
+    }
+    for track_id in 1..=2 {
+        let sample = mp4::Mp4Sample {
+            start_time: 0,
+            duration: 1024,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![track_id as u8; 8]),
+        };
+        writer.write_sample(track_id, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+
+    let mut file = Cursor::new(data.clone());
+    for track_id in 1..=2 {
+        mp4::repack::update_track_header(&mut file, size, track_id, |tkhd| {
+            tkhd.alternate_group = 1;
+            tkhd.set_enabled(track_id == 1);
+        })
+        .unwrap();
+    }
+    assert!(mp4::repack::update_track_header(&mut file, size, 3, |_| {}).is_err());
+    let edited = file.into_inner();
+    assert_eq!(edited.len(), data.len());
+
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(edited), size).unwrap();
+    let eng = &mp4.tracks()[&1];
+    assert!(eng.is_enabled());
+    assert_eq!(eng.alternate_group(), 1);
+    let fra = &mp4.tracks()[&2];
+    assert!(!fra.is_enabled());
+    assert_eq!(fra.alternate_group(), 1);
+    assert_eq!(fra.trak.tkhd.flags & 1, 0);
+    let sample = mp4.read_sample(2, 1).unwrap().unwrap();
+    assert_eq!(sample.bytes, mp4::Bytes::from(vec![2; 8]));
+}
+
+#[test]
+fn test_read_padded_chunks() {
+    let mut writer = start_vp9_writer();
+    let sizes = [3, 4, 5, 6, 7];
+    for (i, size) in sizes.iter().enumerate() {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i as u8 + 1; *size]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    // Lay the samples out in chunks of 2, 2 and 1 samples, with padding
+    // before and between them.
+    let mut body = Vec::new();
+    let mut chunk_offsets = Vec::new();
+    let ftyp_size = mp4.ftyp.get_size();
+    for (chunk, padding) in [(1..3, 2), (3..5, 5), (5..6, 3)] {
+        body.extend_from_slice(&vec![0xFF; padding]);
+        chunk_offsets.push(ftyp_size + 8 + body.len() as u64);
+        for id in chunk {
+            body.extend_from_slice(&vec![id as u8; sizes[id - 1]]);
+        }
+    }
+    let mut moov = mp4.moov.clone();
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    stbl.stsc.entries = vec![
+        mp4::StscEntry {
+            first_chunk: 1,
+            samples_per_chunk: 2,
+            sample_description_index: 1,
+            first_sample: 1,
+        },
+        mp4::StscEntry {
+            first_chunk: 3,
+            samples_per_chunk: 1,
+            sample_description_index: 1,
+            first_sample: 5,
+        },
+    ];
+    stbl.stco = None;
+    stbl.co64 = Some(mp4::Co64Box {
+        version: 0,
+        flags: 0,
+        entries: chunk_offsets,
+    });
+
+    let mut data = Vec::new();
+    mp4.ftyp.write_box(&mut data).unwrap();
+    data.extend_from_slice(&(8 + body.len() as u32).to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&body);
+    moov.write_box(&mut data).unwrap();
+
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    for id in 1..=5u32 {
+        let sample = mp4.read_sample(1, id).unwrap().unwrap();
+        assert_eq!(
+            sample.bytes,
+            mp4::Bytes::from(vec![id as u8; sizes[id as usize - 1]])
+        );
+    }
+}
+
+#[test]
+fn test_probe() {
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let size = data.len() as u64;
+    let info = mp4::probe(&mut Cursor::new(&data), size).unwrap();
+    let mp4 = Mp4Reader::read_header(Cursor::new(&data), size).unwrap();
+
+    assert_eq!(info.major_brand, *mp4.major_brand());
+    assert_eq!(info.compatible_brands, mp4.compatible_brands());
+    assert_eq!(info.timescale, mp4.timescale());
+    assert_eq!(info.duration.as_millis(), mp4.duration().as_millis());
+    assert!(!info.is_fragmented);
+    assert_eq!(info.tracks.len(), mp4.tracks().len());
+    for track_info in info.tracks.iter() {
+        let track = &mp4.tracks()[&track_info.track_id];
+        assert_eq!(track_info.handler_type, track.handler_type());
+        assert_eq!(track_info.box_type, track.box_type().ok());
+        assert_eq!(track_info.media_type, track.media_type().ok());
+        assert_eq!(track_info.width, track.width());
+        assert_eq!(track_info.height, track.height());
+        assert_eq!(track_info.language, track.language());
+        assert_eq!(track_info.duration, track.duration());
+        assert_eq!(track_info.sample_count, track.sample_count());
+    }
+    assert_eq!(info.tracks[0].media_type, Some(MediaType::H264));
+    assert_eq!(info.tracks[0].width, 320);
+
+    let data = write_fragmented(2, 3);
+    let info = mp4::probe(&mut Cursor::new(&data), data.len() as u64).unwrap();
+    assert!(info.is_fragmented);
+    assert_eq!(info.tracks[0].media_type, Some(MediaType::VP9));
+    assert_eq!(info.tracks[0].sample_count, 0);
+}
+
+#[test]
+fn test_probe_bad_box_sizes() {
+    let mut ftyp = vec![0, 0, 0, 24];
+    ftyp.extend_from_slice(b"ftypisom\0\0\x02\0isommp41");
+
+    // A largesize running past the largest offset, and a largesize header
+    // cut short.
+    let mut oversized = ftyp.clone();
+    oversized.extend_from_slice(&[0, 0, 0, 1]);
+    oversized.extend_from_slice(b"moov");
+    oversized.extend_from_slice(&0xFFFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+    let mut truncated = ftyp.clone();
+    truncated.extend_from_slice(&[0, 0, 0, 1]);
+    truncated.extend_from_slice(b"moov");
+    truncated.extend_from_slice(&[0, 0, 0, 0]);
+
+    for data in [oversized, truncated].iter() {
+        let size = data.len() as u64;
+        assert!(mp4::probe(&mut Cursor::new(data), size).is_err());
+        let mut out = Vec::new();
+        assert!(mp4::dump::tree(&mut Cursor::new(data), size, &mut out).is_err());
+    }
+}
+
+#[test]
+fn test_total_bitrate() {
+    let data = write_fragmented(2, 3);
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    // Two mdat boxes of three 16-byte samples each.
+    let mdat_size = 2 * (8 + 3 * 16);
+    let boxes_size = mp4.ftyp.get_size()
+        + mp4.moov.get_size()
+        + mp4.moofs.iter().map(|moof| moof.get_size()).sum::<u64>();
+    assert_eq!(mp4.overhead_bytes(), boxes_size);
+    assert_eq!(mp4.overhead_bytes(), size - mdat_size);
+    // Six samples of 40 ms.
+    assert_eq!(mp4.total_bitrate(), (mdat_size * 8 * 1000 / 240) as u32);
+
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert!(mp4.overhead_bytes() >= mp4.ftyp.get_size() + mp4.moov.get_size());
+    assert!(mp4.overhead_bytes() < size);
+    assert!(mp4.total_bitrate() > 0);
+}
+
+#[test]
+fn test_write_alternate_audio_languages() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig {
+            language: String::from("eng"),
+            alternate_group: 1,
+            ..mp4::TrackConfig::from(mp4::AacConfig::default())
+        })
+        .unwrap();
+    writer
+        .add_track(&mp4::TrackConfig {
+            language: String::from("fra"),
+            extended_language: Some(String::from("fr-CA")),
+            alternate_group: 1,
+            is_default: false,
+            ..mp4::TrackConfig::from(mp4::AacConfig::default())
+        })
+        .unwrap();
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    let english = &mp4.tracks()[&1];
+    assert_eq!(english.language(), "eng");
+    assert_eq!(english.extended_language(), None);
+    assert_eq!(english.alternate_group(), 1);
+    assert!(english.is_enabled());
+
+    let french = &mp4.tracks()[&2];
+    assert_eq!(french.language(), "fra");
+    assert_eq!(french.extended_language(), Some("fr-CA"));
+    assert_eq!(french.alternate_group(), 1);
+    assert!(!french.is_enabled());
+}
+
+#[test]
+fn test_copyright() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::AacConfig::default()))
+        .unwrap();
+    writer.set_copyright("deu", "© 2024 Müller GmbH");
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert_eq!(
+        mp4.copyright(),
+        Some((String::from("deu"), String::from("© 2024 Müller GmbH")))
+    );
+
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.copyright(), None);
+}
+
+#[test]
+fn test_reserve_free_space() {
+    let writer_config = mp4::Mp4WriterConfig {
+        reserved_free_space: 64 * 1024,
+    };
+    let mut writer = mp4::Mp4Writer::write_start_with_config(
+        Cursor::new(Vec::new()),
+        &isom_config(),
+        &writer_config,
+    )
+    .unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::AacConfig::default()))
+        .unwrap();
+    let sample = mp4::Mp4Sample {
+        start_time: 0,
+        duration: 1024,
+        rendering_offset: 0,
+        is_sync: true,
+        bytes: mp4::Bytes::from(vec![7; 8]),
+    };
+    writer.write_sample(1, &sample).unwrap();
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+
+    // The moov and a free box after it fill the room ahead of the mdat.
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), size).unwrap();
+    let ftyp_size = mp4.ftyp.get_size() as usize;
+    let moov_size = mp4.moov.get_size();
+    assert_eq!(&data[ftyp_size + 4..ftyp_size + 8], b"moov");
+    let mdat_start = ftyp_size + 64 * 1024;
+    assert_eq!(&data[mdat_start + 4..mdat_start + 8], b"mdat");
+    assert_eq!(mp4.overhead_bytes(), ftyp_size as u64 + 64 * 1024);
+
+    let notice = "x".repeat(1000);
+    let mut file = Cursor::new(data.clone());
+    mp4::repack::update_moov(&mut file, size, |moov| {
+        moov.udta = Some(mp4::UdtaBox {
+            cprt: Some(mp4::CprtBox::new("eng", &notice)),
+            ..Default::default()
+        });
+    })
+    .unwrap();
+    let edited = file.into_inner();
+    assert_eq!(edited.len(), data.len());
+    assert_eq!(&edited[mdat_start..], &data[mdat_start..]);
+
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(edited.clone()), size).unwrap();
+    assert_eq!(mp4.copyright(), Some((String::from("eng"), notice)));
+    assert!(mp4.moov.get_size() > moov_size);
+    assert_eq!(mp4.size(), size);
+    let sample = mp4.read_sample(1, 1).unwrap().unwrap();
+    assert_eq!(sample.bytes, mp4::Bytes::from(vec![7; 8]));
+
+    // More than the free space left.
+    let mut file = Cursor::new(edited);
+    let result = mp4::repack::update_moov(&mut file, size, |moov| {
+        moov.udta = Some(mp4::UdtaBox {
+            cprt: Some(mp4::CprtBox::new("eng", &"x".repeat(64 * 1024))),
+            ..Default::default()
+        });
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_update_moov_at_end() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::AacConfig::default()))
+        .unwrap();
+    let sample = mp4::Mp4Sample {
+        start_time: 0,
+        duration: 1024,
+        rendering_offset: 0,
+        is_sync: true,
+        bytes: mp4::Bytes::from(vec![7; 8]),
+    };
+    writer.write_sample(1, &sample).unwrap();
+    writer.write_end().unwrap();
+    let mut data = writer.into_writer().into_inner();
+
+    // A vendor box at the end of the trailing moov, which the crate skips.
+    let mp4 = Mp4Reader::read_header(Cursor::new(data.clone()), data.len() as u64).unwrap();
+    let moov_start = data.len() - mp4.moov.get_size() as usize;
+    let vendor = b"\0\0\0\x0cxtra\x01\x02\x03\x04";
+    data.extend_from_slice(vendor);
+    let moov_size = (data.len() - moov_start) as u32;
+    data[moov_start..moov_start + 4].copy_from_slice(&moov_size.to_be_bytes());
+    let size = data.len() as u64;
+
+    // The moov is the last box, so it grows past the end of the file.
+    let notice = "x".repeat(1000);
+    let mut file = Cursor::new(data.clone());
+    mp4::repack::update_moov(&mut file, size, |moov| {
+        moov.udta = Some(mp4::UdtaBox {
+            cprt: Some(mp4::CprtBox::new("eng", &notice)),
+            ..Default::default()
+        });
+    })
+    .unwrap();
+    let edited = file.into_inner();
+    assert!(edited.len() > data.len());
+    assert_eq!(&edited[..moov_start], &data[..moov_start]);
+    assert!(edited.ends_with(vendor));
+
+    let size = edited.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(edited), size).unwrap();
+    assert_eq!(mp4.copyright(), Some((String::from("eng"), notice)));
+    assert_eq!(
+        mp4.moov.get_size() + vendor.len() as u64,
+        size - moov_start as u64
+    );
+    let sample = mp4.read_sample(1, 1).unwrap().unwrap();
+    assert_eq!(sample.bytes, mp4::Bytes::from(vec![7; 8]));
+}
+
+#[test]
+fn test_read_metadata_samples() {
+    // GPMF payloads: a DEVC container holding a DVID of the sample index.
+    let payloads: Vec<Vec<u8>> = (0..3u32)
+        .map(|i| {
+            let mut klv = b"DEVC\0\x01\0\x0cDVIDL\0\x04\0\x01".to_vec();
+            klv.extend_from_slice(&i.to_be_bytes());
+            klv
+        })
+        .collect();
+
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("mp41").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![str::parse("mp41").unwrap()],
+        timescale: 1000,
+    };
+    let mut writer = mp4::Mp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for (i, payload) in payloads.iter().enumerate() {
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 1001,
+            duration: 1001,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(payload.clone()),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+
+    // Turn the track into a GoPro metadata track, whose gpmd sample entry has
+    // no fields of its own.
+    let mut file = Cursor::new(data);
+    mp4::repack::update_moov(&mut file, size, |moov| {
+        let trak = &mut moov.traks[0];
+        trak.tkhd.set_width(0);
+        trak.tkhd.set_height(0);
+        trak.mdia.hdlr.handler_type = str::parse("meta").unwrap();
+        trak.mdia.hdlr.name = String::from("GoPro MET");
+        trak.mdia.minf.vmhd = None;
+        trak.mdia.minf.nmhd = Some(mp4::NmhdBox::default());
+        let stsd = &mut trak.mdia.minf.stbl.stsd;
+        stsd.vp09 = None;
+        stsd.extra_entries = vec![mp4::SampleEntry::Unknown(
+            mp4::BoxType::from(u32::from_be_bytes(*b"gpmd")),
+            vec![0, 0, 0, 0, 0, 0, 0, 1],
+        )];
+    })
+    .unwrap();
+
+    let data = file.into_inner();
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    let track = &mp4.tracks()[&1];
+    assert_eq!(track.handler_type(), mp4::HandlerType::Metadata);
+    assert!(track.trak.mdia.minf.nmhd.is_some());
+    assert_eq!(track.metadata_content_type(), None);
+
+    let samples = mp4.read_metadata_samples(1).unwrap();
+    assert_eq!(samples.len(), 3);
+    for (i, (time, payload)) in samples.iter().enumerate() {
+        assert_eq!(*time, Duration::from_millis(i as u64 * 1001));
+        assert_eq!(payload, &payloads[i]);
+    }
+
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert!(mp4.read_metadata_samples(1).is_err());
+}
+
+#[test]
+fn test_stpp_round_trip() {
+    let documents: Vec<String> = (0..4)
+        .map(|i| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><tt xmlns="http://www.w3.org/ns/ttml" xml:lang="fr"><body><div><p begin="{i}s" end="{}s">Réplique {i}</p></div></body></tt>"#,
+                i + 1
+            )
+        })
+        .collect();
+    let stpp_config = mp4::StppConfig {
+        namespace: String::from(
+            "http://www.w3.org/ns/ttml http://www.w3.org/ns/ttml/profile/imsc1/text",
+        ),
+        ..Default::default()
+    };
+
+    let config = mp4::Mp4Config {
+        major_brand: str::parse("iso6").unwrap(),
+        minor_version: 0,
+        compatible_brands: vec![str::parse("iso6").unwrap(), str::parse("im1t").unwrap()],
+        timescale: 1000,
+    };
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &config).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig {
+            language: String::from("fra"),
+            ..mp4::TrackConfig::from(stpp_config.clone())
+        })
+        .unwrap();
+    for (i, document) in documents.iter().enumerate() {
+        if i % 2 == 0 {
+            writer.start_fragment().unwrap();
+        }
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 1000,
+            duration: 1000,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(document.clone().into_bytes()),
+        };
+        writer.add_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+    assert_eq!(mp4.moofs.len(), 2);
+    let track = &mp4.tracks()[&1];
+    assert_eq!(track.handler_type(), mp4::HandlerType::Subtitle);
+    assert_eq!(track.track_type().unwrap(), TrackType::Subtitle);
+    assert_eq!(track.media_type().unwrap(), MediaType::STPP);
+    assert_eq!(track.stpp_config().unwrap(), stpp_config);
+    assert!(track.trak.mdia.minf.sthd.is_some());
+    assert_eq!(track.sample_count(), 4);
+    for (i, document) in documents.iter().enumerate() {
+        let xml = mp4.read_xml_sample(1, i as u32 + 1).unwrap().unwrap();
+        assert_eq!(&xml, document);
+    }
+}
+
+#[test]
+fn test_parse_zero_size_child() {
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let mp4 = mp4::parse(&data).unwrap();
+    assert!(!mp4.tracks().is_empty());
+
+    // A moov, then a udta in it, holding a child of size 0, which leaves
+    // the position at its own header once skipped.
+    let ftyp = [
+        0, 0, 0, 16, b'f', b't', b'y', b'p', b'i', b's', b'o', b'm', 0, 0, 2, 0,
+    ];
+    let zero = [0, 0, 0, 0, b'z', b'e', b'r', b'o'];
+    let mut moov = ftyp.to_vec();
+    moov.extend_from_slice(&[0, 0, 0, 16, b'm', b'o', b'o', b'v']);
+    moov.extend_from_slice(&zero);
+    let mut udta = ftyp.to_vec();
+    udta.extend_from_slice(&[0, 0, 0, 24, b'm', b'o', b'o', b'v']);
+    udta.extend_from_slice(&[0, 0, 0, 16, b'u', b'd', b't', b'a']);
+    udta.extend_from_slice(&zero);
+    for data in [moov, udta] {
+        assert!(matches!(mp4::parse(&data), Err(mp4::Error::InvalidData(_))));
+    }
+}
+
+#[test]
+fn test_write_track_header_config() {
+    let mut writer = start_writer();
+    writer
+        .add_track(&mp4::TrackConfig {
+            layer: -1,
+            volume: mp4::FixedPointU8::new_raw(0x0080),
+            ..mp4::TrackConfig::from(mp4::AacConfig::default())
+        })
+        .unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config {
+            width: 320,
+            height: 240,
+            ..Default::default()
+        }))
+        .unwrap();
+    writer.write_end().unwrap();
+
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+
+    let audio = &mp4.tracks()[&1];
+    assert_eq!(audio.layer(), -1);
+    assert_eq!(audio.volume().raw_value(), 0x0080);
+    assert_eq!(audio.trak.tkhd.layer, 0xFFFF);
+    assert_eq!(audio.matrix(), &mp4::Matrix::default());
+
+    let video = &mp4.tracks()[&2];
+    assert_eq!(video.layer(), 0);
+    assert_eq!(video.volume().raw_value(), 0);
+}
+
+#[test]
+fn test_file_level_meta_round_trip() {
+    // A file-level meta with a pict hdlr, an idat and a mime property.
+    let mut meta = vec![0, 0, 0, 80, b'm', b'e', b't', b'a', 0, 0, 0, 0];
+    meta.extend_from_slice(&[0, 0, 0, 33, b'h', b'd', b'l', b'r', 0, 0, 0, 0, 0, 0, 0, 0]);
+    meta.extend_from_slice(b"pict");
+    meta.extend_from_slice(&[0; 13]);
+    meta.extend_from_slice(&[0, 0, 0, 12, b'i', b'd', b'a', b't', 1, 2, 3, 4]);
+    meta.extend_from_slice(&[0, 0, 0, 23, b'm', b'i', b'm', b'e', 0, 0, 0, 0]);
+    meta.extend_from_slice(b"image/jpeg\0");
+    assert_eq!(meta.len(), 80);
+
+    let mut data = fs::read("tests/samples/minimal.mp4").unwrap();
+    data.extend_from_slice(&meta);
+    let mp4 = mp4::parse(&data).unwrap();
+    let file_meta = mp4.meta.clone().unwrap();
+    match &file_meta {
+        mp4::MetaBox::Unknown { data, .. } => match &data[..] {
+            [mp4::MetaChild::Raw(name, body), mp4::MetaChild::Mime(mime)] => {
+                assert_eq!(*name, mp4::BoxType::from(u32::from_be_bytes(*b"idat")));
+                assert_eq!(body, &[1, 2, 3, 4]);
+                assert_eq!(mime.content_type, "image/jpeg");
+            }
+            _ => panic!("expected an idat then a mime"),
+        },
+        _ => panic!("expected a pict meta box"),
+    }
+
+    let mut buf = Vec::new();
+    file_meta.write_box(&mut buf).unwrap();
+    assert_eq!(buf, meta);
+
+    // The writer reproduces it.
+    let mut writer = start_writer();
+    writer.set_meta(file_meta);
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    assert!(data.ends_with(&meta));
+}
+
+#[test]
+fn test_file_level_meta_unparsable() {
+    // A file-level meta without a hdlr, followed by a free box.
+    let mut data = fs::read("tests/samples/minimal.mp4").unwrap();
+    data.extend_from_slice(&[0, 0, 0, 24, b'm', b'e', b't', b'a', 0, 0, 0, 0]);
+    data.extend_from_slice(&[0, 0, 0, 12, b'i', b'd', b'a', b't', 1, 2, 3, 4]);
+    data.extend_from_slice(&[0, 0, 0, 8, b'f', b'r', b'e', b'e']);
+
+    let mp4 = mp4::parse(&data).unwrap();
+    assert!(mp4.meta.is_none());
+    assert_eq!(mp4.size(), data.len() as u64);
+    assert_eq!(mp4.tracks().len(), 2);
+}
+
+#[test]
+fn test_sample_size_stats() {
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let mut mp4 = mp4::parse(&data).unwrap();
+    for track_id in [1, 2] {
+        let sample_count = mp4.sample_count(track_id).unwrap();
+        let sizes: Vec<u64> = (1..=sample_count)
+            .map(|id| mp4.read_sample(track_id, id).unwrap().unwrap().bytes.len() as u64)
+            .collect();
+        let track = &mp4.tracks()[&track_id];
+        assert_eq!(track.max_sample_size() as u64, *sizes.iter().max().unwrap());
+        assert_eq!(
+            track.average_sample_size(),
+            sizes.iter().sum::<u64>() / sizes.len() as u64
+        );
+    }
+
+    // Samples of one size are written as a constant stsz.
+    let mut writer = start_vp9_writer();
+    for i in 0..3 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; 20]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let mp4 = mp4::parse(&data).unwrap();
+    let track = &mp4.tracks()[&1];
+    assert_eq!(track.trak.mdia.minf.stbl.stsz.sample_size, 20);
+    assert_eq!(track.max_sample_size(), 20);
+    assert_eq!(track.average_sample_size(), 20);
+
+    // Fragment samples come from the truns of every fragment, the largest
+    // in the second.
+    let mut writer = mp4::FMp4Writer::write_start(Cursor::new(Vec::new()), &iso6_config()).unwrap();
+    writer
+        .add_track(&mp4::TrackConfig::from(mp4::Vp9Config::default()))
+        .unwrap();
+    for (i, &size) in [10, 20, 30, 5, 45, 10].iter().enumerate() {
+        if i % 3 == 0 {
+            writer.start_fragment().unwrap();
+        }
+        let sample = mp4::Mp4Sample {
+            start_time: i as u64 * 40,
+            duration: 40,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![0; size]),
+        };
+        writer.add_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let mp4 = mp4::parse(&data).unwrap();
+    let track = &mp4.tracks()[&1];
+    assert_eq!(track.max_sample_size(), 45);
+    assert_eq!(track.average_sample_size(), 20);
+}
+
+#[test]
+fn test_location() {
+    let location = mp4::GeoLocation::from_iso6709("+37.7749-122.4194/").unwrap();
+    assert_eq!(location.lat, 37.7749);
+    assert_eq!(location.lon, -122.4194);
+    assert_eq!(location.altitude, None);
+
+    // Degrees, minutes and seconds, with an altitude and a CRS.
+    let location = mp4::GeoLocation::from_iso6709("-334512.5+1511230+12.5CRSWGS_84/").unwrap();
+    assert!((location.lat + (33.0 + 45.0 / 60.0 + 12.5 / 3600.0)).abs() < 1e-9);
+    assert!((location.lon - (151.0 + 12.0 / 60.0 + 30.0 / 3600.0)).abs() < 1e-9);
+    assert_eq!(location.altitude, Some(12.5));
+
+    assert_eq!(mp4::GeoLocation::from_iso6709("+37.7749/"), None);
+    assert_eq!(mp4::GeoLocation::from_iso6709("+97.0000+000.0000/"), None);
+
+    for altitude in [None, Some(-12.25)] {
+        let location = mp4::GeoLocation {
+            lat: 37.7749,
+            lon: -122.4194,
+            altitude,
+        };
+        let mut writer = start_writer();
+        writer.set_location(location);
+        writer.write_end().unwrap();
+
+        let data = writer.into_writer().into_inner();
+        let mp4 = mp4::parse(&data).unwrap();
+        assert_eq!(mp4.location(), Some(location));
+    }
+
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    assert_eq!(mp4::parse(&data).unwrap().location(), None);
+}
+
+#[test]
+fn test_sample_iter_all() {
+    let data = fs::read("tests/samples/minimal.mp4").unwrap();
+    let mut mp4 = mp4::parse(&data).unwrap();
+    let sample_count = mp4.sample_count(1).unwrap() + mp4.sample_count(2).unwrap();
+
+    let mut order = Vec::new();
+    for sample in mp4.sample_iter_all().collect::<Vec<_>>() {
+        order.push(sample.unwrap().0);
+    }
+    assert_eq!(order.len(), sample_count as usize);
+    assert!(order.contains(&1) && order.contains(&2));
+
+    // Each track's samples come in order, and all offsets ascend.
+    let mut next_ids = [1, 1];
+    let mut last_offset = 0;
+    for track_id in order {
+        let sample_id = next_ids[track_id as usize - 1];
+        next_ids[track_id as usize - 1] += 1;
+        let offset = mp4.sample_offset(track_id, sample_id).unwrap();
+        assert!(offset >= last_offset);
+        last_offset = offset;
+    }
+
+    let data = write_fragmented(2, 3);
+    let mut mp4 = mp4::parse(&data).unwrap();
+    let samples: Vec<_> = mp4.sample_iter_all().map(|s| s.unwrap()).collect();
+    assert_eq!(samples.len(), 6);
+    for (i, (track_id, sample)) in samples.iter().enumerate() {
+        assert_eq!(*track_id, 1);
+        assert_eq!(sample.start_time, i as u64 * 40);
+    }
+}
+
+#[test]
+fn test_trex_defaults() {
+    let data = write_fragmented(1, 3);
+    let mp4 = mp4::parse(&data).unwrap();
+
+    // Move every per-sample value but the first sample's flags into the trex.
+    let mut moov = mp4.moov.clone();
+    let trex = &mut moov.mvex.as_mut().unwrap().trexs[0];
+    trex.default_sample_description_index = 1;
+    trex.default_sample_duration = 40;
+    trex.default_sample_size = 16;
+    trex.default_sample_flags = 0x01010000;
+
+    let mut moof = mp4.moofs[0].clone();
+    let traf = &mut moof.trafs[0];
+    traf.tfhd.flags &= !(mp4::TfhdBox::FLAG_SAMPLE_DESCRIPTION_INDEX
+        | mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_DURATION
+        | mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_SIZE
+        | mp4::TfhdBox::FLAG_DEFAULT_SAMPLE_FLAGS);
+    traf.tfhd.sample_description_index = None;
+    traf.tfhd.default_sample_duration = None;
+    traf.tfhd.default_sample_size = None;
+    traf.tfhd.default_sample_flags = None;
+    let trun = traf.trun.as_mut().unwrap();
+    trun.flags = mp4::TrunBox::FLAG_DATA_OFFSET | mp4::TrunBox::FLAG_FIRST_SAMPLE_FLAGS;
+    trun.first_sample_flags = Some(0x02000000);
+    trun.sample_durations.clear();
+    trun.sample_sizes.clear();
+    trun.sample_flags.clear();
+    trun.sample_cts.clear();
+    let data_offset = moof.get_size() + 8;
+    moof.trafs[0].trun.as_mut().unwrap().data_offset = Some(data_offset as i32);
+
+    let mut data = Vec::new();
+    mp4.ftyp.write_box(&mut data).unwrap();
+    moov.write_box(&mut data).unwrap();
+
+    // The init segment alone already has the defaults.
+    let init = mp4::parse(&data).unwrap();
+    let trex = init.tracks()[&1].trex().unwrap();
+    assert_eq!(trex.default_sample_duration, 40);
+
+    moof.write_box(&mut data).unwrap();
+    data.extend_from_slice(&[0, 0, 0, 56, b'm', b'd', b'a', b't']);
+    for i in 0..3 {
+        data.extend_from_slice(&[i; 16]);
+    }
+
+    let mut mp4 = mp4::parse(&data).unwrap();
+    let trex = mp4.tracks()[&1].trex().unwrap().clone();
+    assert_eq!(trex.default_sample_duration, 40);
+    assert_eq!(mp4.tracks()[&1].sample_description_index(2).unwrap(), 1);
+    assert_eq!(mp4.tracks()[&1].max_sample_size(), 16);
+    for i in 0..3 {
+        let sample = mp4.read_sample(1, i + 1).unwrap().unwrap();
+        assert_eq!(sample.start_time, i as u64 * 40);
+        assert_eq!(sample.duration, 40);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8; 16]));
+        assert_eq!(sample.is_sync, i == 0);
+    }
+}
+
+#[test]
+fn test_sample_summary() {
+    let sample = mp4::Mp4Sample {
+        start_time: 93843,
+        duration: 3003,
+        rendering_offset: -1501,
+        is_sync: false,
+        bytes: mp4::Bytes::from(vec![0; 1234]),
+    };
+    assert_eq!(
+        sample.summary(90000),
+        "start_time 1.042700s, duration 0.033367s, rendering_offset -0.016678s, non-sync, length 1234"
+    );
+
+    let sample = mp4::Mp4Sample {
+        is_sync: true,
+        ..sample
+    };
+    assert_eq!(
+        sample.summary(0),
+        "start_time 93843, duration 3003, rendering_offset -1501, is_sync true, length 1234"
+    );
+}
+
+#[test]
+fn test_package_segment() {
+    // 10s of 40ms video samples with a keyframe every 1.2s, alongside 20ms
+    // audio samples.
+    let mut writer = start_writer();
+    let mut video_config = mp4::TrackConfig::from(mp4::Vp9Config::default());
+    video_config.timescale = 1000;
+    writer.add_track(&video_config).unwrap();
+    let mut audio_config = mp4::TrackConfig::from(mp4::AacConfig::default());
+    audio_config.timescale = 1000;
+    writer.add_track(&audio_config).unwrap();
+    for i in 0..500u64 {
+        if i % 2 == 0 {
+            let sample = mp4::Mp4Sample {
+                start_time: i * 20,
+                duration: 40,
+                rendering_offset: 0,
+                is_sync: i % 60 == 0,
+                bytes: mp4::Bytes::from(vec![1, i as u8]),
+            };
+            writer.write_sample(1, &sample).unwrap();
+        }
+        let sample = mp4::Mp4Sample {
+            start_time: i * 20,
+            duration: 20,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![2, i as u8]),
+        };
+        writer.write_sample(2, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+
+    let mut init = None;
+    let mut segments = Vec::new();
+    mp4::package::segment(
+        Cursor::new(&data),
+        size,
+        Duration::from_secs(2),
+        |s| match s {
+            mp4::package::Segment::Init(bytes) => init = Some(bytes),
+            mp4::package::Segment::Media(segment) => segments.push(segment),
+        },
+    )
+    .unwrap();
+
+    // Boundaries at 2, 4, 6 and 8s snap to the keyframes at 2.4, 3.6, 6.0
+    // and 8.4s.
+    let starts: Vec<u64> = segments
+        .iter()
+        .map(|s| s.start_time.as_millis() as u64)
+        .collect();
+    assert_eq!(starts, [0, 2400, 3600, 6000, 8400]);
+    let durations: Vec<u64> = segments
+        .iter()
+        .map(|s| s.duration.as_millis() as u64)
+        .collect();
+    assert_eq!(durations, [2400, 1200, 2400, 2400, 1600]);
+    let sequence_numbers: Vec<u32> = segments.iter().map(|s| s.sequence_number).collect();
+    assert_eq!(sequence_numbers, [1, 2, 3, 4, 5]);
+
+    let mut fmp4 = init.unwrap();
+    for segment in segments.iter() {
+        fmp4.extend_from_slice(&segment.data);
+    }
+    let size = fmp4.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(fmp4), size).unwrap();
+    assert!(mp4.is_fragmented());
+    assert!(mp4
+        .ftyp
+        .compatible_brands
+        .contains(&str::parse("cmfc").unwrap()));
+    assert_eq!(mp4.moofs.len(), 5);
+    for (moof, start) in mp4.moofs.iter().zip(starts.iter()) {
+        let video = moof.trafs.iter().find(|t| t.tfhd.track_id == 1).unwrap();
+        assert_eq!(video.tfdt.as_ref().unwrap().base_media_decode_time, *start);
+        let audio = moof.trafs.iter().find(|t| t.tfhd.track_id == 2).unwrap();
+        assert_eq!(audio.tfdt.as_ref().unwrap().base_media_decode_time, *start);
+    }
+
+    assert_eq!(mp4.sample_count(1).unwrap(), 250);
+    assert_eq!(mp4.sample_count(2).unwrap(), 500);
+    for &start in starts.iter() {
+        let sample = mp4.read_sample(1, start as u32 / 40 + 1).unwrap().unwrap();
+        assert!(sample.is_sync);
+        assert_eq!(sample.start_time, start);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![1, (start / 20) as u8]));
+    }
+    let audio = mp4.read_sample(2, 500).unwrap().unwrap();
+    assert_eq!(audio.start_time, 9980);
+    assert_eq!(audio.bytes, mp4::Bytes::from(vec![2, (499 & 0xff) as u8]));
+}
+
+#[test]
+fn test_package_segment_sample_descriptions() {
+    // Four 1s video samples, one per chunk, the last two using a second
+    // sample description.
+    let mut writer = start_vp9_writer();
+    for i in 0..4u64 {
+        let sample = mp4::Mp4Sample {
+            start_time: i * 1000,
+            duration: 1000,
+            rendering_offset: 0,
+            is_sync: true,
+            bytes: mp4::Bytes::from(vec![i as u8; 4]),
+        };
+        writer.write_sample(1, &sample).unwrap();
+    }
+    writer.write_end().unwrap();
+    let data = writer.into_writer().into_inner();
+    let size = data.len() as u64;
+    let mut moov = Mp4Reader::read_header(Cursor::new(&data), size)
+        .unwrap()
+        .moov;
+
+    // The writer puts the moov last.
+    let moov_start = data.len() - moov.get_size() as usize;
+    let stbl = &mut moov.traks[0].mdia.minf.stbl;
+    let vp09 = stbl.stsd.vp09.clone().unwrap();
+    stbl.stsd.extra_entries.push(mp4::SampleEntry::Vp09(vp09));
+    stbl.stsc.entries = vec![
+        mp4::StscEntry {
+            first_chunk: 1,
+            samples_per_chunk: 1,
+            sample_description_index: 1,
+            first_sample: 1,
+        },
+        mp4::StscEntry {
+            first_chunk: 3,
+            samples_per_chunk: 1,
+            sample_description_index: 2,
+            first_sample: 3,
+        },
+    ];
+    let mut data = data[..moov_start].to_vec();
+    moov.write_box(&mut data).unwrap();
+    let size = data.len() as u64;
+
+    let mut fmp4 = Vec::new();
+    mp4::package::segment(
+        Cursor::new(&data),
+        size,
+        Duration::from_secs(3),
+        |s| match s {
+            mp4::package::Segment::Init(bytes) => fmp4.extend_from_slice(&bytes),
+            mp4::package::Segment::Media(segment) => fmp4.extend_from_slice(&segment.data),
+        },
+    )
+    .unwrap();
+
+    // The first segment holds samples 1 to 3, in a traf per description.
+    let size = fmp4.len() as u64;
+    let mut mp4 = Mp4Reader::read_header(Cursor::new(fmp4), size).unwrap();
+    let trafs: Vec<Vec<Option<u32>>> = mp4
+        .moofs
+        .iter()
+        .map(|moof| {
+            moof.trafs
+                .iter()
+                .map(|traf| traf.tfhd.sample_description_index)
+                .collect()
+        })
+        .collect();
+    assert_eq!(trafs, [vec![None, Some(2)], vec![Some(2)]]);
+    let track = &mp4.tracks()[&1];
+    let indices: Vec<u32> = (1..=4)
+        .map(|id| track.sample_description_index(id).unwrap())
+        .collect();
+    assert_eq!(indices, [1, 1, 2, 2]);
+    for i in 0..4u32 {
+        let sample = mp4.read_sample(1, i + 1).unwrap().unwrap();
+        assert_eq!(sample.start_time, i as u64 * 1000);
+        assert_eq!(sample.bytes, mp4::Bytes::from(vec![i as u8; 4]));
+    }
+}
+
+#[test]
+fn test_he_aac_default_extension_freq() {
+    for (freq_index, output) in [
+        (SampleFreqIndex::Freq24000, 48000),
+        (SampleFreqIndex::Freq64000, 64000),
+    ] {
+        let mut writer = start_writer();
+        writer
+            .add_track(&mp4::TrackConfig::from(mp4::AacConfig {
+                freq_index,
+                sbr_present: true,
+                ..Default::default()
+            }))
+            .unwrap();
+        writer.write_end().unwrap();
+
+        let data = writer.into_writer().into_inner();
+        let size = data.len() as u64;
+        let mp4 = Mp4Reader::read_header(Cursor::new(data), size).unwrap();
+        let track = mp4.tracks().get(&1).unwrap();
+        let aac_config = track.aac_config().unwrap();
+        assert_eq!(aac_config.freq_index, freq_index);
+        assert!(aac_config.sbr_present);
+        assert_eq!(aac_config.output_sample_freq(), output);
+        assert_eq!(track.sample_rate().unwrap(), output);
+    }
+}